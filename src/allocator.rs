@@ -51,6 +51,14 @@ impl Allocator {
     }
 }
 
+impl AllocationGuard {
+    pub fn page_addresses(&self) -> Vec<u64> {
+        let granularity = self.allocator.0.lock().unwrap().granularity as u64;
+        let base = self.memory.as_ptr().unwrap() as u64;
+        (0..self.memory.len() / granularity).map(|page| base + page * granularity).collect()
+    }
+}
+
 impl Drop for AllocationGuard {
     fn drop(&mut self) {
         let mut allocator = self.allocator.0.lock().unwrap();
@@ -1,16 +1,184 @@
+use std::fs::OpenOptions;
+use std::io::{Seek, SeekFrom, Write};
 use std::path::PathBuf;
+use std::time::{Duration, Instant};
 
-use clap::Parser;
+use anyhow::anyhow;
+use clap::{Parser, Subcommand};
+use clap_num::maybe_hex;
 
-use mlx5cmd::cmdif::CmdIf;
-use mlx5cmd::registers::flash::MFBA;
-use mlx5cmd::registers::flash::MFPA;
 use mlx5cmd::cmdif::vfio::VfioCmdIf;
+use mlx5cmd::flash::{Flash, MAX_MFBA_DATA, READ_CHUNK};
+
+const DUMP_BLOCK: u32 = 0x1000;
+const VERIFY_RETRIES: usize = 3;
 
 #[derive(Parser, Debug)]
 struct CliArgs {
     #[arg(short, long, default_value = "/sys/bus/pci/devices/0000:04:00.0")]
     device: PathBuf,
+
+    /// Flash select
+    #[arg(long, default_value_t = 0)]
+    fs: usize,
+
+    /// Log erases and writes without performing them
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Bytes per MFBA read
+    #[arg(long, value_parser=maybe_hex::<usize>, default_value_t = READ_CHUNK)]
+    chunk_size: usize,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Dump the flash, the default
+    Dump(DumpArgs),
+    /// Write a file, erasing and rewriting only the sectors that change
+    Write {
+        input: PathBuf,
+        #[arg(long, value_parser=maybe_hex::<u32>, default_value = "0")]
+        address: u32,
+        #[arg(long)]
+        no_verify: bool,
+    },
+    /// Erase the sectors covering a range
+    Erase {
+        #[arg(long, value_parser=maybe_hex::<u32>)]
+        address: u32,
+        #[arg(long, value_parser=maybe_hex::<u32>)]
+        len: u32,
+    },
+    /// Compare flash contents with a file
+    Verify {
+        input: PathBuf,
+        #[arg(long, value_parser=maybe_hex::<u32>, default_value = "0")]
+        address: u32,
+    },
+}
+
+#[derive(Parser, Debug)]
+struct DumpArgs {
+    #[arg(short, long, default_value = "flash_data")]
+    output: PathBuf,
+
+    #[arg(long, value_parser=maybe_hex::<u32>, default_value = "0")]
+    start: u32,
+
+    /// End address, the flash size reported by MFPA if not given
+    #[arg(long, value_parser=maybe_hex::<u32>)]
+    end: Option<u32>,
+
+    /// Continue a partial dump in the output file
+    #[arg(long)]
+    resume: bool,
+
+    /// Read every block twice and retry blocks that differ
+    #[arg(long)]
+    verify: bool,
+
+    /// Sync the output file to disk every this many bytes
+    #[arg(long, value_parser=maybe_hex::<u32>, default_value = "0x10000")]
+    checkpoint: u32,
+}
+
+struct Progress {
+    started: Instant,
+    last_report: Instant,
+    resumed: u64,
+    total: u64,
+}
+
+impl Progress {
+    fn new(resumed: u64, total: u64) -> Self {
+        Self {
+            started: Instant::now(),
+            last_report: Instant::now(),
+            resumed,
+            total,
+        }
+    }
+
+    fn update(&mut self, done: u64, address: u32) {
+        if self.last_report.elapsed() < Duration::from_millis(500) && done < self.total {
+            return;
+        }
+        self.last_report = Instant::now();
+
+        let rate = (done - self.resumed) as f64 / self.started.elapsed().as_secs_f64().max(1e-3);
+        let eta = (self.total - done) as f64 / rate.max(1.0);
+        eprint!(
+            "\r{:6.2}% {address:#010x} {:8.1} KiB/s ETA {:5.0}s",
+            100.0 * done as f64 / self.total.max(1) as f64,
+            rate / 1024.0,
+            eta
+        );
+        if done >= self.total {
+            eprintln!();
+        }
+    }
+}
+
+fn read_block(flash: &Flash<VfioCmdIf>, address: u32, len: usize, verify: bool) -> anyhow::Result<Vec<u8>> {
+    let mut data = flash.read(address, len)?;
+    if !verify {
+        return Ok(data);
+    }
+    for _ in 0..VERIFY_RETRIES {
+        let again = flash.read(address, len)?;
+        if again == data {
+            return Ok(data);
+        }
+        log::warn!("Reads of {len:#x} bytes at {address:#x} differ, retrying");
+        data = again;
+    }
+    Err(anyhow!("Reads at {address:#x} did not stabilize after {VERIFY_RETRIES} retries"))
+}
+
+fn dump(flash: &Flash<VfioCmdIf>, args: DumpArgs) -> anyhow::Result<()> {
+    let end = match args.end {
+        Some(end) => end,
+        None => {
+            let size = flash.size().ok_or_else(|| anyhow!("Unknown flash size, pass --end"))?;
+            u32::try_from(size).map_err(|_| anyhow!("Flash size {size:#x} does not fit the 32 bit address space"))?
+        }
+    };
+
+    let mut output = OpenOptions::new().create(true).write(true).truncate(false).open(&args.output)?;
+    let done = if args.resume {
+        (output.metadata()?.len() as u32 / DUMP_BLOCK * DUMP_BLOCK).min(end.saturating_sub(args.start))
+    } else {
+        0
+    };
+    output.set_len(done as u64)?;
+    output.seek(SeekFrom::Start(done as u64))?;
+
+    let mut address = args.start + done;
+    if done != 0 {
+        log::info!("Resuming at {address:#x}");
+    }
+
+    let mut progress = Progress::new(done as u64, end.saturating_sub(args.start) as u64);
+    let mut since_checkpoint = 0;
+    while address < end {
+        let len = (end - address).min(DUMP_BLOCK);
+        output.write_all(&read_block(flash, address, len as usize, args.verify)?)?;
+        address += len;
+
+        since_checkpoint += len;
+        if since_checkpoint >= args.checkpoint {
+            output.sync_data()?;
+            since_checkpoint = 0;
+        }
+        progress.update((address - args.start) as u64, address);
+    }
+    output.sync_data()?;
+
+    Ok(())
 }
 
 fn main() -> anyhow::Result<()> {
@@ -18,23 +186,29 @@ fn main() -> anyhow::Result<()> {
     let args = CliArgs::parse();
 
     let cmdif = VfioCmdIf::open_from_sysfs(&args.device, true, true)?;
+    let mut flash = Flash::open(&cmdif, args.fs, args.dry_run)?;
+    flash.read_chunk = args.chunk_size.clamp(4, MAX_MFBA_DATA);
+    log::info!("{:x?}", flash.mfpa);
 
-    let mut flash_data = vec![];
-
-    log::info!("{:x?}", cmdif.read_register(MFPA::default(), 0)?);
-    for address in (0..16*1024*1024).step_by(0x40) {
-        if address & 0xffff == 0 {
-            log::info!("{:x}", address);
+    match args.command.unwrap_or_else(|| Command::Dump(DumpArgs::parse_from(["dump"]))) {
+        Command::Dump(dump_args) => dump(&flash, dump_args)?,
+        Command::Write {
+            input,
+            address,
+            no_verify,
+        } => {
+            let data = std::fs::read(input)?;
+            flash.write(address, &data, !no_verify)?;
+        }
+        Command::Erase { address, len } => {
+            flash.erase(address..address + len)?;
+        }
+        Command::Verify { input, address } => {
+            let data = std::fs::read(input)?;
+            flash.verify(address, &data)?;
+            log::info!("Flash matches {:#x} bytes at {address:#x}", data.len());
         }
-        let chunk = cmdif.read_register(MFBA {
-            size: 0x40,
-            address: address,
-            ..MFBA::default()
-        }, 0)?.data;
-        flash_data.extend_from_slice(&chunk);
     }
 
-    std::fs::write("flash_data", flash_data)?;
-
     Ok(())
-}
\ No newline at end of file
+}
@@ -0,0 +1,90 @@
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand};
+use clap_num::maybe_hex;
+
+use mlx5cmd::fwimage::{FwImage, TocEntry};
+
+#[derive(Parser, Debug)]
+struct CliArgs {
+    /// Flash dump, e.g. the flash_data written by flash-access
+    image: PathBuf,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// List hardware pointers and sections, the default
+    List {
+        #[arg(long)]
+        json: bool,
+    },
+    /// Write sections to files named after their type and address
+    Extract {
+        /// Section name or type, all sections if not given
+        section: Vec<String>,
+
+        #[arg(short, long, default_value = ".")]
+        outdir: PathBuf,
+    },
+}
+
+fn matches(entry: &TocEntry, selector: &str) -> bool {
+    entry.name.eq_ignore_ascii_case(selector) || maybe_hex::<u8>(selector).is_ok_and(|section_type| section_type == entry.section_type)
+}
+
+fn main() -> anyhow::Result<()> {
+    env_logger::init();
+    let args = CliArgs::parse();
+
+    let flash = std::fs::read(&args.image)?;
+    let image = FwImage::parse(&flash)?;
+
+    match args.command.unwrap_or(Command::List { json: false }) {
+        Command::List { json: true } => {
+            println!("{}", serde_json::to_string_pretty(&image)?);
+        }
+        Command::List { json: false } => {
+            println!("Image at {:#x}", image.image_start);
+            if let Some(info) = &image.image_info {
+                println!("FW version {} PSID {}", info.fw_version, info.psid);
+            }
+            for pointer in &image.hw_pointers {
+                println!("  {:<24} {:#010x}{}", pointer.name, pointer.ptr, if pointer.crc_ok { "" } else { " (bad crc)" });
+            }
+            for entry in image.sections() {
+                let crc = match entry.section_crc_ok(&flash) {
+                    Some(true) => "crc ok",
+                    Some(false) => "crc BAD",
+                    None => "no crc",
+                };
+                println!(
+                    "  {:<24} {:#010x} {:#9x} {crc}{}",
+                    entry.name,
+                    entry.flash_addr,
+                    entry.size,
+                    if entry.entry_crc_ok { "" } else { " (bad entry crc)" }
+                );
+            }
+        }
+        Command::Extract { section, outdir } => {
+            std::fs::create_dir_all(&outdir)?;
+            let selected = image
+                .sections()
+                .filter(|entry| section.is_empty() || section.iter().any(|selector| matches(entry, selector)));
+            for entry in selected {
+                let Some(data) = entry.data(&flash) else {
+                    log::warn!("{} at {:#x} is outside of the image", entry.name, entry.flash_addr);
+                    continue;
+                };
+                let path = outdir.join(format!("{}_{:08x}.bin", entry.name, entry.flash_addr));
+                std::fs::write(&path, data)?;
+                println!("{}", path.display());
+            }
+        }
+    }
+
+    Ok(())
+}
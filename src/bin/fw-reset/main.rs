@@ -0,0 +1,53 @@
+use std::path::PathBuf;
+
+use clap::Parser;
+
+use mlx5cmd::cmdif::vfio::VfioCmdIf;
+use mlx5cmd::cmdif::CmdIf;
+use mlx5cmd::registers::mfrl::{MFRL, MFRL_RESET_TYPE_FULL_CHIP, MFRL_RESET_TYPE_NET_PORT_ALIVE};
+use mlx5cmd::registers::mgir::MGIR;
+
+#[derive(Parser, Debug)]
+struct CliArgs {
+    #[arg(short, long, default_value = "/sys/bus/pci/devices/0000:04:00.0")]
+    device: PathBuf,
+
+    /// Only print the supported reset levels and types and the reset state
+    #[arg(long)]
+    query: bool,
+
+    /// Keep the network link up during the reset
+    #[arg(long)]
+    keep_link: bool,
+}
+
+fn main() -> anyhow::Result<()> {
+    env_logger::init();
+    let args = CliArgs::parse();
+
+    // A crashed firmware fails initialization, the reset below recovers it anyway
+    let mut cmdif = VfioCmdIf::open_from_sysfs(&args.device, true, false)?;
+    if let Err(err) = cmdif.initialize() {
+        log::warn!("Could not initialize the device: {err}");
+    }
+
+    if args.query {
+        let mfrl = cmdif.read_register(MFRL::default(), 0)?;
+        println!("Reset levels: {:#04x}", mfrl.reset_level);
+        println!("Reset types:  {:#04x}", mfrl.reset_type);
+        println!("Reset state:  {:?}", mfrl.state());
+        return Ok(());
+    }
+
+    let rst_type_sel = if args.keep_link {
+        MFRL_RESET_TYPE_NET_PORT_ALIVE
+    } else {
+        MFRL_RESET_TYPE_FULL_CHIP
+    };
+    cmdif.firmware_reset(rst_type_sel)?;
+
+    let mgir = cmdif.read_register(MGIR::default(), 0)?;
+    println!("Firmware {} is up", mgir.fw_version());
+
+    Ok(())
+}
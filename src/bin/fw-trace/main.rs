@@ -0,0 +1,65 @@
+use std::fs::OpenOptions;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use std::thread::sleep;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use clap::Parser;
+
+use mlx5cmd::cmdif::vfio::VfioCmdIf;
+use mlx5cmd::tracer::{FwTracer, TraceMessage};
+
+#[derive(Parser, Debug)]
+struct CliArgs {
+    #[arg(short, long, default_value = "/sys/bus/pci/devices/0000:04:00.0")]
+    device: PathBuf,
+
+    /// Trace ring size as log2 of 4k pages
+    #[arg(long, default_value_t = 6)]
+    log_pages: u8,
+
+    #[arg(long, default_value_t = 100)]
+    interval_ms: u64,
+
+    /// Stop after this many seconds instead of running until killed
+    #[arg(long)]
+    duration: Option<u64>,
+
+    /// Append to this file instead of writing to stdout
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+fn write_messages(output: &mut impl Write, messages: Vec<TraceMessage>) -> anyhow::Result<()> {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?;
+    for message in messages {
+        writeln!(output, "{}.{:06} {message}", now.as_secs(), now.subsec_micros())?;
+    }
+    output.flush()?;
+    Ok(())
+}
+
+fn main() -> anyhow::Result<()> {
+    env_logger::init();
+    let args = CliArgs::parse();
+
+    let cmdif = VfioCmdIf::open_from_sysfs(&args.device, true, true)?;
+
+    let mut output: Box<dyn Write> = match &args.output {
+        Some(path) => Box::new(BufWriter::new(OpenOptions::new().create(true).append(true).open(path)?)),
+        None => Box::new(std::io::stdout().lock()),
+    };
+
+    let mut tracer = FwTracer::start(&cmdif, args.log_pages)?;
+    log::info!("Tracing into {} blocks", tracer.num_blocks());
+
+    let deadline = args.duration.map(|secs| Instant::now() + Duration::from_secs(secs));
+    while !deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+        write_messages(&mut output, tracer.poll()?)?;
+        sleep(Duration::from_millis(args.interval_ms));
+    }
+
+    write_messages(&mut output, tracer.stop()?)?;
+
+    Ok(())
+}
@@ -0,0 +1,91 @@
+use std::path::PathBuf;
+
+use anyhow::bail;
+use clap::Parser;
+use clap_num::maybe_hex;
+
+use mlx5cmd::cmdif::vfio::VfioCmdIf;
+use mlx5cmd::cmdif::CmdIf;
+use mlx5cmd::fw_update::{flash_component, query_capabilities, query_components, UpdateStage, COMPONENT_BOOT_IMG};
+use mlx5cmd::fwimage::FwImage;
+use mlx5cmd::registers::mgir::MGIR;
+
+#[derive(Parser, Debug)]
+struct CliArgs {
+    #[arg(short, long, default_value = "/sys/bus/pci/devices/0000:04:00.0")]
+    device: PathBuf,
+
+    /// Component identifier, 1 is the firmware image
+    #[arg(long, value_parser=maybe_hex::<u16>, default_value_t = COMPONENT_BOOT_IMG)]
+    component: u16,
+
+    /// Update even if the image PSID does not match the device
+    #[arg(long)]
+    force: bool,
+
+    /// Leave the new component pending instead of activating it
+    #[arg(long)]
+    no_activate: bool,
+
+    /// Only list the device components
+    #[arg(long)]
+    list: bool,
+
+    image: Option<PathBuf>,
+}
+
+fn main() -> anyhow::Result<()> {
+    env_logger::init();
+    let args = CliArgs::parse();
+
+    let cmdif = VfioCmdIf::open_from_sysfs(&args.device, true, true)?;
+
+    if args.list {
+        for component in query_components(&cmdif)? {
+            let cap = query_capabilities(&cmdif, component.component_index)?;
+            println!(
+                "{:2} identifier {:#06x} status {:#x} update state {:#x} max size {:#x}",
+                component.component_index,
+                component.identifier,
+                component.component_status,
+                component.component_update_state,
+                cap.max_component_size
+            );
+        }
+        return Ok(());
+    }
+
+    let Some(image_path) = args.image else {
+        bail!("No image given");
+    };
+    let data = std::fs::read(&image_path)?;
+
+    if args.component == COMPONENT_BOOT_IMG {
+        let device_psid = cmdif.read_register(MGIR::default(), 0)?.psid();
+        match FwImage::parse(&data) {
+            Ok(FwImage {
+                image_info: Some(info), ..
+            }) => {
+                log::info!("Image version {} PSID {}, device PSID {device_psid}", info.fw_version, info.psid);
+                if info.psid != device_psid && !args.force {
+                    bail!("Image PSID {} does not match device PSID {device_psid}, use --force to update anyway", info.psid);
+                }
+            }
+            Ok(_) => log::warn!("Image has no image info section, not checking the PSID"),
+            Err(err) => log::warn!("Could not parse image, not checking the PSID: {err}"),
+        }
+    }
+
+    let mut last_stage: Option<UpdateStage> = None;
+    flash_component(&cmdif, args.component, &data, !args.no_activate, |stage, done, total| {
+        if last_stage.is_some_and(|last_stage| last_stage != stage) {
+            eprintln!();
+        }
+        last_stage = Some(stage);
+        eprint!("\r{stage:?}: {:3}%", 100 * done / total.max(1));
+    })?;
+    eprintln!();
+    log::info!("Update of component {:#x} done", args.component);
+
+    Ok(())
+}
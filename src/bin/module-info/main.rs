@@ -0,0 +1,94 @@
+use std::path::PathBuf;
+
+use anyhow::anyhow;
+use clap::Parser;
+use clap_num::maybe_hex;
+
+use mlx5cmd::cmdif::vfio::VfioCmdIf;
+use mlx5cmd::sff::{module_info, read_page, write_eeprom, I2C_ADDRESS_LOW};
+
+#[derive(Parser, Debug)]
+struct CliArgs {
+    #[arg(short, long, default_value = "/sys/bus/pci/devices/0000:04:00.0")]
+    device: PathBuf,
+
+    #[arg(short, long, default_value_t = 0)]
+    module: u8,
+
+    /// Print the decoded module information as JSON
+    #[arg(long)]
+    json: bool,
+
+    /// Hex dump an EEPROM page instead of decoding it
+    #[arg(long)]
+    dump: bool,
+
+    #[arg(long, value_parser=maybe_hex::<u8>, default_value_t = I2C_ADDRESS_LOW)]
+    i2c_address: u8,
+
+    #[arg(long, value_parser=maybe_hex::<u8>, default_value = "0")]
+    page: u8,
+
+    /// Write hex bytes to the EEPROM at this offset of the page
+    #[arg(long, value_parser=maybe_hex::<usize>, requires = "write_data")]
+    write_offset: Option<usize>,
+
+    #[arg(long)]
+    write_data: Option<String>,
+}
+
+fn parse_hex(data: &str) -> anyhow::Result<Vec<u8>> {
+    if data.len() % 2 != 0 {
+        return Err(anyhow!("Odd number of hex digits in {data}"));
+    }
+    (0..data.len())
+        .step_by(2)
+        .map(|index| u8::from_str_radix(&data[index..index + 2], 16).map_err(|err| anyhow!("{data}: {err}")))
+        .collect()
+}
+
+fn main() -> anyhow::Result<()> {
+    env_logger::init();
+    let args = CliArgs::parse();
+
+    let cmdif = VfioCmdIf::open_from_sysfs(&args.device, true, true)?;
+
+    if let (Some(offset), Some(data)) = (args.write_offset, &args.write_data) {
+        let data = parse_hex(data)?;
+        write_eeprom(&cmdif, args.module, args.i2c_address, args.page, offset, &data)?;
+        log::info!("Wrote {:#x} bytes at {offset:#x}", data.len());
+        return Ok(());
+    }
+
+    if args.dump {
+        let page = read_page(&cmdif, args.module, args.i2c_address, args.page)?;
+        for (line, chunk) in page.chunks(0x10).enumerate() {
+            println!("{:04x}: {:02x?}", line * 0x10, chunk);
+        }
+        return Ok(());
+    }
+
+    let info = module_info(&cmdif, args.module)?;
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&info)?);
+        return Ok(());
+    }
+
+    println!("Module {}: {} ({:#04x})", args.module, info.identifier_name, info.identifier);
+    println!("  Vendor:     {} ({})", info.vendor_name, info.vendor_oui);
+    println!("  Part:       {} rev {}", info.part_number, info.revision);
+    println!("  Serial:     {}", info.serial_number);
+    println!("  Date code:  {}", info.date_code);
+    if let Some(diagnostics) = &info.diagnostics {
+        println!("  Temperature {:.2} C, Vcc {:.3} V", diagnostics.temperature, diagnostics.vcc);
+        for (lane, bias) in diagnostics.tx_bias.iter().enumerate() {
+            println!(
+                "  Lane {lane}: tx bias {bias:.3} mA tx power {:.4} mW rx power {:.4} mW",
+                diagnostics.tx_power.get(lane).unwrap_or(&0.0),
+                diagnostics.rx_power.get(lane).unwrap_or(&0.0)
+            );
+        }
+    }
+
+    Ok(())
+}
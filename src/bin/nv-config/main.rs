@@ -0,0 +1,150 @@
+use std::path::PathBuf;
+
+use anyhow::anyhow;
+use clap::{Parser, Subcommand, ValueEnum};
+use clap_num::maybe_hex;
+
+use mlx5cmd::cmdif::vfio::VfioCmdIf;
+use mlx5cmd::nvconfig::{find_param, query_support, read_item, read_param, reset, stored_items, write_params, NV_PARAMS};
+use mlx5cmd::registers::nvconfig::{
+    config_item_type, ACCESS_MODE_CURRENT, ACCESS_MODE_DEFAULT, ACCESS_MODE_NEXT, MAX_CONFIG_ITEM_DATA,
+};
+
+#[derive(Parser, Debug)]
+struct CliArgs {
+    #[arg(short, long, default_value = "/sys/bus/pci/devices/0000:04:00.0")]
+    device: PathBuf,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Show the default, current and next boot values of parameters, all known ones by default
+    Query { names: Vec<String> },
+    /// Set parameters for the next boot, given as NAME=VALUE
+    Set { assignments: Vec<String> },
+    /// Restore the default configuration from the next boot
+    Reset,
+    /// List the configuration items stored on the device
+    List,
+    /// Read a configuration item by type
+    Raw {
+        #[arg(long, value_parser=maybe_hex::<u8>, default_value = "0")]
+        class: u8,
+        #[arg(long, value_parser=maybe_hex::<u32>)]
+        index: u32,
+        #[arg(long, default_value_t = 0)]
+        port: u8,
+        #[arg(long, value_parser=maybe_hex::<usize>, default_value = "0x10")]
+        len: usize,
+        #[arg(long, value_enum, default_value_t = Access::Next)]
+        access: Access,
+    },
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy)]
+enum Access {
+    Next,
+    Current,
+    Default,
+}
+
+impl Access {
+    fn mode(self) -> u8 {
+        match self {
+            Access::Next => ACCESS_MODE_NEXT,
+            Access::Current => ACCESS_MODE_CURRENT,
+            Access::Default => ACCESS_MODE_DEFAULT,
+        }
+    }
+}
+
+fn main() -> anyhow::Result<()> {
+    env_logger::init();
+    let args = CliArgs::parse();
+
+    let cmdif = VfioCmdIf::open_from_sysfs(&args.device, true, true)?;
+
+    match args.command.unwrap_or(Command::Query { names: vec![] }) {
+        Command::Query { names } => {
+            let params = if names.is_empty() {
+                NV_PARAMS.iter().collect()
+            } else {
+                names
+                    .iter()
+                    .map(|name| find_param(name).ok_or_else(|| anyhow!("Unknown parameter {name}")))
+                    .collect::<anyhow::Result<Vec<_>>>()?
+            };
+
+            println!("{:<28} {:<16} {:<16} {:<16}", "Parameter", "Default", "Current", "Next boot");
+            for param in params {
+                let mut values = vec![];
+                for access in [Access::Default, Access::Current, Access::Next] {
+                    match read_param(&cmdif, param, access.mode()) {
+                        Ok(value) => values.push(param.format_value(value)),
+                        Err(err) => {
+                            log::debug!("{} {access:?}: {err}", param.name);
+                            values.push("-".to_string());
+                        }
+                    }
+                }
+                println!("{:<28} {:<16} {:<16} {:<16}", param.name, values[0], values[1], values[2]);
+            }
+        }
+        Command::Set { assignments } => {
+            let mut params = vec![];
+            for assignment in &assignments {
+                let (name, value) = assignment
+                    .split_once('=')
+                    .ok_or_else(|| anyhow!("Expected NAME=VALUE, got {assignment}"))?;
+                let param = find_param(name).ok_or_else(|| anyhow!("Unknown parameter {name}"))?;
+                let value = param
+                    .parse_value(value)
+                    .ok_or_else(|| anyhow!("Invalid value {value} for {}", param.name))?;
+                params.push((param, value));
+            }
+            write_params(&cmdif, &params)?;
+            log::info!("Configuration applies from the next boot");
+        }
+        Command::Reset => {
+            reset(&cmdif)?;
+            log::info!("Default configuration applies from the next boot");
+        }
+        Command::List => {
+            for item in stored_items(&cmdif)? {
+                let len = (item.header.length as usize).min(item.data.len());
+                let name = NV_PARAMS
+                    .iter()
+                    .find(|param| param.item_type() == item.header.item_type)
+                    .map(|param| param.name)
+                    .unwrap_or("");
+                println!(
+                    "{:#010x} writer {:#x} {:02x?} {name}",
+                    item.header.item_type,
+                    item.header.writer_id,
+                    &item.data[..len]
+                );
+            }
+        }
+        Command::Raw {
+            class,
+            index,
+            port,
+            len,
+            access,
+        } => {
+            let item_type = config_item_type(class, port, index);
+            let support = query_support(&cmdif, item_type)?;
+            log::info!("{support:x?}");
+            let item = read_item(&cmdif, item_type, len.min(MAX_CONFIG_ITEM_DATA), access.mode())?;
+            println!("{:x?}", item.header);
+            for (line, chunk) in item.data.chunks(0x10).enumerate() {
+                println!("{:04x}: {:02x?}", line * 0x10, chunk);
+            }
+        }
+    }
+
+    Ok(())
+}
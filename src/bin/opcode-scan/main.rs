@@ -0,0 +1,231 @@
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::anyhow;
+use clap::Parser;
+use clap_num::maybe_hex;
+use serde::{Deserialize, Serialize};
+
+use mlx5cmd::cmdif::vfio::VfioCmdIf;
+use mlx5cmd::cmdif::CmdIf;
+use mlx5cmd::commands::{CommandErrorStatus, RawCommand, RawCommandOutput};
+use mlx5cmd::error::{Error, Result};
+use mlx5cmd::opcodes::{is_destructive, opcode_name};
+
+#[derive(Parser, Debug)]
+struct CliArgs {
+    #[arg(short, long, default_value = "/sys/bus/pci/devices/0000:04:00.0")]
+    device: PathBuf,
+
+    #[arg(long, value_parser=maybe_hex::<u16>, default_value = "0x0")]
+    start: u16,
+
+    #[arg(long, value_parser=maybe_hex::<u16>, default_value = "0x1000")]
+    end: u16,
+
+    /// Scan op_mod values of this opcode instead of the opcode space
+    #[arg(long, value_parser=maybe_hex::<u16>)]
+    opcode: Option<u16>,
+
+    #[arg(long, value_parser=maybe_hex::<u16>, default_value = "0x100")]
+    op_mod_end: u16,
+
+    /// Binary search the smallest accepted input and output lengths
+    #[arg(long)]
+    probe_lengths: bool,
+
+    #[arg(long, value_parser=maybe_hex::<usize>, default_value = "0x1000")]
+    max_len: usize,
+
+    #[arg(long)]
+    include_destructive: bool,
+
+    /// JSON lines report, a header line followed by one line per probed opcode/op_mod
+    #[arg(short, long, default_value = "opcode-scan.jsonl")]
+    output: PathBuf,
+
+    /// Continue a partial scan in the output file, skipping the probes it already holds
+    #[arg(long)]
+    resume: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum Outcome {
+    Ok,
+    Status { status: String, syndrome: u32 },
+    Error { message: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct ScanEntry {
+    opcode: u16,
+    op_mod: u16,
+    name: Option<String>,
+    outcome: Outcome,
+    min_input_len: Option<usize>,
+    min_output_len: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct ScanHeader {
+    device: PathBuf,
+    timestamp: u64,
+    skipped: Vec<u16>,
+}
+
+fn run(cmdif: &impl CmdIf, opcode: u16, op_mod: u16, inlen: usize, outlen: usize) -> Result<RawCommandOutput> {
+    cmdif.do_command(RawCommand::new(opcode, op_mod, vec![0; inlen - 8], outlen))
+}
+
+fn classify(res: &Result<RawCommandOutput>) -> Outcome {
+    match res {
+        Ok(_) => Outcome::Ok,
+        Err(Error::Command { status, syndrome, .. }) => Outcome::Status {
+            status: format!("{status:?}"),
+            syndrome: *syndrome,
+        },
+        Err(err) => Outcome::Error {
+            message: err.to_string(),
+        },
+    }
+}
+
+fn is_status(res: &Result<RawCommandOutput>, expected: CommandErrorStatus) -> bool {
+    matches!(res, Err(Error::Command { status, .. }) if *status == expected)
+}
+
+// Smallest dword aligned length in 8..=max_len that is not rejected with `rejected`
+fn min_len(max_len: usize, rejected: CommandErrorStatus, try_len: impl Fn(usize) -> Result<RawCommandOutput>) -> Option<usize> {
+    if is_status(&try_len(max_len), rejected) {
+        return None;
+    }
+
+    let (mut lo, mut hi) = (8 / 4, max_len / 4);
+    while lo < hi {
+        let mid = (lo + hi) / 2;
+        if is_status(&try_len(mid * 4), rejected) {
+            lo = mid + 1;
+        } else {
+            hi = mid;
+        }
+    }
+    Some(lo * 4)
+}
+
+fn scan(cmdif: &impl CmdIf, args: &CliArgs, opcode: u16, op_mod: u16) -> ScanEntry {
+    let res = run(cmdif, opcode, op_mod, 0x10, 0x10);
+    let outcome = classify(&res);
+    log::info!("{opcode:#05x}/{op_mod:#x}: {outcome:?}");
+
+    let implemented = !is_status(&res, CommandErrorStatus::BadOperation) && matches!(outcome, Outcome::Ok | Outcome::Status { .. });
+    let (min_input_len, min_output_len) = if args.probe_lengths && implemented {
+        (
+            min_len(args.max_len, CommandErrorStatus::BadInputLen, |len| run(cmdif, opcode, op_mod, len, args.max_len)),
+            min_len(args.max_len, CommandErrorStatus::BadOutputLen, |len| run(cmdif, opcode, op_mod, args.max_len, len)),
+        )
+    } else {
+        (None, None)
+    };
+
+    ScanEntry {
+        opcode,
+        op_mod,
+        name: opcode_name(opcode).map(str::to_string),
+        outcome,
+        min_input_len,
+        min_output_len,
+    }
+}
+
+fn write_line(output: &mut File, record: &impl Serialize) -> anyhow::Result<()> {
+    serde_json::to_writer(&mut *output, record)?;
+    output.write_all(b"\n")?;
+    Ok(())
+}
+
+// Reads back a report, dropping a last line cut short by a hung or crashed firmware
+fn read_report(path: &Path) -> anyhow::Result<(ScanHeader, Vec<ScanEntry>)> {
+    let text = std::fs::read_to_string(path)?;
+    let mut lines = text.lines();
+    let header = serde_json::from_str(lines.next().ok_or_else(|| anyhow!("{} is empty", path.display()))?)?;
+    let mut entries = vec![];
+    for (lineno, line) in lines.enumerate() {
+        match serde_json::from_str(line) {
+            Ok(entry) => entries.push(entry),
+            Err(err) => {
+                log::warn!("Dropping line {} of {}: {err}", lineno + 2, path.display());
+                break;
+            }
+        }
+    }
+    Ok((header, entries))
+}
+
+fn main() -> anyhow::Result<()> {
+    env_logger::init();
+    let args = CliArgs::parse();
+
+    let cmdif = VfioCmdIf::open_from_sysfs(&args.device, true, true)?;
+
+    let timestamp = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH)?.as_secs();
+
+    let mut probes: Vec<(u16, u16)> = match args.opcode {
+        Some(opcode) => (0..args.op_mod_end).map(|op_mod| (opcode, op_mod)).collect(),
+        None => (args.start..args.end).map(|opcode| (opcode, 0)).collect(),
+    };
+
+    let mut skipped = vec![];
+    probes.retain(|&(opcode, _)| {
+        let skip = !args.include_destructive && is_destructive(opcode);
+        if skip && !skipped.contains(&opcode) {
+            log::warn!("Skipping destructive opcode {opcode:#x}");
+            skipped.push(opcode);
+        }
+        !skip
+    });
+
+    let (header, mut entries) = if args.resume && args.output.exists() {
+        let (header, entries) = read_report(&args.output)?;
+        log::info!("Resuming with {} probes already done", entries.len());
+        (header, entries)
+    } else {
+        let header = ScanHeader {
+            device: args.device.clone(),
+            timestamp,
+            skipped,
+        };
+        (header, vec![])
+    };
+
+    let mut output = File::create(&args.output)?;
+    write_line(&mut output, &header)?;
+    for entry in &entries {
+        write_line(&mut output, entry)?;
+    }
+
+    let done: HashSet<(u16, u16)> = entries.iter().map(|entry| (entry.opcode, entry.op_mod)).collect();
+    for (opcode, op_mod) in probes.into_iter().filter(|probe| !done.contains(probe)) {
+        let entry = scan(&cmdif, &args, opcode, op_mod);
+        write_line(&mut output, &entry)?;
+        entries.push(entry);
+    }
+
+    entries.sort_by_key(|entry| (entry.opcode, entry.op_mod));
+    let implemented = entries.iter().filter(|entry| {
+        !matches!(&entry.outcome, Outcome::Status { status, .. } if status == "BadOperation")
+    });
+    for entry in implemented {
+        println!(
+            "{:#05x} op_mod {:#x} {:<32} {:?}",
+            entry.opcode,
+            entry.op_mod,
+            entry.name.as_deref().unwrap_or("?"),
+            entry.outcome
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,47 @@
+use std::path::PathBuf;
+
+use clap::Parser;
+use clap_num::maybe_hex;
+
+use mlx5cmd::cmdif::vfio::VfioCmdIf;
+use mlx5cmd::pcie::{read_pcie_counters, read_pcie_info, MPCNT_GROUPS};
+
+#[derive(Parser, Debug)]
+struct CliArgs {
+    #[arg(short, long, default_value = "/sys/bus/pci/devices/0000:04:00.0")]
+    device: PathBuf,
+
+    #[arg(long, default_value_t = 0)]
+    pcie_index: u8,
+
+    /// MPCNT counter groups to print, all known groups by default
+    #[arg(short, long, value_parser=maybe_hex::<u8>)]
+    group: Vec<u8>,
+
+    /// Clear the counters after reading them
+    #[arg(long)]
+    clear: bool,
+}
+
+fn main() -> anyhow::Result<()> {
+    env_logger::init();
+    let args = CliArgs::parse();
+
+    let cmdif = VfioCmdIf::open_from_sysfs(&args.device, true, true)?;
+
+    println!("{}", read_pcie_info(&cmdif, args.pcie_index)?);
+
+    let groups = if args.group.is_empty() {
+        MPCNT_GROUPS.iter().map(|(grp, _)| *grp).collect()
+    } else {
+        args.group
+    };
+    for grp in groups {
+        match read_pcie_counters(&cmdif, args.pcie_index, grp, args.clear) {
+            Ok(mpcnt) => println!("{mpcnt}"),
+            Err(err) => log::warn!("Could not read counter group {grp:#x}: {err}"),
+        }
+    }
+
+    Ok(())
+}
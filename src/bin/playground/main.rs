@@ -13,6 +13,7 @@ use mlx5cmd::allocator::AllocationGuard;
 use mlx5cmd::cmdif::CmdIf;
 use mlx5cmd::commands::QueryEQ;
 use mlx5cmd::{commands::{create_mkey::{AccessMode, CreateMKey, MKeyContext}, AllocPD, AllocUAR, CreateEQ, EQContext, EnableHCA, ExecShellcode64, InitHCA, ManagePages, ManagePagesOpMod, QueryHCACap, QueryISSI, QueryPages, QueryPagesOpMod, SetISSI}, mtcr::{VCR_CMD_ADDR, VCR_CTRL_ADDR}, registers::mtrc::{MtrcCapReg, MtrcConfReg, MtrcCtrlReg}};
+use mlx5cmd::tracer::{StringDb, TraceDecoder, TraceFormat};
 use mlx5cmd::mtcr::{ICmd, AS_CR_SPACE, AS_EXPANSION_ROM, AS_ICMD, AS_ICMD_EXT, AS_NODNIC_INIT_SEG, MTCR};
 use pci_driver::regions::PciMemoryRegion;
 use pci_driver::{backends::vfio::VfioPciDevice, device::PciDevice, regions::PciRegion};
@@ -37,33 +38,7 @@ pub fn clear_allocation(allocation: &AllocationGuard) {
 pub fn create_mtt_mkey(cmdif: &VfioCmdIf, pd: u32, key:u8, pages: usize) -> Result<(u32, AllocationGuard)> {
     let memory = cmdif.dma_allocator.alloc(pages).unwrap();
     clear_allocation(&memory);
-    let mkey_index = cmdif.do_command(CreateMKey {
-        pg_access: false,
-        umem_valid: false,
-        context: MKeyContext {
-            free: false,
-            umr_en: false,
-            a: false,
-            rw: false,
-            rr: false,
-            lw: true,
-            lr: true,
-            access_mode: AccessMode::MTT,
-            qpn: 0xffffff,
-            mkey: key,
-            length64: false,
-            pd: pd,
-            // start_addr: memory.as_ptr().unwrap() as u64,
-            // len: memory.len(),
-            start_addr: 0x0000,
-            len: 0x1000 * pages as u64,
-            bsf_octword_size: 0, 
-            translation_octword_size: (pages as u32) / 2,
-            log_entry_size: 12,
-        },
-        translation_octwords_actual_size: pages as u32 / 2,
-        translation_entries: (0..pages).map(|i| (memory.as_ptr().unwrap() as u64 + 0x1000 * (i as u64)) | 0).collect(),
-    })?.mkey_index;
+    let mkey_index = cmdif.do_command(mlx5cmd::commands::create_mkey::create_mtt_mkey(pd, key, memory.page_addresses()))?.mkey_index;
 
     Ok(((mkey_index << 8) | (key as u32), memory))
 }
@@ -94,6 +69,8 @@ pub fn create_pa_mkey(cmdif: &VfioCmdIf, pd: u32, key: u8, pages: usize) -> Resu
             log_entry_size: 0,
         },
         translation_octwords_actual_size: 0,
+        mkey_umem_id: 0,
+        mkey_umem_offset: 0,
         translation_entries: vec![],
     }))?.mkey_index;
 
@@ -209,6 +186,14 @@ fn main() -> anyhow::Result<()> {
     }
 
     write_region("trace_buffer", *trace_mem)?;
+
+    let trace_cap = cmdif.read_register(MtrcCapReg::default(), 0)?;
+    let mut trace_decoder = TraceDecoder::new(StringDb::read(&cmdif, &trace_cap)?, TraceFormat::from(&trace_cap));
+    let mut trace_buffer = vec![0u8; trace_mem.len() as usize];
+    trace_mem.read_bytes(0, &mut trace_buffer)?;
+    for message in trace_decoder.decode(&trace_buffer) {
+        println!("{message}");
+    }
 //    write_region("pagerequest_eq_buffer", *pagerequest_eq_mem)?;
 
 //    write_region("dma_region", cmdif.dma_allocator.0.lock().unwrap().memory)?;
@@ -0,0 +1,72 @@
+use std::path::PathBuf;
+
+use anyhow::anyhow;
+use clap::Parser;
+use clap_num::maybe_hex;
+
+use mlx5cmd::cmdif::vfio::VfioCmdIf;
+use mlx5cmd::port::{ppcnt_group_name, port_status, read_counters, PPCNT_GROUPS};
+
+#[derive(Parser, Debug)]
+struct CliArgs {
+    #[arg(short, long, default_value = "/sys/bus/pci/devices/0000:04:00.0")]
+    device: PathBuf,
+
+    #[arg(short, long, default_value_t = 1)]
+    port: u8,
+
+    /// PPCNT counter groups to print, all known groups with --all-counters
+    #[arg(short, long, value_parser=maybe_hex::<u8>)]
+    group: Vec<u8>,
+
+    #[arg(long)]
+    all_counters: bool,
+
+    /// Priority or traffic class for the per priority and per traffic class groups
+    #[arg(long, default_value_t = 0)]
+    prio_tc: u8,
+
+    /// Clear the counters after reading them
+    #[arg(long)]
+    clear: bool,
+
+    /// Also print counters that are zero
+    #[arg(long)]
+    zeros: bool,
+}
+
+fn main() -> anyhow::Result<()> {
+    env_logger::init();
+    let args = CliArgs::parse();
+
+    let cmdif = VfioCmdIf::open_from_sysfs(&args.device, true, true)?;
+
+    println!("{}", port_status(&cmdif, args.port)?);
+
+    let groups = if args.all_counters {
+        PPCNT_GROUPS.iter().map(|(grp, _)| *grp).collect()
+    } else {
+        args.group
+    };
+    for grp in groups {
+        let ppcnt = match read_counters(&cmdif, args.port, grp, args.prio_tc, args.clear) {
+            Ok(ppcnt) => ppcnt,
+            Err(err) => {
+                log::warn!("Could not read counter group {grp:#x}: {err}");
+                continue;
+            }
+        };
+        let counters = ppcnt
+            .counters()
+            .ok_or_else(|| anyhow!("Unknown counter group {grp:#x}"))?;
+
+        println!("{} ({grp:#x}):", ppcnt_group_name(grp).unwrap_or("?"));
+        for (name, value) in counters {
+            if value != 0 || args.zeros {
+                println!("  {name:<40} {value}");
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,53 @@
+use std::path::PathBuf;
+
+use clap::Parser;
+
+use mlx5cmd::capabilities::discover_capabilities;
+use mlx5cmd::cmdif::vfio::VfioCmdIf;
+use mlx5cmd::registers::{register_name, REGISTER_NAMES};
+
+#[derive(Parser, Debug)]
+struct CliArgs {
+    #[arg(short, long, default_value = "/sys/bus/pci/devices/0000:04:00.0")]
+    device: PathBuf,
+
+    #[arg(long)]
+    json: bool,
+
+    /// Also list known registers in the covered ranges that the firmware does not report
+    #[arg(long)]
+    missing: bool,
+}
+
+fn main() -> anyhow::Result<()> {
+    env_logger::init();
+    let args = CliArgs::parse();
+
+    let cmdif = VfioCmdIf::open_from_sysfs(&args.device, true, true)?;
+    let capabilities = discover_capabilities(&cmdif)?;
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&capabilities)?);
+        return Ok(());
+    }
+
+    println!("Supported registers:");
+    for &register_id in &capabilities.registers {
+        println!("  {register_id:#06x} {}", register_name(register_id).unwrap_or("?"));
+    }
+
+    if args.missing {
+        println!("Known registers not reported:");
+        for &(register_id, name) in REGISTER_NAMES {
+            if capabilities.is_covered(register_id) && !capabilities.registers.contains(&register_id) {
+                println!("  {register_id:#06x} {name}");
+            }
+        }
+    }
+
+    for (source, features) in &capabilities.features {
+        println!("{source} features: {features:x?}");
+    }
+
+    Ok(())
+}
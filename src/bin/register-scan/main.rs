@@ -0,0 +1,255 @@
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::anyhow;
+use clap::Parser;
+use clap_num::maybe_hex;
+use serde::{Deserialize, Serialize};
+
+use mlx5cmd::capabilities::discover_capabilities;
+use mlx5cmd::cmdif::vfio::VfioCmdIf;
+use mlx5cmd::cmdif::CmdIf;
+use mlx5cmd::commands::access_register::{AccessRegister, AccessRegisterOpMod, AccessRegisterOutput};
+use mlx5cmd::error::{Error, Result};
+use mlx5cmd::registers::mgir::MGIR;
+use mlx5cmd::registers::{register_name, MAX_REGISTER_SIZE};
+
+#[derive(Parser, Debug)]
+struct CliArgs {
+    #[arg(short, long, default_value = "/sys/bus/pci/devices/0000:04:00.0")]
+    device: PathBuf,
+
+    #[arg(long, value_parser=maybe_hex::<u16>, default_value = "0x9000")]
+    start: u16,
+
+    #[arg(long, value_parser=maybe_hex::<u16>, default_value = "0x9200")]
+    end: u16,
+
+    /// Register data lengths to read each ID with, rounded up to dwords
+    #[arg(long, value_parser=maybe_hex::<usize>, value_delimiter = ',', default_value = "0x4,0x10,0x40,0x100")]
+    lengths: Vec<usize>,
+
+    /// Register argument passed with every read
+    #[arg(long, value_parser=maybe_hex::<u32>, default_value = "0")]
+    argument: u32,
+
+    /// Include the data of the longest accepted read in the report
+    #[arg(long)]
+    data: bool,
+
+    /// JSON lines report, a header line followed by one line per scanned register ID
+    #[arg(short, long, default_value = "register-scan.jsonl")]
+    output: PathBuf,
+
+    /// Continue a partial scan in the output file, skipping the IDs it already holds
+    #[arg(long)]
+    resume: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum Outcome {
+    Ok,
+    Status { status: String, syndrome: u32 },
+    Error { message: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct Probe {
+    len: usize,
+    outcome: Outcome,
+    // Length up to the last non-zero byte of the returned data
+    data_len: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct ScanEntry {
+    register_id: u16,
+    name: Option<String>,
+    accepted: bool,
+    // Whether MCAM/PCAM/QCAM list the register, None outside the ranges they describe
+    reported: Option<bool>,
+    probes: Vec<Probe>,
+    data: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct ScanHeader {
+    device: PathBuf,
+    timestamp: u64,
+    fw_version: Option<String>,
+    argument: u32,
+}
+
+fn read(cmdif: &impl CmdIf, register_id: u16, argument: u32, len: usize) -> Result<AccessRegisterOutput> {
+    cmdif.do_command(AccessRegister {
+        op_mod: AccessRegisterOpMod::Read,
+        register_id,
+        argument,
+        register_data: vec![0; len],
+    })
+}
+
+fn classify(res: &Result<AccessRegisterOutput>) -> Outcome {
+    match res {
+        Ok(_) => Outcome::Ok,
+        Err(Error::Command { status, syndrome, .. }) => Outcome::Status {
+            status: format!("{status:?}"),
+            syndrome: *syndrome,
+        },
+        Err(err) => Outcome::Error {
+            message: err.to_string(),
+        },
+    }
+}
+
+fn hex(data: &[u8]) -> String {
+    data.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn scan(cmdif: &impl CmdIf, args: &CliArgs, lengths: &[usize], register_id: u16) -> ScanEntry {
+    let mut probes = vec![];
+    let mut data = None;
+    for &len in lengths {
+        let res = read(cmdif, register_id, args.argument, len);
+        let data_len = res.as_ref().ok().map(|output| {
+            output
+                .register_data
+                .iter()
+                .rposition(|byte| *byte != 0)
+                .map_or(0, |last| last + 1)
+        });
+        if let Ok(output) = &res {
+            data = Some(hex(&output.register_data));
+        }
+        probes.push(Probe {
+            len,
+            outcome: classify(&res),
+            data_len,
+        });
+    }
+
+    let accepted = probes.iter().any(|probe| probe.outcome == Outcome::Ok);
+    log::info!(
+        "{register_id:#06x} {}: {}",
+        register_name(register_id).unwrap_or("?"),
+        if accepted { "accepted" } else { "rejected" }
+    );
+
+    ScanEntry {
+        register_id,
+        name: register_name(register_id).map(str::to_string),
+        accepted,
+        reported: None,
+        probes,
+        data: data.filter(|_| args.data),
+    }
+}
+
+fn write_line(output: &mut File, record: &impl Serialize) -> anyhow::Result<()> {
+    serde_json::to_writer(&mut *output, record)?;
+    output.write_all(b"\n")?;
+    Ok(())
+}
+
+// Reads back a report, dropping a last line cut short by an interrupted scan
+fn read_report(path: &Path) -> anyhow::Result<(ScanHeader, Vec<ScanEntry>)> {
+    let text = std::fs::read_to_string(path)?;
+    let mut lines = text.lines();
+    let header = serde_json::from_str(lines.next().ok_or_else(|| anyhow!("{} is empty", path.display()))?)?;
+    let mut entries = vec![];
+    for (lineno, line) in lines.enumerate() {
+        match serde_json::from_str(line) {
+            Ok(entry) => entries.push(entry),
+            Err(err) => {
+                log::warn!("Dropping line {} of {}: {err}", lineno + 2, path.display());
+                break;
+            }
+        }
+    }
+    Ok((header, entries))
+}
+
+fn main() -> anyhow::Result<()> {
+    env_logger::init();
+    let args = CliArgs::parse();
+
+    let cmdif = VfioCmdIf::open_from_sysfs(&args.device, true, true)?;
+
+    let timestamp = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH)?.as_secs();
+    let fw_version = cmdif
+        .read_register(MGIR::default(), 0)
+        .map(|mgir| mgir.fw_version())
+        .map_err(|err| log::warn!("Could not read MGIR: {err}"))
+        .ok();
+    let capabilities = discover_capabilities(&cmdif)
+        .map_err(|err| log::warn!("Could not read the capability masks: {err}"))
+        .ok();
+
+    let mut lengths: Vec<usize> = args
+        .lengths
+        .iter()
+        .map(|len| len.next_multiple_of(4).clamp(4, MAX_REGISTER_SIZE))
+        .collect();
+    lengths.sort();
+    lengths.dedup();
+
+    let (header, mut entries) = if args.resume && args.output.exists() {
+        let (header, entries) = read_report(&args.output)?;
+        if header.argument != args.argument {
+            log::warn!("Resuming a scan made with argument {:#x}", header.argument);
+        }
+        log::info!("Resuming with {} register IDs already scanned", entries.len());
+        (header, entries)
+    } else {
+        let header = ScanHeader {
+            device: args.device.clone(),
+            timestamp,
+            fw_version,
+            argument: args.argument,
+        };
+        (header, vec![])
+    };
+
+    let mut output = File::create(&args.output)?;
+    write_line(&mut output, &header)?;
+    for entry in &entries {
+        write_line(&mut output, entry)?;
+    }
+
+    let done: HashSet<u16> = entries.iter().map(|entry| entry.register_id).collect();
+    for register_id in (args.start..args.end).filter(|register_id| !done.contains(register_id)) {
+        let mut entry = scan(&cmdif, &args, &lengths, register_id);
+        entry.reported = capabilities
+            .as_ref()
+            .filter(|capabilities| capabilities.is_covered(register_id))
+            .map(|capabilities| capabilities.registers.contains(&register_id));
+        write_line(&mut output, &entry)?;
+        entries.push(entry);
+    }
+
+    entries.sort_by_key(|entry| entry.register_id);
+    for entry in entries.iter().filter(|entry| entry.accepted) {
+        let accepted_lengths: Vec<String> = entry
+            .probes
+            .iter()
+            .filter(|probe| probe.outcome == Outcome::Ok)
+            .map(|probe| format!("{:#x}/{:#x}", probe.len, probe.data_len.unwrap_or(0)))
+            .collect();
+        println!(
+            "{:#06x} {:<16} {:<14} {}",
+            entry.register_id,
+            entry.name.as_deref().unwrap_or("?"),
+            match entry.reported {
+                Some(true) => "reported",
+                Some(false) => "not reported",
+                None => "",
+            },
+            accepted_lengths.join(" ")
+        );
+    }
+
+    Ok(())
+}
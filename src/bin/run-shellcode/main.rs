@@ -10,7 +10,7 @@ use mlx5cmd::cmdif::CmdIf;
 use mlx5cmd::commands::ExecShellcode64;
 
 use mlx5cmd::{
-    error::Result, cmdif::vfio::VfioCmdIf
+    error::Result, cmdif::vfio::VfioCmdIf, device_info::DeviceInfo
 };
 
 use irisc_asm::assemble_template;
@@ -45,6 +45,7 @@ struct Experiment {
     arguments: [Vec<u32>; 6],
     template: String,
     timestamp: u64,
+    device_info: Option<DeviceInfo>,
 }
 
 
@@ -68,6 +69,8 @@ fn main() -> Result<()> {
 
     let cmdif = VfioCmdIf::open_from_sysfs(&args.device, true, true)?;
 
+    let device_info = cmdif.device_info().map_err(|err| log::warn!("Could not query device info: {err}")).ok();
+
     let template = std::fs::read_to_string(&args.template)?;
     let timestamp = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs();
 
@@ -93,6 +96,7 @@ fn main() -> Result<()> {
         arguments: Default::default(),
         template: template.clone(),
         timestamp: timestamp,
+        device_info,
     })?;
 
     let mut iteration_start_time = SystemTime::now();
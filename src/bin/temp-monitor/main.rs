@@ -0,0 +1,128 @@
+use std::fs::OpenOptions;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use std::thread::sleep;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use clap::Parser;
+
+use mlx5cmd::cmdif::vfio::VfioCmdIf;
+use mlx5cmd::cmdif::CmdIf;
+use mlx5cmd::registers::thermal::{MODULE_SENSOR_BASE, MTCAP, MTMP};
+
+#[derive(Parser, Debug)]
+struct CliArgs {
+    #[arg(short, long, default_value = "/sys/bus/pci/devices/0000:04:00.0")]
+    device: PathBuf,
+
+    #[arg(long, default_value_t = 1000)]
+    interval_ms: u64,
+
+    /// Stop after this many seconds instead of running until killed
+    #[arg(long)]
+    duration: Option<u64>,
+
+    /// Sensors to poll instead of the ones MTCAP reports
+    #[arg(short, long)]
+    sensor: Vec<u16>,
+
+    /// Also poll the sensors of this many transceiver modules
+    #[arg(long, default_value_t = 0)]
+    modules: u16,
+
+    /// Clear the recorded maximum temperatures before polling
+    #[arg(long)]
+    reset_max: bool,
+
+    /// Append CSV rows to this file instead of writing to stdout
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+fn main() -> anyhow::Result<()> {
+    env_logger::init();
+    let args = CliArgs::parse();
+
+    let cmdif = VfioCmdIf::open_from_sysfs(&args.device, true, true)?;
+
+    let mut sensors = if args.sensor.is_empty() {
+        let mtcap = cmdif.read_register(MTCAP::default(), 0)?;
+        log::info!("{} sensors, map {:#x}", mtcap.sensor_count, mtcap.sensor_map);
+        mtcap.sensors()
+    } else {
+        args.sensor
+    };
+    sensors.extend((0..args.modules).map(|module| MODULE_SENSOR_BASE + module));
+
+    // Read-modify-write like mlx5_hwmon_enable_max_temp, so the thresholds and tee are kept
+    for &sensor_index in &sensors {
+        let mtmp = cmdif
+            .read_register(
+                MTMP {
+                    sensor_index,
+                    ..Default::default()
+                },
+                0,
+            )
+            .and_then(|mtmp| {
+                cmdif.write_register(
+                    MTMP {
+                        mte: true,
+                        mtr: args.reset_max,
+                        ..mtmp
+                    },
+                    0,
+                )
+            });
+        if let Err(err) = mtmp {
+            log::warn!("Could not enable max temperature tracking on sensor {sensor_index}: {err}");
+        }
+    }
+
+    let (mut output, header): (Box<dyn Write>, bool) = match &args.output {
+        Some(path) => {
+            let file = OpenOptions::new().create(true).append(true).open(path)?;
+            let empty = file.metadata()?.len() == 0;
+            (Box::new(BufWriter::new(file)), empty)
+        }
+        None => (Box::new(std::io::stdout().lock()), true),
+    };
+    if header {
+        writeln!(output, "time,sensor,name,temperature,max_temperature,threshold_hi,threshold_lo")?;
+    }
+
+    let deadline = args.duration.map(|secs| Instant::now() + Duration::from_secs(secs));
+    while !deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?;
+        for &sensor_index in &sensors {
+            let mtmp = match cmdif.read_register(
+                MTMP {
+                    sensor_index,
+                    ..Default::default()
+                },
+                0,
+            ) {
+                Ok(mtmp) => mtmp,
+                Err(err) => {
+                    log::warn!("Could not read sensor {sensor_index}: {err}");
+                    continue;
+                }
+            };
+            writeln!(
+                output,
+                "{}.{:03},{sensor_index},{},{:.3},{:.3},{:.3},{:.3}",
+                now.as_secs(),
+                now.subsec_millis(),
+                mtmp.name(),
+                mtmp.temperature_celsius(),
+                mtmp.max_temperature_celsius(),
+                mtmp.threshold_hi_celsius(),
+                mtmp.threshold_lo_celsius()
+            )?;
+        }
+        output.flush()?;
+        sleep(Duration::from_millis(args.interval_ms));
+    }
+
+    Ok(())
+}
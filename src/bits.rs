@@ -0,0 +1,40 @@
+// MSB first bit fields, as the PRM numbers bits within big endian layouts
+
+pub fn get_bits(data: &[u8], offset: usize, bits: usize) -> u64 {
+    (offset..offset + bits).fold(0, |value, bit| {
+        let set = data.get(bit / 8).is_some_and(|byte| byte & (0x80 >> (bit % 8)) != 0);
+        value << 1 | set as u64
+    })
+}
+
+pub fn set_bits(data: &mut [u8], offset: usize, bits: usize, value: u64) {
+    for (index, bit) in (offset..offset + bits).enumerate() {
+        let mask = 0x80 >> (bit % 8);
+        if value >> (bits - 1 - index) & 1 != 0 {
+            data[bit / 8] |= mask;
+        } else {
+            data[bit / 8] &= !mask;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bits() {
+        let mut data = vec![0; 8];
+        set_bits(&mut data, 0x1d, 3, 0b101);
+        assert_eq!(data, [0, 0, 0, 0b101, 0, 0, 0, 0]);
+        assert_eq!(get_bits(&data, 0x1d, 3), 0b101);
+
+        set_bits(&mut data, 0x30, 16, 0x1234);
+        assert_eq!(&data[6..], [0x12, 0x34]);
+        set_bits(&mut data, 0x31, 1, 1);
+        assert_eq!(get_bits(&data, 0x30, 16), 0x5234);
+
+        // Reads past the end of the data are zero
+        assert_eq!(get_bits(&data, 0x38, 16), 0x3400);
+    }
+}
@@ -0,0 +1,130 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::ops::Range;
+
+use serde::Serialize;
+
+use crate::cmdif::CmdIf;
+use crate::error::Result;
+use crate::registers::cam::{CAP_MASK_SIZE, MCAM, PCAM, QCAM, REGISTERS_PER_GROUP};
+use crate::registers::Register;
+
+// MCAM describes the 0x9000 registers in several groups, PCAM and QCAM only have group 0
+const MCAM_GROUPS: u8 = 3;
+
+// Set bits of a big endian capability mask, counted from the least significant bit
+pub fn mask_bits(mask: &[u8; CAP_MASK_SIZE]) -> Vec<u16> {
+    (0..CAP_MASK_SIZE * 8)
+        .filter(|bit| mask[CAP_MASK_SIZE - 1 - bit / 8] & (1 << (bit % 8)) != 0)
+        .map(|bit| bit as u16)
+        .collect()
+}
+
+// Registers and features the firmware reports in its capability mask registers. Only the register
+// ranges covered by a mask that was read are known, registers outside them are not refused.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Capabilities {
+    pub registers: BTreeSet<u16>,
+    pub covered: Vec<Range<u16>>,
+    pub features: BTreeMap<&'static str, BTreeSet<u16>>,
+}
+
+impl Capabilities {
+    pub fn add_register_mask(&mut self, base: u16, group: u8, mask: &[u8; CAP_MASK_SIZE]) {
+        let start = base + group as u16 * REGISTERS_PER_GROUP;
+        self.covered.push(start..start + REGISTERS_PER_GROUP);
+        self.registers
+            .extend(mask_bits(mask).into_iter().map(|bit| start + bit));
+    }
+
+    pub fn add_feature_mask(&mut self, source: &'static str, group: u8, mask: &[u8; CAP_MASK_SIZE]) {
+        let first = group as u16 * CAP_MASK_SIZE as u16 * 8;
+        self.features
+            .entry(source)
+            .or_default()
+            .extend(mask_bits(mask).into_iter().map(|bit| first + bit));
+    }
+
+    pub fn is_covered(&self, register_id: u16) -> bool {
+        self.covered.iter().any(|range| range.contains(&register_id))
+    }
+
+    // The capability registers themselves are always allowed so discovery can be repeated
+    pub fn supports(&self, register_id: u16) -> bool {
+        [MCAM::REGISTER_ID, PCAM::REGISTER_ID, QCAM::REGISTER_ID].contains(&register_id)
+            || !self.is_covered(register_id)
+            || self.registers.contains(&register_id)
+    }
+}
+
+pub fn discover_capabilities(cmdif: &impl CmdIf) -> Result<Capabilities> {
+    let mut capabilities = Capabilities::default();
+
+    // Only the register group varies, feature_group 0 (enhanced features) is the only one the
+    // firmware knows, as in mlx5_query_mcam_reg
+    for group in 0..MCAM_GROUPS {
+        let mcam = cmdif.read_register(
+            MCAM {
+                access_reg_group: group,
+                feature_group: 0,
+                ..Default::default()
+            },
+            0,
+        );
+        match mcam {
+            Ok(mcam) => {
+                capabilities.add_register_mask(MCAM::BASE, group, &mcam.access_reg_cap_mask);
+                if group == 0 {
+                    capabilities.add_feature_mask("MCAM", 0, &mcam.feature_cap_mask);
+                }
+            }
+            // Older firmware rejects groups it does not know
+            Err(err) if group != 0 => log::debug!("MCAM group {group}: {err}"),
+            Err(err) => return Err(err),
+        }
+    }
+
+    match cmdif.read_register(PCAM::default(), 0) {
+        Ok(pcam) => {
+            capabilities.add_register_mask(PCAM::BASE, 0, &pcam.access_reg_cap_mask);
+            capabilities.add_feature_mask("PCAM", 0, &pcam.feature_cap_mask);
+        }
+        Err(err) => log::warn!("Could not read PCAM: {err}"),
+    }
+
+    match cmdif.read_register(QCAM::default(), 0) {
+        Ok(qcam) => {
+            capabilities.add_register_mask(QCAM::BASE, 0, &qcam.access_reg_cap_mask);
+            capabilities.add_feature_mask("QCAM", 0, &qcam.feature_cap_mask);
+        }
+        Err(err) => log::warn!("Could not read QCAM: {err}"),
+    }
+
+    Ok(capabilities)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_register_mask() {
+        // MCQS, MCQI, MCC and MCDA as the kernel's mcam_access_reg layout places them
+        let mut mask = [0; CAP_MASK_SIZE];
+        mask[3] = 0x0f;
+        mask[CAP_MASK_SIZE - 1] = 0x01;
+        assert_eq!(mask_bits(&mask), [0x00, 0x60, 0x61, 0x62, 0x63]);
+
+        let mut capabilities = Capabilities::default();
+        capabilities.add_register_mask(MCAM::BASE, 0, &mask);
+        assert!(capabilities.supports(0x9062));
+        assert!(!capabilities.supports(0x9010));
+        assert!(capabilities.supports(MCAM::REGISTER_ID));
+        // Not covered by any mask that was read
+        assert!(capabilities.supports(0x9110));
+        assert!(capabilities.supports(0x5006));
+
+        capabilities.add_register_mask(MCAM::BASE, 2, &mask);
+        assert!(capabilities.supports(0x9160));
+        assert!(!capabilities.supports(0x9110));
+    }
+}
@@ -3,20 +3,37 @@ use irisc_asm::assemble;
 
 pub mod vfio;
 
-use crate::{commands::{access_register::{AccessRegister, AccessRegisterOpMod}, BaseOutputStatus, Command, CommandErrorStatus, ExecShellcode64}, error::{Error, Result}, registers::Register};
+use crate::{capabilities::Capabilities, commands::{access_register::{AccessRegister, AccessRegisterOpMod}, general_object::{CreateGeneralObject, DestroyGeneralObject, GeneralObject, ModifyGeneralObject, QueryGeneralObject}, BaseOutputStatus, Command, CommandErrorStatus, ExecShellcode64}, error::{Error, Result}, opcodes::describe_opcode, registers::{register_data, Register}, syndrome};
 
 pub trait CmdIf {
     fn exec_command(&self, input: &[u8], outlen: u32) -> Result<Vec<u8>>;
 
+    // Discovered register capabilities, register accesses are checked against them when present
+    fn capabilities(&self) -> Option<&Capabilities> {
+        None
+    }
+
+    fn check_register_supported(&self, register_id: u16) -> Result<()> {
+        match self.capabilities() {
+            Some(capabilities) if !capabilities.supports(register_id) => {
+                Err(Error::UnsupportedRegister { register_id })
+            }
+            _ => Ok(()),
+        }
+    }
+
     fn do_command<Cmd: Command + core::fmt::Debug>(&self, cmd: Cmd) -> Result<Cmd::Output> {
-        log::debug!("Command: {cmd:x?}");
         let msg = cmd.to_bytes()?;
-        let out = self.exec_command(&msg, cmd.outlen() as u32)?;
+        log::debug!("Command {}: {cmd:x?}", describe_opcode(u16::from_be_bytes([msg[0], msg[1]])));
+        let out = self.exec_command(&msg, cmd.outlen()? as u32)?;
         let base_output = BaseOutputStatus::from_bytes((&out, 0))?.1;
         if base_output.0.status != CommandErrorStatus::Ok {
             return Err(Error::Command {
+                opcode: u16::from_be_bytes([msg[0], msg[1]]),
+                op_mod: u16::from_be_bytes([msg[6], msg[7]]),
                 status: base_output.0.status,
                 syndrome: base_output.0.syndrome,
+                message: syndrome::lookup(base_output.0.syndrome),
             });
         }
 
@@ -27,11 +44,12 @@ pub trait CmdIf {
 
    fn read_register<Reg: Register + core::fmt::Debug>(&self, reg: Reg, argument: u32) -> Result<Reg> {
         log::debug!("Reading register {reg:x?}");
+        self.check_register_supported(Reg::REGISTER_ID)?;
         let resp = self.do_command(AccessRegister {
             op_mod: AccessRegisterOpMod::Read,
             argument,
             register_id: Reg::REGISTER_ID,
-            register_data: reg.to_bytes()?,
+            register_data: register_data(&reg)?,
         })?;
         let reg = Reg::from_bytes((&resp.register_data, 0))?.1;
         log::debug!("Register value: {reg:x?}");
@@ -40,17 +58,58 @@ pub trait CmdIf {
 
    fn write_register<Reg: Register + core::fmt::Debug>(&self, reg: Reg, argument: u32) -> Result<Reg> {
         log::debug!("Writing register {reg:x?}");
+        self.check_register_supported(Reg::REGISTER_ID)?;
         let resp = self.do_command(AccessRegister {
             op_mod: AccessRegisterOpMod::Write,
             argument,
             register_id: Reg::REGISTER_ID,
-            register_data: reg.to_bytes()?,
+            register_data: register_data(&reg)?,
         })?;
         let reg = Reg::from_bytes((&resp.register_data, 0))?.1;
         log::debug!("Register value after write {reg:x?}");
         Ok(reg)
     }
 
+    fn create_general_object<Obj: GeneralObject>(&self, obj: &Obj) -> Result<u32> {
+        Ok(self.do_command(CreateGeneralObject {
+            obj_type: Obj::OBJ_TYPE,
+            obj_id: 0,
+            op_param: 0,
+            obj_context: obj.to_bytes()?,
+        })?.obj_id)
+    }
+
+    fn modify_general_object<Obj: GeneralObject>(&self, obj_id: u32, obj: &Obj) -> Result<()> {
+        self.do_command(ModifyGeneralObject {
+            obj_type: Obj::OBJ_TYPE,
+            obj_id,
+            op_param: 0,
+            obj_context: obj.to_bytes()?,
+        })?;
+        Ok(())
+    }
+
+    fn query_general_object<Obj: GeneralObject>(&self, obj_id: u32) -> Result<Obj> {
+        let resp = self.do_command(QueryGeneralObject {
+            obj_type: Obj::OBJ_TYPE,
+            obj_id,
+            op_param: 0,
+            context_size: Obj::CONTEXT_SIZE,
+        })?;
+        let obj = Obj::from_bytes((&resp.obj_context, 0))?.1;
+        log::debug!("General object {obj_id:#x}: {obj:x?}");
+        Ok(obj)
+    }
+
+    fn destroy_general_object<Obj: GeneralObject>(&self, obj_id: u32) -> Result<()> {
+        self.do_command(DestroyGeneralObject {
+            obj_type: Obj::OBJ_TYPE,
+            obj_id,
+            op_param: 0,
+        })?;
+        Ok(())
+    }
+
     fn run_shellcode(&self, shellcode: &str) -> anyhow::Result<[u64;3]> {
         let (code, _labels) = assemble(0, shellcode)?;
         let mut shellcode = [0u8; 0xa0];
@@ -1,9 +1,10 @@
-use std::{collections::HashMap, path::Path, thread::sleep, time::Duration};
+use std::{collections::HashMap, fs::{File, OpenOptions}, os::unix::fs::FileExt, path::{Path, PathBuf}, thread::{sleep, yield_now}, time::{Duration, Instant}};
 
 use crate::{
-    allocator::{AllocationGuard, Allocator}, cmdif::CmdIf, commands::{
+    allocator::{AllocationGuard, Allocator}, capabilities::{discover_capabilities, Capabilities}, cmdif::CmdIf, commands::{
         ManagePages, ManagePagesOpMod, QueryPages, QueryPagesOpMod
-    }, cqe::CQE, error::{Error, Result}, init::InitSegment, mailbox::MailboxAllocator
+    }, cqe::CQE, error::{Error, Result}, init::InitSegment, mailbox::{MailboxAllocator, MAILBOX_DATA_SIZE, MAILBOX_REGION_PAGES},
+    registers::mfrl::{MFRL, MFRL_RESET_LEVEL3}
 };
 use log::{debug, trace};
 use pci_driver::{
@@ -25,9 +26,30 @@ pub struct VfioCmdIf {
     pub dma_allocator: Allocator,
     pub cqe_region: AllocationGuard,
     pub managed_pages: HashMap<u64, AllocationGuard>,
+    pub capabilities: Option<Capabilities>,
+    pub sysfs_path: Option<PathBuf>,
+    // How long exec_command waits for the firmware to complete a command
+    pub cmd_timeout: Duration,
 }
 
 const DMA_PAGES: usize = 32768;
+const FW_RESET_TIMEOUT: Duration = Duration::from_secs(60);
+// The kernel's MLX5_CMD_TIMEOUT_MSEC
+const CMD_TIMEOUT: Duration = Duration::from_secs(60);
+// Firmware that is still alive answers MFRL right away, a crashed one should not delay the link toggle
+const MFRL_TIMEOUT: Duration = Duration::from_secs(2);
+
+const PCI_CAPABILITY_LIST: u64 = 0x34;
+const PCI_CAP_ID_EXP: u8 = 0x10;
+const PCI_EXP_DEVCTL: u64 = 0x08;
+const PCI_EXP_LNKCAP: u64 = 0x0c;
+const PCI_EXP_LNKCAP_DLLLARC: u32 = 1 << 20;
+const PCI_EXP_LNKCTL: u64 = 0x10;
+const PCI_EXP_LNKCTL_LD: u16 = 1 << 4;
+const PCI_EXP_LNKSTA: u64 = 0x12;
+const PCI_EXP_LNKSTA_DLLLA: u16 = 1 << 13;
+const LINK_DOWN_TIME: Duration = Duration::from_millis(500);
+const LINK_UP_TIMEOUT: Duration = Duration::from_secs(1);
 
 impl VfioCmdIf {
     pub fn open_from_sysfs(
@@ -35,11 +57,12 @@ impl VfioCmdIf {
         reset: bool,
         init: bool,
     ) -> Result<Self> {
-        let pci_device = VfioPciDevice::open(sysfs_path)?;
+        let pci_device = VfioPciDevice::open(&sysfs_path)?;
         if reset {
             pci_device.reset()?;
         }
         let mut cmdif = Self::open_vfio_device(pci_device)?;
+        cmdif.sysfs_path = Some(sysfs_path.as_ref().to_path_buf());
         if init {
             cmdif.initialize()?
         }
@@ -65,6 +88,9 @@ impl VfioCmdIf {
             dma_allocator,
             cqe_region,
             managed_pages: HashMap::new(),
+            capabilities: None,
+            sysfs_path: None,
+            cmd_timeout: CMD_TIMEOUT,
         };
         this.setup_cmdq_phy_addr(cqe_ptr)?;
 
@@ -127,6 +153,81 @@ impl VfioCmdIf {
         Ok(())
     }
 
+    // Reads MCAM/PCAM/QCAM and refuses accesses to registers they do not list from then on
+    pub fn enable_register_check(&mut self) -> Result<&Capabilities> {
+        self.capabilities = None;
+        let capabilities = discover_capabilities(&*self)?;
+        debug!("Firmware supports {} registers", capabilities.registers.len());
+        Ok(self.capabilities.insert(capabilities))
+    }
+
+    // Requests a firmware reset through MFRL, triggers it by toggling the link of the upstream port
+    // and brings the command interface back up. Firmware that no longer answers commands only gets
+    // the link toggle. rst_type_sel is one of the MFRL_RESET_TYPE_* values.
+    pub fn firmware_reset(&mut self, rst_type_sel: u8) -> Result<()> {
+        let sysfs_path = self
+            .sysfs_path
+            .clone()
+            .ok_or(Error::FirmwareReset("the device was not opened through sysfs"))?;
+        let register_check = self.capabilities.is_some();
+
+        let cmd_timeout = std::mem::replace(&mut self.cmd_timeout, MFRL_TIMEOUT);
+        let mfrl = self.read_register(MFRL::default(), 0);
+        self.cmd_timeout = cmd_timeout;
+        match mfrl {
+            Ok(mfrl) => {
+                debug!("{mfrl:x?}");
+                if mfrl.reset_level & MFRL_RESET_LEVEL3 == 0 {
+                    return Err(Error::FirmwareReset("reset level 3 is not supported"));
+                }
+                if !mfrl.supports_type(rst_type_sel) {
+                    return Err(Error::FirmwareReset("reset type is not supported"));
+                }
+                self.write_register(
+                    MFRL {
+                        reset_level: MFRL_RESET_LEVEL3,
+                        rst_type_sel,
+                        ..Default::default()
+                    },
+                    0,
+                )?;
+            }
+            Err(err) => log::warn!("Firmware did not answer MFRL, falling back to a link toggle: {err}"),
+        }
+
+        toggle_upstream_link(&sysfs_path)?;
+        self.wait_initializing(FW_RESET_TIMEOUT)?;
+
+        // Pages handed to the firmware and discovered capabilities do not survive the reset
+        self.managed_pages.clear();
+        self.capabilities = None;
+
+        self.pci_device
+            .config()
+            .command()
+            .bus_master_enable()
+            .write(true)?;
+        let cqe_ptr = self.cqe_region.as_ptr().unwrap() as u64;
+        self.setup_cmdq_phy_addr(cqe_ptr)?;
+        self.initialize()?;
+
+        if register_check {
+            self.enable_register_check()?;
+        }
+        Ok(())
+    }
+
+    pub fn wait_initializing(&self, timeout: Duration) -> Result<()> {
+        let started = Instant::now();
+        while self.init_segment().initializing().read()?.to_be() & 0x80000000 != 0 {
+            if started.elapsed() > timeout {
+                return Err(Error::Timeout("firmware initialization"));
+            }
+            sleep(Duration::from_millis(100));
+        }
+        Ok(())
+    }
+
     pub fn init_segment(&self) -> InitSegment {
         InitSegment::backed_by(&self.bar0_region)
     }
@@ -139,19 +240,19 @@ impl VfioCmdIf {
             .cmdq_phy_addr_lo()
             .write(((cmdq_phy_addr & 0xffffffff) as u32).to_be())?;
 
-        while self.init_segment().initializing().read()?.to_be() & 0x80000000 != 0x00000000 {
-            sleep(Duration::from_millis(100));
-        }
-
-        Ok(())
+        self.wait_initializing(FW_RESET_TIMEOUT)
     }
 }
 
 impl CmdIf for VfioCmdIf {
+    fn capabilities(&self) -> Option<&Capabilities> {
+        self.capabilities.as_ref()
+    }
+
     fn exec_command(&self, input: &[u8], outlen: u32) -> Result<Vec<u8>> {
         log::trace!("Executing command input={input:02x?} outlen={outlen}");
         let cmd = CQE::backed_by(&*self.cqe_region);
-        let mailbox_region = self.dma_allocator.alloc(256).unwrap();
+        let mailbox_region = self.dma_allocator.alloc(MAILBOX_REGION_PAGES).ok_or(Error::OutOfMemory)?;
         let mut mailbox_allocator = MailboxAllocator::new((&*mailbox_region).subregion(..));
 
         cmd.cmd_type().write(0x07)?;
@@ -160,11 +261,12 @@ impl CmdIf for VfioCmdIf {
         cmd.set_output_mb(0)?;
 
         cmd.input_length().write((input.len() as u32).to_be())?;
-        for (i, b) in input[..0x10].iter().enumerate() {
-            cmd.write_u8(0x10 + i as u64, *b)?;
+        let (inline_input, mailbox_input) = input.split_at(input.len().min(0x10));
+        for i in 0..0x10 {
+            cmd.write_u8(0x10 + i as u64, inline_input.get(i).copied().unwrap_or(0))?;
         }
 
-        let in_mb_vec = mailbox_allocator.build_mailbox(0x00, &input[0x10..])?;
+        let in_mb_vec = mailbox_allocator.build_mailbox(0x00, mailbox_input)?;
         if let Some(in_mb) = in_mb_vec.first() {
             cmd.set_input_mb(in_mb.as_ptr().unwrap() as u64)?;
         }
@@ -174,7 +276,7 @@ impl CmdIf for VfioCmdIf {
             cmd.write_u8(0x20 + i as u64, *b)?;
         }
 
-        let out_mb_vec = mailbox_allocator.build_mailbox(0x00, &vec![0u8; outlen as usize])?;
+        let out_mb_vec = mailbox_allocator.build_mailbox(0x00, &vec![0u8; (outlen as usize).saturating_sub(0x10)])?;
         if let Some(out_mb) = out_mb_vec.first() {
             cmd.set_output_mb(out_mb.as_ptr().unwrap() as u64)?;
         }
@@ -187,8 +289,12 @@ impl CmdIf for VfioCmdIf {
             .cmdq_doorbell()
             .write(0x00000001_u32.to_be())?;
 
+        let started = Instant::now();
         while cmd.status().read()? & 0x01 != 0x00 {
-            log::trace!("Waiting for command status");
+            if started.elapsed() > self.cmd_timeout {
+                return Err(Error::Timeout("command completion"));
+            }
+            yield_now();
         }
         let err = cmd.status().read()? >> 1;
         if err != 0x00 {
@@ -201,7 +307,7 @@ impl CmdIf for VfioCmdIf {
             output.push(cmd.read_u8(0x20 + i)?)
         }
         for out_mb in out_mb_vec.iter() {
-            let mut chunk = vec![0u8; 0x200];
+            let mut chunk = vec![0u8; MAILBOX_DATA_SIZE];
             out_mb.read_bytes(0, &mut chunk)?;
             output.extend_from_slice(&chunk[..]);
         }
@@ -212,6 +318,100 @@ impl CmdIf for VfioCmdIf {
     }
 }
 
+// Config space of a PCI function through sysfs, which reaches the hardware while vfio-pci owns it
+struct SysfsConfig(File);
+
+impl SysfsConfig {
+    fn open(device: &Path) -> Result<Self> {
+        Ok(Self(OpenOptions::new().read(true).write(true).open(device.join("config"))?))
+    }
+
+    fn read<const N: usize>(&self, offset: u64) -> Result<[u8; N]> {
+        let mut data = [0; N];
+        self.0.read_exact_at(&mut data, offset)?;
+        Ok(data)
+    }
+
+    fn read_u16(&self, offset: u64) -> Result<u16> {
+        Ok(u16::from_le_bytes(self.read(offset)?))
+    }
+
+    fn read_u32(&self, offset: u64) -> Result<u32> {
+        Ok(u32::from_le_bytes(self.read(offset)?))
+    }
+
+    fn write(&self, offset: u64, data: &[u8]) -> Result<()> {
+        self.0.write_all_at(data, offset)?;
+        Ok(())
+    }
+
+    fn express_capability(&self) -> Result<u64> {
+        let mut offset = self.read::<1>(PCI_CAPABILITY_LIST)?[0] as u64 & !3;
+        // Bounded like the kernel's capability walk, in case the list loops
+        for _ in 0..48 {
+            if offset == 0 {
+                break;
+            }
+            let [id, next] = self.read::<2>(offset)?;
+            if id == PCI_CAP_ID_EXP {
+                return Ok(offset);
+            }
+            offset = next as u64 & !3;
+        }
+        Err(Error::FirmwareReset("no PCI Express capability"))
+    }
+}
+
+// Resets the device by disabling the link of the port above it, as mlx5_pci_link_toggle does.
+// Every function behind that port goes down with it. The link down does not go through vfio-pci,
+// so the config header and device control are saved and restored here.
+fn toggle_upstream_link(device: &Path) -> Result<()> {
+    let device = device.canonicalize()?;
+    let port = device
+        .parent()
+        .filter(|port| port.join("config").exists())
+        .ok_or(Error::FirmwareReset("the device has no upstream PCI port"))?;
+    let config = SysfsConfig::open(&device)?;
+    let port = SysfsConfig::open(port)?;
+
+    let header: [u8; 0x40] = config.read(0)?;
+    let express = config.express_capability()?;
+    let devctl: [u8; 2] = config.read(express + PCI_EXP_DEVCTL)?;
+
+    let port_express = port.express_capability()?;
+    let lnkctl = port.read_u16(port_express + PCI_EXP_LNKCTL)?;
+    debug!("Toggling the link of {}", device.display());
+    port.write(port_express + PCI_EXP_LNKCTL, &(lnkctl | PCI_EXP_LNKCTL_LD).to_le_bytes())?;
+    sleep(LINK_DOWN_TIME);
+    port.write(port_express + PCI_EXP_LNKCTL, &(lnkctl & !PCI_EXP_LNKCTL_LD).to_le_bytes())?;
+    sleep(Duration::from_millis(100));
+
+    if port.read_u32(port_express + PCI_EXP_LNKCAP)? & PCI_EXP_LNKCAP_DLLLARC != 0 {
+        let started = Instant::now();
+        while port.read_u16(port_express + PCI_EXP_LNKSTA)? & PCI_EXP_LNKSTA_DLLLA == 0 {
+            if started.elapsed() > LINK_UP_TIMEOUT {
+                return Err(Error::Timeout("PCI link up"));
+            }
+            sleep(Duration::from_millis(10));
+        }
+    }
+
+    // Config reads return all ones until the device is back
+    let started = Instant::now();
+    while config.read::<4>(0)? != header[..4] {
+        if started.elapsed() > FW_RESET_TIMEOUT {
+            return Err(Error::Timeout("device after the link toggle"));
+        }
+        sleep(Duration::from_millis(100));
+    }
+
+    // Top down like pci_restore_config_space, so the BARs are set before the command register
+    for offset in (4..header.len()).step_by(4).rev() {
+        config.write(offset as u64, &header[offset..offset + 4])?;
+    }
+    config.write(express + PCI_EXP_DEVCTL, &devctl)
+}
+
 fn iommu_map(
     iommu: &pci_driver::iommu::PciIommu,
     iova: u64,
@@ -1,16 +1,4 @@
-pub mod access_register;
-pub mod create_mkey;
-pub mod exec_shellcode;
-pub mod hca;
-pub mod issi;
-pub mod manage_pages;
-pub mod query_adapter;
-pub mod query_hca_cap;
-pub mod query_pages;
-pub mod set_driver_version;
-pub mod uar;
-pub mod pd;
-pub mod eq;
+pub mod raw;
 
 pub use exec_shellcode::*;
 pub use hca::*;
@@ -23,6 +11,11 @@ pub use set_driver_version::*;
 pub use pd::*;
 pub use uar::*;
 pub use eq::*;
+pub use counters::*;
+pub use umem::*;
+pub use srq::*;
+pub use dct::*;
+pub use raw::*;
 
 use thiserror::Error;
 
@@ -31,11 +24,17 @@ use std::fmt::Debug;
 use deku::ctx::Endian;
 use deku::prelude::*;
 
+use crate::error::Result;
+
 pub trait Command: DekuContainerWrite {
     type Output: for<'a> DekuContainerRead<'a> + Debug;
 
-    fn size(&self) -> usize;
-    fn outlen(&self) -> usize;
+    // Input length, as serialized from the deku layout
+    fn size(&self) -> Result<usize> {
+        Ok(self.to_bytes()?.len())
+    }
+
+    fn outlen(&self) -> Result<usize>;
 }
 
 #[derive(Debug, PartialEq, DekuRead, DekuWrite)]
@@ -82,7 +81,10 @@ pub enum CommandErrorStatus {
     #[deku(id = "0x06")]
     ResourceBusy,
 
-    // 0x07 ???
+    #[error["FW not ready"]]
+    #[deku(id = "0x07")]
+    NotReady,
+
     #[error["Exceeded limit"]]
     #[deku(id = "0x08")]
     ExceededLimit,
@@ -99,6 +101,18 @@ pub enum CommandErrorStatus {
     #[deku(id = "0x0f")]
     NoResources,
 
+    #[error["Bad QP state"]]
+    #[deku(id = "0x10")]
+    BadQpState,
+
+    #[error["Bad packet"]]
+    #[deku(id = "0x30")]
+    BadPacket,
+
+    #[error["Bad size of outstanding CQEs"]]
+    #[deku(id = "0x40")]
+    BadSizeOutstandingCqes,
+
     #[error["Bad input length"]]
     #[deku(id = "0x50")]
     BadInputLen,
@@ -111,3 +125,184 @@ pub enum CommandErrorStatus {
     #[deku(id_pat = "_")]
     UnknownError(u8),
 }
+
+#[derive(Debug, Clone, Copy)]
+pub struct CommandInfo {
+    pub opcode: u16,
+    pub name: &'static str,
+    pub decode_input: fn(&[u8]) -> Option<String>,
+    pub decode_output: fn(&[u8]) -> Option<String>,
+}
+
+// Modules defining their commands through mlx5_commands! and their opcode tables, registry() is
+// generated from this list so a new module cannot be left out of it
+macro_rules! command_modules {
+    ($($module:ident => $table:ident,)*) => {
+        $(pub mod $module;)*
+
+        // Every command defined through mlx5_commands!
+        pub fn registry() -> impl Iterator<Item = &'static CommandInfo> {
+            [$($module::$table),*].into_iter().flatten()
+        }
+    };
+}
+
+command_modules! {
+    access_register => ACCESS_REGISTER_COMMANDS,
+    counters => COUNTER_COMMANDS,
+    create_mkey => CREATE_MKEY_COMMANDS,
+    dct => DCT_COMMANDS,
+    eq => EQ_COMMANDS,
+    exec_shellcode => EXEC_SHELLCODE_COMMANDS,
+    general_object => GENERAL_OBJECT_COMMANDS,
+    hca => HCA_COMMANDS,
+    issi => ISSI_COMMANDS,
+    manage_pages => MANAGE_PAGES_COMMANDS,
+    pd => PD_COMMANDS,
+    query_adapter => QUERY_ADAPTER_COMMANDS,
+    query_hca_cap => QUERY_HCA_CAP_COMMANDS,
+    query_pages => QUERY_PAGES_COMMANDS,
+    set_driver_version => SET_DRIVER_VERSION_COMMANDS,
+    srq => SRQ_COMMANDS,
+    uar => UAR_COMMANDS,
+    umem => UMEM_COMMANDS,
+}
+
+#[doc(hidden)]
+pub const OUTPUT_PROBE_LEN: usize = 0x4000;
+
+// Length of a fixed size output layout, found by parsing zeroes and seeing how much is consumed
+#[doc(hidden)]
+pub fn layout_len<T: for<'a> DekuContainerRead<'a>>(probe_len: usize) -> Result<usize> {
+    let probe = vec![0u8; probe_len];
+    let ((rest, _), _) = T::from_bytes((&probe, 0))?;
+    Ok(probe_len - rest.len())
+}
+
+#[doc(hidden)]
+pub fn check_layout<Cmd: Command>(cmd: Cmd, opcode: u16, fixed_output: bool) {
+    let input = cmd.to_bytes().expect("input layout does not serialize");
+    assert_eq!(input[..2], opcode.to_be_bytes(), "opcode does not match the input layout");
+    assert!(input.len() >= 0x10, "input is shorter than the command header");
+    assert_eq!(input.len() % 4, 0, "input length is not dword aligned");
+
+    if fixed_output {
+        assert_eq!(
+            layout_len::<Cmd::Output>(OUTPUT_PROBE_LEN).unwrap(),
+            layout_len::<Cmd::Output>(2 * OUTPUT_PROBE_LEN).unwrap(),
+            "variable length output layout needs an explicit outlen"
+        );
+    }
+
+    let outlen = cmd.outlen().expect("output layout does not parse from zeroes");
+    assert!(outlen >= 0x10, "output is shorter than the command header");
+    assert_eq!(outlen % 4, 0, "output length is not dword aligned");
+
+    let output = vec![0u8; outlen];
+    let ((rest, _), _) = Cmd::Output::from_bytes((&output, 0)).expect("output layout does not fit in outlen");
+    assert!(rest.is_empty(), "output layout is shorter than the declared outlen");
+}
+
+// Defines the Command impls of a module and its opcode table.
+//
+//     mlx5_commands! {
+//         HCA_COMMANDS;
+//
+//         InitHCA => InitHCAOutput, opcode = 0x102, example = InitHCA(());
+//         AccessRegister => AccessRegisterOutput, opcode = 0x805, outlen = (|cmd| ...), example = ...;
+//     }
+//
+// size() is the serialized input, outlen() the length of the output layout. Outputs whose length
+// depends on the input give outlen as `(|cmd| ...)` instead. A generated test serializes the
+// example and checks the opcode and both layouts, the table is what opcodes::decode_command uses to
+// pretty-print captured buffers.
+#[macro_export]
+macro_rules! mlx5_commands {
+    (@outlen $out:ident) => {
+        fn outlen(&self) -> $crate::error::Result<usize> {
+            static OUTLEN: ::std::sync::OnceLock<usize> = ::std::sync::OnceLock::new();
+            if let Some(outlen) = OUTLEN.get() {
+                return Ok(*outlen);
+            }
+            let outlen = $crate::commands::layout_len::<$out>($crate::commands::OUTPUT_PROBE_LEN)?;
+            Ok(*OUTLEN.get_or_init(|| outlen))
+        }
+    };
+
+    (@outlen $out:ident (|$this:ident| $outlen:expr)) => {
+        fn outlen(&self) -> $crate::error::Result<usize> {
+            let $this = self;
+            Ok($outlen)
+        }
+    };
+
+    (@fixed) => {
+        true
+    };
+
+    (@fixed $outlen:tt) => {
+        false
+    };
+
+    (
+        $table:ident;
+
+        $($cmd:ident => $out:ident, opcode = $opcode:expr, $(outlen = $outlen:tt,)? example = $example:expr;)*
+    ) => {
+        $(
+            impl $crate::commands::Command for $cmd {
+                type Output = $out;
+
+                $crate::mlx5_commands!(@outlen $out $($outlen)?);
+            }
+
+            #[cfg(test)]
+            impl $cmd {
+                fn layout_example() -> Self {
+                    $example
+                }
+            }
+        )*
+
+        pub const $table: &[$crate::commands::CommandInfo] = &[
+            $($crate::commands::CommandInfo {
+                opcode: $opcode,
+                name: stringify!($cmd),
+                decode_input: |input| {
+                    <$cmd as ::deku::DekuContainerRead>::from_bytes((input, 0)).ok().map(|(_, cmd)| format!("{cmd:x?}"))
+                },
+                decode_output: |output| {
+                    <$out as ::deku::DekuContainerRead>::from_bytes((output, 0)).ok().map(|(_, out)| format!("{out:x?}"))
+                },
+            },)*
+        ];
+
+        #[cfg(test)]
+        mod command_layout {
+            $(
+                #[test]
+                #[allow(non_snake_case)]
+                fn $cmd() {
+                    $crate::commands::check_layout(
+                        super::$cmd::layout_example(),
+                        $opcode,
+                        $crate::mlx5_commands!(@fixed $($outlen)?),
+                    );
+                }
+            )*
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_not_ready() {
+        let output = BaseOutputStatus::try_from(&[0x07, 0, 0, 0, 0x12, 0x34, 0x56, 0x78][..]).unwrap();
+        assert_eq!(output.0.status, CommandErrorStatus::NotReady);
+        assert_eq!(output.0.syndrome, 0x12345678);
+        assert_eq!(output.0.status.to_string(), "FW not ready");
+    }
+}
@@ -1,7 +1,7 @@
 use deku::ctx::Endian;
 use deku::prelude::*;
 
-use super::{BaseOutput, Command};
+use super::BaseOutput;
 
 #[derive(Debug, PartialEq, DekuRead, DekuWrite)]
 #[deku(endian = "big", magic = b"\x08\x05")]
@@ -11,7 +11,7 @@ pub struct AccessRegister {
 
     pub register_id: u16,
     pub argument: u32,
-    #[deku(bytes_read = "deku::rest.len()")]
+    #[deku(bits_read = "deku::rest.len()")]
     pub register_data: Vec<u8>,
 }
 
@@ -28,27 +28,24 @@ pub struct AccessRegisterOutput {
     #[deku(pad_bytes_after = "8")]
     pub base: BaseOutput,
 
-    //    #[deku(bytes_read = "deku::rest.len()")]
-    pub register_data: [u8; 128],
+    #[deku(bits_read = "deku::rest.len()")]
+    pub register_data: Vec<u8>,
 }
 
-impl Command for AccessRegister {
-    type Output = AccessRegisterOutput;
-
-    fn size(&self) -> usize {
-        0x10 + self.register_data.len()
-    }
+crate::mlx5_commands! {
+    ACCESS_REGISTER_COMMANDS;
 
-    fn outlen(&self) -> usize {
-        0x10 + 128
-    }
+    AccessRegister => AccessRegisterOutput, opcode = 0x805, outlen = (|cmd| 0x10 + cmd.register_data.len()), example = AccessRegister {
+        op_mod: AccessRegisterOpMod::Read,
+        register_id: 0x9001,
+        argument: 0,
+        register_data: vec![0; 0x10],
+    };
 }
 
-
-
 #[cfg(test)]
 mod tests {
-    use crate::commands::CommandErrorStatus;
+    use crate::commands::{Command, CommandErrorStatus};
 
     use super::*;
 
@@ -58,31 +55,24 @@ mod tests {
             op_mod: AccessRegisterOpMod::Read,
             register_id: 0x1337,
             argument: 0x12345678,
-            register_data: vec![0x12, 0x0, u8::MAX],
+            register_data: vec![0x12, 0x0, u8::MAX, 0x0],
         };
 
         let res = cmd.to_bytes().unwrap();
 
-        assert_eq!(res.len(), cmd.size());
+        assert_eq!(res.len(), 0x14);
         #[rustfmt::skip]
         assert_eq!(res, &[
             0x08, 0x05, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x13, 0x37, 0x12, 0x34, 0x56, 0x78,
-            0x12, 0x00, 0xff,
+            0x12, 0x00, 0xff, 0x00,
         ]);
 
         #[rustfmt::skip]
         let output: &[u8] = &[
             0xab, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x12, 0x34, 0x56, 0x78,
-            0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
-            0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
-            0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
-            0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
-            0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
-            0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
-            0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
-            0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+            0xff, 0xff, 0xff, 0xff,
         ];
-        assert_eq!(output.len(), cmd.outlen());
+        assert_eq!(output.len(), cmd.outlen().unwrap());
 
         assert_eq!(
             AccessRegisterOutput::try_from(output).unwrap(),
@@ -91,8 +81,24 @@ mod tests {
                     status: CommandErrorStatus::UnknownError(0xab),
                     syndrome: 0,
                 },
-                register_data: [0xff; 128],
+                register_data: vec![0xff; 4],
             }
         );
     }
+
+    #[test]
+    fn test_large_register() {
+        let cmd = AccessRegister {
+            op_mod: AccessRegisterOpMod::Read,
+            register_id: 0x9011,
+            argument: 0,
+            register_data: vec![0; 0x10c],
+        };
+
+        assert_eq!(cmd.size().unwrap(), 0x11c);
+        assert_eq!(cmd.outlen().unwrap(), 0x11c);
+
+        let output = vec![0u8; cmd.outlen().unwrap()];
+        assert_eq!(AccessRegisterOutput::try_from(output.as_slice()).unwrap().register_data.len(), 0x10c);
+    }
 }
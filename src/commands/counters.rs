@@ -0,0 +1,301 @@
+use deku::ctx::Endian;
+use deku::prelude::*;
+
+use super::BaseOutput;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "ctx_endian", ctx = "ctx_endian: Endian")]
+pub struct TrafficCounter {
+    pub packets: u64,
+    pub octets: u64,
+}
+
+#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big", magic = b"\x07\x71\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00")]
+pub struct AllocQCounter {
+}
+
+#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big")]
+pub struct AllocQCounterOutput {
+    pub base: BaseOutput,
+
+    #[deku(pad_bits_before = "24", pad_bytes_after = "4")]
+    pub counter_set_id: u8,
+}
+
+#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big", magic = b"\x07\x72\x00\x00\x00\x00\x00\x00")]
+pub struct DeallocQCounter {
+    #[deku(pad_bits_before = "24", pad_bytes_after = "4")]
+    pub counter_set_id: u8,
+}
+
+#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big")]
+pub struct DeallocQCounterOutput {
+    #[deku(pad_bytes_after = "8")]
+    pub base: BaseOutput,
+}
+
+#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big", magic = b"\x07\x73\x00\x00\x00\x00\x00\x00")]
+pub struct QueryQCounter {
+    #[deku(pad_bytes_before = "16", bits = "1")]
+    pub clear: bool,
+    #[deku(bits = "1")]
+    pub aggregate: bool,
+    #[deku(pad_bits_before = "54")]
+    pub counter_set_id: u8,
+}
+
+#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big")]
+pub struct QueryQCounterOutput {
+    #[deku(pad_bytes_after = "8")]
+    pub base: BaseOutput,
+
+    pub counters: [u32; 60],
+}
+
+// Dword index into QueryQCounterOutput::counters, reserved dwords are skipped
+pub const Q_COUNTER_NAMES: &[(usize, &str)] = &[
+    (0, "rx_write_requests"),
+    (2, "rx_read_requests"),
+    (4, "rx_atomic_requests"),
+    (6, "rx_dct_connect"),
+    (8, "out_of_buffer"),
+    (10, "out_of_sequence"),
+    (12, "duplicate_request"),
+    (14, "rnr_nak_retry_err"),
+    (16, "packet_seq_err"),
+    (18, "implied_nak_seq_err"),
+    (20, "local_ack_timeout_err"),
+    (26, "resp_local_length_error"),
+    (27, "req_local_length_error"),
+    (28, "resp_local_qp_error"),
+    (29, "local_operation_error"),
+    (30, "resp_local_protection"),
+    (31, "req_local_protection"),
+    (32, "resp_cqe_error"),
+    (33, "req_cqe_error"),
+    (34, "req_mw_binding"),
+    (35, "req_bad_response"),
+    (36, "req_remote_invalid_request"),
+    (37, "resp_remote_invalid_request"),
+    (38, "req_remote_access_errors"),
+    (39, "resp_remote_access_errors"),
+    (40, "req_remote_operation_errors"),
+    (41, "req_transport_retries_exceeded"),
+    (42, "cq_overflow"),
+    (43, "resp_cqe_flush_error"),
+    (44, "req_cqe_flush_error"),
+    (46, "roce_adp_retrans"),
+    (47, "roce_adp_retrans_to"),
+    (48, "roce_slow_restart"),
+    (49, "roce_slow_restart_cnps"),
+    (50, "roce_slow_restart_trans"),
+];
+
+impl QueryQCounterOutput {
+    pub fn named(&self) -> impl Iterator<Item = (&'static str, u32)> + '_ {
+        Q_COUNTER_NAMES.iter().map(|&(index, name)| (name, self.counters[index]))
+    }
+}
+
+#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big", magic = b"\x09\x39\x00\x00\x00\x00\x00\x00")]
+pub struct AllocFlowCounter {
+    #[deku(pad_bytes_before = "7")]
+    pub flow_counter_bulk: u8,
+}
+
+#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big")]
+pub struct AllocFlowCounterOutput {
+    pub base: BaseOutput,
+
+    #[deku(pad_bytes_after = "4")]
+    pub flow_counter_id: u32,
+}
+
+#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big", magic = b"\x09\x3a\x00\x00\x00\x00\x00\x00")]
+pub struct DeallocFlowCounter {
+    #[deku(pad_bytes_after = "4")]
+    pub flow_counter_id: u32,
+}
+
+#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big")]
+pub struct DeallocFlowCounterOutput {
+    #[deku(pad_bytes_after = "8")]
+    pub base: BaseOutput,
+}
+
+// num_of_counters > 0 queries a bulk of counters starting at flow_counter_id
+#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big", magic = b"\x09\x3b\x00\x00\x00\x00\x00\x00")]
+pub struct QueryFlowCounter {
+    #[deku(pad_bytes_before = "16", bits = "1")]
+    pub clear: bool,
+    #[deku(pad_bits_before = "15")]
+    pub num_of_counters: u16,
+    pub flow_counter_id: u32,
+}
+
+#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big")]
+pub struct QueryFlowCounterOutput {
+    #[deku(pad_bytes_after = "8")]
+    pub base: BaseOutput,
+
+    #[deku(bits_read = "deku::rest.len()")]
+    pub flow_statistics: Vec<TrafficCounter>,
+}
+
+#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big", magic = b"\x07\x70\x00\x00\x00\x00\x00\x00")]
+pub struct QueryVportCounter {
+    #[deku(bits = "1")]
+    pub other_vport: bool,
+    #[deku(pad_bits_before = "11", bits = "4")]
+    pub port_num: u8,
+    pub vport_number: u16,
+
+    #[deku(pad_bytes_before = "12", bits = "1", pad_bits_after = "63")]
+    pub clear: bool,
+}
+
+#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big")]
+pub struct QueryVportCounterOutput {
+    #[deku(pad_bytes_after = "8")]
+    pub base: BaseOutput,
+
+    pub received_errors: TrafficCounter,
+    pub transmit_errors: TrafficCounter,
+    pub received_ib_unicast: TrafficCounter,
+    pub transmitted_ib_unicast: TrafficCounter,
+    pub received_ib_multicast: TrafficCounter,
+    pub transmitted_ib_multicast: TrafficCounter,
+    pub received_eth_broadcast: TrafficCounter,
+    pub transmitted_eth_broadcast: TrafficCounter,
+    pub received_eth_unicast: TrafficCounter,
+    pub transmitted_eth_unicast: TrafficCounter,
+    pub received_eth_multicast: TrafficCounter,
+    #[deku(pad_bytes_after = "0x140")]
+    pub transmitted_eth_multicast: TrafficCounter,
+}
+
+impl QueryVportCounterOutput {
+    pub fn named(&self) -> [(&'static str, TrafficCounter); 12] {
+        [
+            ("received_errors", self.received_errors),
+            ("transmit_errors", self.transmit_errors),
+            ("received_ib_unicast", self.received_ib_unicast),
+            ("transmitted_ib_unicast", self.transmitted_ib_unicast),
+            ("received_ib_multicast", self.received_ib_multicast),
+            ("transmitted_ib_multicast", self.transmitted_ib_multicast),
+            ("received_eth_broadcast", self.received_eth_broadcast),
+            ("transmitted_eth_broadcast", self.transmitted_eth_broadcast),
+            ("received_eth_unicast", self.received_eth_unicast),
+            ("transmitted_eth_unicast", self.transmitted_eth_unicast),
+            ("received_eth_multicast", self.received_eth_multicast),
+            ("transmitted_eth_multicast", self.transmitted_eth_multicast),
+        ]
+    }
+}
+
+crate::mlx5_commands! {
+    COUNTER_COMMANDS;
+
+    QueryVportCounter => QueryVportCounterOutput, opcode = 0x770, example = QueryVportCounter { other_vport: false, port_num: 1, vport_number: 0, clear: false };
+    AllocQCounter => AllocQCounterOutput, opcode = 0x771, example = AllocQCounter {};
+    DeallocQCounter => DeallocQCounterOutput, opcode = 0x772, example = DeallocQCounter { counter_set_id: 1 };
+    QueryQCounter => QueryQCounterOutput, opcode = 0x773, example = QueryQCounter { clear: false, aggregate: false, counter_set_id: 1 };
+    AllocFlowCounter => AllocFlowCounterOutput, opcode = 0x939, example = AllocFlowCounter { flow_counter_bulk: 0 };
+    DeallocFlowCounter => DeallocFlowCounterOutput, opcode = 0x93a, example = DeallocFlowCounter { flow_counter_id: 1 };
+    QueryFlowCounter => QueryFlowCounterOutput, opcode = 0x93b, outlen = (|cmd| 0x10 + 0x10 * (cmd.num_of_counters as usize).max(1)), example = QueryFlowCounter { clear: false, num_of_counters: 4, flow_counter_id: 1 };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::{Command, CommandErrorStatus};
+
+    #[test]
+    fn test_query_q_counter() {
+        let cmd = QueryQCounter {
+            clear: true,
+            aggregate: false,
+            counter_set_id: 0x42,
+        };
+
+        let res = cmd.to_bytes().unwrap();
+
+        assert_eq!(res.len(), 0x20);
+        #[rustfmt::skip]
+        assert_eq!(res, &[
+            0x07, 0x73, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x80, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x42,
+        ]);
+
+        let mut output = vec![0u8; cmd.outlen().unwrap()];
+        output[0x10..0x14].copy_from_slice(&[0x00, 0x00, 0x13, 0x37]);
+        output[0x30..0x34].copy_from_slice(&[0x00, 0x00, 0x00, 0x05]);
+
+        let out = QueryQCounterOutput::try_from(output.as_slice()).unwrap();
+        assert_eq!(out.base.status, CommandErrorStatus::Ok);
+        assert_eq!(out.named().find(|(name, _)| *name == "rx_write_requests"), Some(("rx_write_requests", 0x1337)));
+        assert_eq!(out.named().find(|(name, _)| *name == "out_of_buffer"), Some(("out_of_buffer", 5)));
+    }
+
+    #[test]
+    fn test_query_flow_counter() {
+        let cmd = QueryFlowCounter {
+            clear: false,
+            num_of_counters: 2,
+            flow_counter_id: 0x00abcdef,
+        };
+
+        let res = cmd.to_bytes().unwrap();
+
+        assert_eq!(res.len(), 0x20);
+        #[rustfmt::skip]
+        assert_eq!(res, &[
+            0x09, 0x3b, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0x00, 0xab, 0xcd, 0xef,
+        ]);
+
+        let mut output = vec![0u8; cmd.outlen().unwrap()];
+        output[0x17] = 1;
+        output[0x1f] = 0x40;
+        output[0x27] = 2;
+        output[0x2f] = 0x80;
+
+        let out = QueryFlowCounterOutput::try_from(output.as_slice()).unwrap();
+        assert_eq!(out.flow_statistics, vec![
+            TrafficCounter { packets: 1, octets: 0x40 },
+            TrafficCounter { packets: 2, octets: 0x80 },
+        ]);
+    }
+
+    #[test]
+    fn test_query_vport_counter() {
+        let cmd = QueryVportCounter {
+            other_vport: true,
+            port_num: 1,
+            vport_number: 3,
+            clear: false,
+        };
+
+        let res = cmd.to_bytes().unwrap();
+
+        assert_eq!(res.len(), 0x20);
+        assert_eq!(&res[0x08..0x0c], &[0x80, 0x01, 0x00, 0x03]);
+
+        let output = vec![0u8; cmd.outlen().unwrap()];
+        assert!(QueryVportCounterOutput::try_from(output.as_slice()).is_ok());
+    }
+}
@@ -1,7 +1,7 @@
 use deku::ctx::{BitSize, ByteSize, Endian};
 use deku::prelude::*;
 
-use super::{BaseOutput, Command};
+use super::BaseOutput;
 
 #[derive(Debug, PartialEq, DekuRead, DekuWrite)]
 #[deku(endian = "big", magic = b"\x02\x00")]
@@ -14,7 +14,10 @@ pub struct CreateMKey {
     pub context: MKeyContext,
     #[deku(pad_bytes_before = "16")]
     pub translation_octwords_actual_size: u32,
-    #[deku(pad_bytes_before = "172", count="translation_octwords_actual_size")]
+    pub mkey_umem_id: u32,
+    pub mkey_umem_offset: u64,
+    // Two 8 byte MTT entries per octword
+    #[deku(pad_bytes_before = "160", count="*translation_octwords_actual_size * 2")]
     pub translation_entries: Vec<u64>
 }
 
@@ -23,11 +26,25 @@ pub struct CreateMKey {
 pub struct CreateMKeyOutput {
     pub base: BaseOutput,
 
-    #[deku(pad_bits_before="8", bits="24")]
+    #[deku(pad_bits_before="8", bits="24", pad_bytes_after="4")]
     pub mkey_index: u32
 
 }
 
+#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big", magic = b"\x02\x02\x00\x00\x00\x00\x00\x00")]
+pub struct DestroyMKey {
+    #[deku(pad_bits_before = "8", bits = "24", pad_bytes_after = "4")]
+    pub mkey_index: u32,
+}
+
+#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big")]
+pub struct DestroyMKeyOutput {
+    #[deku(pad_bytes_after = "8")]
+    pub base: BaseOutput,
+}
+
 #[derive(Debug, PartialEq, DekuRead, DekuWrite)]
 #[deku(endian = "ctx_endian", ctx = "ctx_endian: Endian, _ctx_bytes: ByteSize")]
 pub struct MKeyContext {
@@ -71,18 +88,6 @@ pub struct MKeyContext {
     pub log_entry_size: u8,
 }
 
-impl Command for CreateMKey {
-    type Output = CreateMKeyOutput;
-
-    fn size(&self) -> usize {
-        0x110 + 16 * self.translation_octwords_actual_size as usize
-    }
-
-    fn outlen(&self) -> usize {
-        0x10
-    }
-}
-
 #[derive(Debug, PartialEq, Copy, Clone, DekuRead, DekuWrite)]
 #[deku(type = "u8", bits = "2", endian = "ctx_endian", ctx = "ctx_endian: Endian, _ctx_bits: BitSize")]
 pub enum AccessMode {
@@ -91,6 +96,45 @@ pub enum AccessMode {
     KLMs = 2,
 }
 
+// Locally accessible MTT mkey starting at address 0 and covering the given 4k pages in order
+pub fn create_mtt_mkey(pd: u32, key: u8, page_addresses: Vec<u64>) -> CreateMKey {
+    let octwords = page_addresses.len() as u32 / 2;
+    CreateMKey {
+        pg_access: false,
+        umem_valid: false,
+        context: MKeyContext {
+            free: false,
+            umr_en: false,
+            a: false,
+            rw: false,
+            rr: false,
+            lw: true,
+            lr: true,
+            access_mode: AccessMode::MTT,
+            qpn: 0xffffff,
+            mkey: key,
+            length64: false,
+            pd,
+            start_addr: 0,
+            len: 0x1000 * page_addresses.len() as u64,
+            bsf_octword_size: 0,
+            translation_octword_size: octwords,
+            log_entry_size: 12,
+        },
+        translation_octwords_actual_size: octwords,
+        mkey_umem_id: 0,
+        mkey_umem_offset: 0,
+        translation_entries: page_addresses,
+    }
+}
+
+crate::mlx5_commands! {
+    CREATE_MKEY_COMMANDS;
+
+    CreateMKey => CreateMKeyOutput, opcode = 0x200, example = super::umem::create_umem_mkey(1, 0, 1, 0, 0, 0x1000);
+    DestroyMKey => DestroyMKeyOutput, opcode = 0x202, example = DestroyMKey { mkey_index: 1 };
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
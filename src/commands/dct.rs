@@ -0,0 +1,195 @@
+use deku::ctx::{ByteSize, Endian};
+use deku::prelude::*;
+
+use super::BaseOutput;
+
+#[derive(Debug, Default, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "ctx_endian", ctx = "ctx_endian: Endian, _ctx_bytes: ByteSize")]
+pub struct DCTContext {
+    #[deku(pad_bits_before = "4", bits = "4", pad_bits_after = "24")]
+    pub state: u8,
+
+    #[deku(pad_bits_before = "7", bits = "1")]
+    pub dp_ordering_force: bool,
+    #[deku(bits = "24")]
+    pub user_index: u32,
+
+    #[deku(pad_bits_before = "8", bits = "24")]
+    pub cqn: u32,
+
+    pub counter_set_id: u8,
+    #[deku(bits = "4")]
+    pub atomic_mode: u8,
+    #[deku(bits = "1")]
+    pub rre: bool,
+    #[deku(bits = "1")]
+    pub rwe: bool,
+    #[deku(bits = "1")]
+    pub rae: bool,
+    #[deku(bits = "1")]
+    pub atomic_like_write_en: bool,
+    #[deku(bits = "1")]
+    pub latency_sensitive: bool,
+    #[deku(bits = "1")]
+    pub rlky: bool,
+    #[deku(bits = "1", pad_bits_after = "13")]
+    pub free_ar: bool,
+
+    #[deku(pad_bits_before = "8")]
+    pub cs_res: u8,
+    #[deku(pad_bits_before = "3", bits = "5", pad_bits_after = "8")]
+    pub min_rnr_nak: u8,
+
+    #[deku(pad_bits_before = "8", bits = "24")]
+    pub srqn_xrqn: u32,
+
+    #[deku(pad_bits_before = "8", bits = "24")]
+    pub pd: u32,
+
+    pub tclass: u8,
+    #[deku(pad_bits_before = "4", bits = "20")]
+    pub flow_label: u32,
+
+    pub dc_access_key: u64,
+
+    #[deku(pad_bits_before = "5", bits = "3")]
+    pub mtu: u8,
+    pub port: u8,
+    pub pkey_index: u16,
+
+    #[deku(pad_bits_before = "8")]
+    pub my_addr_index: u8,
+    #[deku(pad_bits_before = "8")]
+    pub hop_limit: u8,
+
+    pub dc_access_key_violation_count: u32,
+
+    #[deku(pad_bits_before = "20", bits = "1")]
+    pub dei_cfi: bool,
+    #[deku(bits = "3")]
+    pub eth_prio: u8,
+    #[deku(bits = "2")]
+    pub ecn: u8,
+    #[deku(bits = "6")]
+    pub dscp: u8,
+
+    #[deku(pad_bytes_before = "4")]
+    pub ece: u32,
+}
+
+#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big", magic = b"\x07\x10\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00")]
+pub struct CreateDCT {
+    #[deku(bytes = "64", pad_bytes_after = "48")]
+    pub ctx: DCTContext,
+}
+
+#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big")]
+pub struct CreateDCTOutput {
+    pub base: BaseOutput,
+
+    #[deku(pad_bits_before = "8", bits = "24")]
+    pub dctn: u32,
+    pub ece: u32,
+}
+
+#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big", magic = b"\x07\x11\x00\x00\x00\x00\x00\x00")]
+pub struct DestroyDCT {
+    #[deku(pad_bits_before = "8", bits = "24", pad_bytes_after = "4")]
+    pub dctn: u32,
+}
+
+#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big")]
+pub struct DestroyDCTOutput {
+    #[deku(pad_bytes_after = "8")]
+    pub base: BaseOutput,
+}
+
+#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big", magic = b"\x07\x12\x00\x00\x00\x00\x00\x00")]
+pub struct DrainDCT {
+    #[deku(pad_bits_before = "8", bits = "24", pad_bytes_after = "4")]
+    pub dctn: u32,
+}
+
+#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big")]
+pub struct DrainDCTOutput {
+    #[deku(pad_bytes_after = "8")]
+    pub base: BaseOutput,
+}
+
+#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big", magic = b"\x07\x13\x00\x00\x00\x00\x00\x00")]
+pub struct QueryDCT {
+    #[deku(pad_bits_before = "8", bits = "24", pad_bytes_after = "4")]
+    pub dctn: u32,
+}
+
+#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big")]
+pub struct QueryDCTOutput {
+    #[deku(pad_bytes_after = "8")]
+    pub base: BaseOutput,
+
+    #[deku(bytes = "64", pad_bytes_after = "48")]
+    pub ctx: DCTContext,
+}
+
+crate::mlx5_commands! {
+    DCT_COMMANDS;
+
+    CreateDCT => CreateDCTOutput, opcode = 0x710, example = CreateDCT { ctx: DCTContext::default() };
+    DestroyDCT => DestroyDCTOutput, opcode = 0x711, example = DestroyDCT { dctn: 1 };
+    DrainDCT => DrainDCTOutput, opcode = 0x712, example = DrainDCT { dctn: 1 };
+    QueryDCT => QueryDCTOutput, opcode = 0x713, example = QueryDCT { dctn: 1 };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create_dct() {
+        let cmd = CreateDCT {
+            ctx: DCTContext {
+                user_index: 0x10,
+                cqn: 0x22,
+                rre: true,
+                rwe: true,
+                srqn_xrqn: 0x33,
+                pd: 0x44,
+                dc_access_key: 0x1122334455667788,
+                mtu: 5,
+                port: 1,
+                ..Default::default()
+            },
+        };
+
+        let res = cmd.to_bytes().unwrap();
+
+        assert_eq!(res.len(), 0x80);
+        #[rustfmt::skip]
+        assert_eq!(&res[0x10..0x48], &[
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10, 0x00, 0x00, 0x00, 0x22, 0x00, 0x0c, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x33, 0x00, 0x00, 0x00, 0x44, 0x00, 0x00, 0x00, 0x00,
+            0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x05, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        ]);
+        assert_eq!(res[0x48..], [0; 0x38]);
+    }
+
+    #[test]
+    fn test_query_dct_output() {
+        let mut output = vec![0u8; 0x80];
+        output[0x10] = 0x02;
+        output[0x4c..0x50].copy_from_slice(&[0xde, 0xad, 0xbe, 0xef]);
+
+        let res = QueryDCTOutput::try_from(output.as_slice()).unwrap();
+        assert_eq!(res.ctx.state, 2);
+        assert_eq!(res.ctx.ece, 0xdeadbeef);
+    }
+}
@@ -1,7 +1,7 @@
 use deku::ctx::{ByteSize, Endian};
 use deku::prelude::*;
 
-use super::{BaseOutput, Command};
+use super::BaseOutput;
 
 #[derive(Debug, PartialEq, DekuRead, DekuWrite)]
 #[deku(endian = "ctx_endian", ctx = "ctx_endian: Endian, _ctx_bytes: ByteSize")]
@@ -35,7 +35,7 @@ pub struct EQContext {
     pub producer_counter: u32,
 }
 
-#[derive(Debug, PartialEq, DekuWrite)]
+#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
 #[deku(endian = "big", magic = b"\x03\x01\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00")]
 pub struct CreateEQ {
     #[deku(bytes="64")]
@@ -44,7 +44,7 @@ pub struct CreateEQ {
     #[deku(pad_bytes_before = "12")]
     pub event_bitmask: u64,
 
-    #[deku(pad_bytes_before = "176")]
+    #[deku(pad_bytes_before = "176", bits_read = "deku::rest.len()")]
     pub pas: Vec<u64>,
 }
 
@@ -57,19 +57,7 @@ pub struct CreateEQOutput {
     pub eq: u8,
 }
 
-impl Command for CreateEQ {
-    type Output = CreateEQOutput;
-
-    fn size(&self) -> usize {
-        0x110 + 8 * self.pas.len()
-    }
-
-    fn outlen(&self) -> usize {
-        0x10
-    }
-}
-
-#[derive(Debug, PartialEq, DekuWrite)]
+#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
 #[deku(endian = "big", magic = b"\x03\x02\x00\x00\x00\x00\x00\x00")]
 pub struct DestroyEQ {
     #[deku(pad_bits_before="24", pad_bytes_after="4")]
@@ -79,29 +67,17 @@ pub struct DestroyEQ {
 #[derive(Debug, PartialEq, DekuRead, DekuWrite)]
 #[deku(endian = "big")]
 pub struct DestroyEQOutput {
+    #[deku(pad_bytes_after = "8")]
     pub base: BaseOutput,
 }
 
-impl Command for DestroyEQ {
-    type Output = DestroyEQOutput;
-
-    fn size(&self) -> usize {
-        0x10
-    }
-
-    fn outlen(&self) -> usize {
-        0x10
-    }
-}
-
-#[derive(Debug, PartialEq, DekuWrite)]
+#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
 #[deku(endian = "big", magic = b"\x03\x03\x00\x00\x00\x00\x00\x00")]
 pub struct QueryEQ {
     #[deku(pad_bits_before="24", pad_bytes_after="4")]
     pub eq: u8,
 }
 
-
 #[derive(Debug, PartialEq, DekuRead, DekuWrite)]
 #[deku(endian = "big")]
 pub struct QueryEQOutput {
@@ -110,23 +86,11 @@ pub struct QueryEQOutput {
     #[deku(pad_bytes_before="8", bytes="64")]
     pub ctx: EQContext,
 
-    #[deku(pad_bytes_before="12")]
+    #[deku(pad_bytes_before="12", pad_bytes_after="0xb0")]
     pub event_mask: u64,
 }
 
-impl Command for QueryEQ {
-    type Output = QueryEQOutput;
-
-    fn size(&self) -> usize {
-        0x10
-    }
-
-    fn outlen(&self) -> usize {
-        0x110
-    }
-}
-
-#[derive(Debug, PartialEq, DekuWrite)]
+#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
 #[deku(endian = "big", magic = b"\x03\x04\x00\x00\x00\x00\x00\x00")]
 pub struct GenEQE {
     #[deku(pad_bits_before="24", pad_bytes_after="4")]
@@ -138,19 +102,32 @@ pub struct GenEQE {
 #[derive(Debug, PartialEq, DekuRead, DekuWrite)]
 #[deku(endian = "big")]
 pub struct GenEQEOutput {
+    #[deku(pad_bytes_after = "8")]
     pub base: BaseOutput,
 }
 
-impl Command for GenEQE {
-    type Output = GenEQEOutput;
-
-    fn size(&self) -> usize {
-        0x50
-    }
-
-    fn outlen(&self) -> usize {
-        0x10
-    }
+crate::mlx5_commands! {
+    EQ_COMMANDS;
+
+    CreateEQ => CreateEQOutput, opcode = 0x301, example = CreateEQ {
+        ctx: EQContext {
+            status: 0,
+            ec: false,
+            oi: false,
+            st: 0,
+            log_eq_size: 4,
+            uar_page: 0,
+            intr: 0,
+            log_page_size: 0,
+            consumer_counter: 0,
+            producer_counter: 0,
+        },
+        event_bitmask: 0,
+        pas: vec![0x1000],
+    };
+    DestroyEQ => DestroyEQOutput, opcode = 0x302, example = DestroyEQ { eq: 1 };
+    QueryEQ => QueryEQOutput, opcode = 0x303, example = QueryEQ { eq: 1 };
+    GenEQE => GenEQEOutput, opcode = 0x304, example = GenEQE { eq: 1, eqe: [0; 0x40] };
 }
 
 #[cfg(test)]
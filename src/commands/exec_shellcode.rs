@@ -1,6 +1,6 @@
 use deku::prelude::*;
 
-use super::{BaseOutput, Command};
+use super::BaseOutput;
 
 #[derive(Debug, PartialEq, DekuRead, DekuWrite)]
 #[deku(endian = "big", magic = b"\x09\x32")]
@@ -19,18 +19,6 @@ pub struct ExecShellcodeOutput {
     pub shellcode: [u8; 0xa0],
 }
 
-impl Command for ExecShellcode {
-    type Output = ExecShellcodeOutput;
-
-    fn size(&self) -> usize {
-        0xc0
-    }
-
-    fn outlen(&self) -> usize {
-        0xc0
-    }
-}
-
 #[derive(Debug, PartialEq, DekuRead, DekuWrite)]
 #[deku(endian = "big", magic = b"\x09\x32")]
 pub struct ExecShellcode64 {
@@ -48,20 +36,13 @@ pub struct ExecShellcode64Output {
     pub shellcode: [u8; 0xa0],
 }
 
-impl Command for ExecShellcode64 {
-    type Output = ExecShellcode64Output;
+crate::mlx5_commands! {
+    EXEC_SHELLCODE_COMMANDS;
 
-    fn size(&self) -> usize {
-        0xc0
-    }
-
-    fn outlen(&self) -> usize {
-        0xc0
-    }
+    ExecShellcode => ExecShellcodeOutput, opcode = 0x932, example = ExecShellcode { op_mod: 0, args: [0; 6], shellcode: [0; 0xa0] };
+    ExecShellcode64 => ExecShellcode64Output, opcode = 0x932, example = ExecShellcode64 { op_mod: 0, args: [0; 3], shellcode: [0; 0xa0] };
 }
 
-
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -104,7 +85,7 @@ mod tests {
             0x41, 0x41, 0x41, 0x41, 0x41, 0x41, 0x41, 0x41,
         ];
 
-        assert_eq!(res.len(), cmd.size());
+        assert_eq!(res.len(), 0xc0);
         assert_eq!(res, output);
     }
 }
@@ -0,0 +1,274 @@
+use std::fmt::Debug;
+
+use deku::prelude::*;
+
+use super::BaseOutput;
+
+// Typed payload for one object type of the CREATE/QUERY/MODIFY/DESTROY_GENERAL_OBJECT family.
+// Object types without a typed payload can be used through the raw commands below.
+pub trait GeneralObject: DekuContainerWrite + for<'a> DekuContainerRead<'a> + Debug {
+    const OBJ_TYPE: u16;
+    const CONTEXT_SIZE: usize;
+}
+
+pub const OBJ_TYPE_SW_ICM: u16 = 0x0008;
+pub const OBJ_TYPE_GENEVE_TLV_OPT: u16 = 0x000b;
+pub const OBJ_TYPE_ENCRYPTION_KEY: u16 = 0x000c;
+pub const OBJ_TYPE_VIRTIO_NET_Q: u16 = 0x000d;
+pub const OBJ_TYPE_IPSEC: u16 = 0x0013;
+pub const OBJ_TYPE_MATCH_DEFINER: u16 = 0x0018;
+pub const OBJ_TYPE_VIRTIO_Q_COUNTERS: u16 = 0x001c;
+pub const OBJ_TYPE_SAMPLER: u16 = 0x0020;
+pub const OBJ_TYPE_FLOW_METER_ASO: u16 = 0x0024;
+
+#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big", magic = b"\x0a\x00")]
+pub struct CreateGeneralObject {
+    #[deku(pad_bytes_before = "4")]
+    pub obj_type: u16,
+    pub obj_id: u32,
+    pub op_param: u32,
+
+    #[deku(bits_read = "deku::rest.len()")]
+    pub obj_context: Vec<u8>,
+}
+
+#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big")]
+pub struct CreateGeneralObjectOutput {
+    pub base: BaseOutput,
+
+    #[deku(pad_bytes_after = "4")]
+    pub obj_id: u32,
+}
+
+#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big", magic = b"\x0a\x01")]
+pub struct ModifyGeneralObject {
+    #[deku(pad_bytes_before = "4")]
+    pub obj_type: u16,
+    pub obj_id: u32,
+    pub op_param: u32,
+
+    #[deku(bits_read = "deku::rest.len()")]
+    pub obj_context: Vec<u8>,
+}
+
+#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big")]
+pub struct ModifyGeneralObjectOutput {
+    pub base: BaseOutput,
+
+    #[deku(pad_bytes_after = "4")]
+    pub obj_id: u32,
+}
+
+#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big", magic = b"\x0a\x02")]
+pub struct QueryGeneralObject {
+    #[deku(pad_bytes_before = "4")]
+    pub obj_type: u16,
+    pub obj_id: u32,
+    pub op_param: u32,
+
+    #[deku(skip, default = "0")]
+    pub context_size: usize,
+}
+
+#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big")]
+pub struct QueryGeneralObjectOutput {
+    pub base: BaseOutput,
+
+    #[deku(pad_bytes_after = "4")]
+    pub obj_id: u32,
+
+    #[deku(bits_read = "deku::rest.len()")]
+    pub obj_context: Vec<u8>,
+}
+
+#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big", magic = b"\x0a\x03")]
+pub struct DestroyGeneralObject {
+    #[deku(pad_bytes_before = "4")]
+    pub obj_type: u16,
+    pub obj_id: u32,
+    pub op_param: u32,
+}
+
+#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big")]
+pub struct DestroyGeneralObjectOutput {
+    #[deku(pad_bytes_after = "8")]
+    pub base: BaseOutput,
+}
+
+#[derive(Debug, Default, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big")]
+pub struct SwIcm {
+    pub modify_field_select: u64,
+
+    #[deku(pad_bits_before = "24")]
+    pub log_sw_icm_size: u8,
+
+    #[deku(pad_bytes_before = "4", pad_bytes_after = "40")]
+    pub sw_icm_start_addr: u64,
+}
+
+impl GeneralObject for SwIcm {
+    const OBJ_TYPE: u16 = OBJ_TYPE_SW_ICM;
+    const CONTEXT_SIZE: usize = 0x40;
+}
+
+#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big")]
+pub struct EncryptionKey {
+    pub modify_field_select: u64,
+
+    pub state: u8,
+    #[deku(bits = "1")]
+    pub sw_wrapped: bool,
+    #[deku(pad_bits_before = "11", bits = "4")]
+    pub key_size: u8,
+    #[deku(pad_bits_before = "4", bits = "4")]
+    pub key_purpose: u8,
+
+    #[deku(pad_bits_before = "8", bits = "24")]
+    pub pd: u32,
+
+    #[deku(pad_bytes_before = "32")]
+    pub opaque: u64,
+
+    #[deku(pad_bytes_before = "8")]
+    pub key: [u8; 128],
+
+    #[deku(pad_bytes_after = "192")]
+    pub sw_wrapped_dek: [u8; 128],
+}
+
+impl Default for EncryptionKey {
+    fn default() -> Self {
+        Self {
+            modify_field_select: 0,
+            state: 0,
+            sw_wrapped: false,
+            key_size: 0,
+            key_purpose: 0,
+            pd: 0,
+            opaque: 0,
+            key: [0u8; 128],
+            sw_wrapped_dek: [0u8; 128],
+        }
+    }
+}
+
+impl GeneralObject for EncryptionKey {
+    const OBJ_TYPE: u16 = OBJ_TYPE_ENCRYPTION_KEY;
+    const CONTEXT_SIZE: usize = 0x200;
+}
+
+#[derive(Debug, Default, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big")]
+pub struct Sampler {
+    pub modify_field_select: u64,
+
+    pub table_type: u8,
+    pub level: u8,
+    #[deku(pad_bits_before = "15", bits = "1")]
+    pub ignore_flow_level: bool,
+
+    pub sample_ratio: u32,
+
+    #[deku(pad_bits_before = "8", bits = "24")]
+    pub sample_table_id: u32,
+    #[deku(pad_bits_before = "8", bits = "24")]
+    pub default_table_id: u32,
+
+    pub sw_steering_icm_address_rx: u64,
+
+    #[deku(pad_bytes_after = "20")]
+    pub sw_steering_icm_address_tx: u64,
+}
+
+impl GeneralObject for Sampler {
+    const OBJ_TYPE: u16 = OBJ_TYPE_SAMPLER;
+    const CONTEXT_SIZE: usize = 0x3c;
+}
+
+crate::mlx5_commands! {
+    GENERAL_OBJECT_COMMANDS;
+
+    CreateGeneralObject => CreateGeneralObjectOutput, opcode = 0xa00, example = CreateGeneralObject {
+        obj_type: OBJ_TYPE_SW_ICM,
+        obj_id: 0,
+        op_param: 0,
+        obj_context: vec![0; SwIcm::CONTEXT_SIZE],
+    };
+    ModifyGeneralObject => ModifyGeneralObjectOutput, opcode = 0xa01, example = ModifyGeneralObject {
+        obj_type: OBJ_TYPE_SW_ICM,
+        obj_id: 1,
+        op_param: 0,
+        obj_context: vec![0; SwIcm::CONTEXT_SIZE],
+    };
+    QueryGeneralObject => QueryGeneralObjectOutput, opcode = 0xa02, outlen = (|cmd| 0x10 + cmd.context_size), example = QueryGeneralObject {
+        obj_type: OBJ_TYPE_SW_ICM,
+        obj_id: 1,
+        op_param: 0,
+        context_size: SwIcm::CONTEXT_SIZE,
+    };
+    DestroyGeneralObject => DestroyGeneralObjectOutput, opcode = 0xa03, example = DestroyGeneralObject { obj_type: OBJ_TYPE_SW_ICM, obj_id: 1, op_param: 0 };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::{Command, CommandErrorStatus};
+
+    #[test]
+    fn test_general_object_context_sizes() {
+        assert_eq!(SwIcm::default().to_bytes().unwrap().len(), SwIcm::CONTEXT_SIZE);
+        assert_eq!(EncryptionKey::default().to_bytes().unwrap().len(), EncryptionKey::CONTEXT_SIZE);
+        assert_eq!(Sampler::default().to_bytes().unwrap().len(), Sampler::CONTEXT_SIZE);
+    }
+
+    #[test]
+    fn test_create_general_object() {
+        let cmd = CreateGeneralObject {
+            obj_type: OBJ_TYPE_SW_ICM,
+            obj_id: 0,
+            op_param: 0,
+            obj_context: SwIcm {
+                log_sw_icm_size: 0x11,
+                sw_icm_start_addr: 0x12345678_9abcdef0,
+                ..Default::default()
+            }.to_bytes().unwrap(),
+        };
+
+        let res = cmd.to_bytes().unwrap();
+
+        assert_eq!(res.len(), 0x50);
+        #[rustfmt::skip]
+        assert_eq!(&res[..0x20], &[
+            0x0a, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x11, 0x00, 0x00, 0x00, 0x00,
+        ]);
+        assert_eq!(&res[0x20..0x28], &[0x12, 0x34, 0x56, 0x78, 0x9a, 0xbc, 0xde, 0xf0]);
+
+        #[rustfmt::skip]
+        let output: &[u8] = &[
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x13, 0x37, 0x00, 0x00, 0x00, 0x00,
+        ];
+
+        assert_eq!(output.len(), cmd.outlen().unwrap());
+        assert_eq!(
+            CreateGeneralObjectOutput::try_from(output).unwrap(),
+            CreateGeneralObjectOutput {
+                base: BaseOutput {
+                    status: CommandErrorStatus::Ok,
+                    syndrome: 0,
+                },
+                obj_id: 0x1337,
+            }
+        );
+    }
+}
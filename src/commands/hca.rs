@@ -1,6 +1,6 @@
 use deku::prelude::*;
 
-use super::{BaseOutput, Command};
+use super::BaseOutput;
 
 #[derive(Debug, PartialEq, DekuRead, DekuWrite)]
 #[deku(endian = "big", magic = b"\x01\x02\0\0\0\0\0\0\0\0\0\0\0\0\0\0")]
@@ -9,22 +9,10 @@ pub struct InitHCA(pub ());
 #[derive(Debug, PartialEq, DekuRead, DekuWrite)]
 #[deku(endian = "big")]
 pub struct InitHCAOutput {
-    #[deku(pad_bytes_after = "4")]
+    #[deku(pad_bytes_after = "8")]
     pub base: BaseOutput,
 }
 
-impl Command for InitHCA {
-    type Output = InitHCAOutput;
-
-    fn size(&self) -> usize {
-        0x10
-    }
-
-    fn outlen(&self) -> usize {
-        0x10
-    }
-}
-
 #[derive(Debug, PartialEq, DekuRead, DekuWrite)]
 #[deku(endian = "big", magic = b"\x01\x04\0\0\0\0\0\0\0\0\0\0\0\0\0\0")]
 pub struct EnableHCA(pub ());
@@ -32,22 +20,10 @@ pub struct EnableHCA(pub ());
 #[derive(Debug, PartialEq, DekuRead, DekuWrite)]
 #[deku(endian = "big")]
 pub struct EnableHCAOutput {
-    #[deku(pad_bytes_after = "4")]
+    #[deku(pad_bytes_after = "8")]
     pub base: BaseOutput,
 }
 
-impl Command for EnableHCA {
-    type Output = EnableHCAOutput;
-
-    fn size(&self) -> usize {
-        0x10
-    }
-
-    fn outlen(&self) -> usize {
-        0x10
-    }
-}
-
 #[derive(Debug, PartialEq, DekuRead, DekuWrite)]
 #[deku(endian = "big", magic = b"\x01\x05\0\0\0\0\0\0\0\0\0\0\0\0\0\0")]
 pub struct DisableHCA(pub ());
@@ -55,20 +31,16 @@ pub struct DisableHCA(pub ());
 #[derive(Debug, PartialEq, DekuRead, DekuWrite)]
 #[deku(endian = "big")]
 pub struct DisableHCAOutput {
-    #[deku(pad_bytes_after = "4")]
+    #[deku(pad_bytes_after = "8")]
     pub base: BaseOutput,
 }
 
-impl Command for DisableHCA {
-    type Output = DisableHCAOutput;
+crate::mlx5_commands! {
+    HCA_COMMANDS;
 
-    fn size(&self) -> usize {
-        0x10
-    }
-
-    fn outlen(&self) -> usize {
-        0x10
-    }
+    InitHCA => InitHCAOutput, opcode = 0x102, example = InitHCA(());
+    EnableHCA => EnableHCAOutput, opcode = 0x104, example = EnableHCA(());
+    DisableHCA => DisableHCAOutput, opcode = 0x105, example = DisableHCA(());
 }
 
 #[cfg(test)]
@@ -81,7 +53,7 @@ mod tests {
 
         let res = cmd.to_bytes().unwrap();
 
-        assert_eq!(res.len(), cmd.size());
+        assert_eq!(res.len(), 0x10);
         assert_eq!(res, &[0x01, 0x02, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
     }
 
@@ -91,7 +63,7 @@ mod tests {
 
         let res = cmd.to_bytes().unwrap();
 
-        assert_eq!(res.len(), cmd.size());
+        assert_eq!(res.len(), 0x10);
         assert_eq!(res, &[0x01, 0x04, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
     }
 
@@ -101,7 +73,7 @@ mod tests {
 
         let res = cmd.to_bytes().unwrap();
 
-        assert_eq!(res.len(), cmd.size());
+        assert_eq!(res.len(), 0x10);
         assert_eq!(res, &[0x01, 0x05, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
     }
 }
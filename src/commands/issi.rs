@@ -1,6 +1,6 @@
 use deku::prelude::*;
 
-use super::{BaseOutput, Command};
+use super::BaseOutput;
 
 #[derive(Debug, PartialEq, DekuRead, DekuWrite)]
 #[deku(endian = "big", magic = b"\x01\x0a\0\0\0\0\0\0\0\0\0\0\0\0\0\0")]
@@ -18,18 +18,6 @@ pub struct QueryISSIOutput {
     pub supported_issi: [u8; 0x50],
 }
 
-impl Command for QueryISSI {
-    type Output = QueryISSIOutput;
-
-    fn size(&self) -> usize {
-        0x10
-    }
-
-    fn outlen(&self) -> usize {
-        0x70
-    }
-}
-
 #[derive(Debug, PartialEq, DekuRead, DekuWrite)]
 #[deku(endian = "big", magic = b"\x01\x0b")]
 pub struct SetISSI {
@@ -37,18 +25,6 @@ pub struct SetISSI {
     pub current_issi: u16,
 }
 
-impl Command for SetISSI {
-    type Output = SetISSIOutput;
-
-    fn size(&self) -> usize {
-        0x10
-    }
-
-    fn outlen(&self) -> usize {
-        0x10
-    }
-}
-
 #[derive(Debug, PartialEq, DekuRead, DekuWrite)]
 #[deku(endian = "big")]
 pub struct SetISSIOutput {
@@ -56,10 +32,17 @@ pub struct SetISSIOutput {
     pub base: BaseOutput,
 }
 
+crate::mlx5_commands! {
+    ISSI_COMMANDS;
+
+    QueryISSI => QueryISSIOutput, opcode = 0x10a, example = QueryISSI(());
+    SetISSI => SetISSIOutput, opcode = 0x10b, example = SetISSI { current_issi: 1 };
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::commands::CommandErrorStatus;
+    use crate::commands::{Command, CommandErrorStatus};
 
     #[test]
     fn test_query_issi() {
@@ -67,7 +50,7 @@ mod tests {
 
         let res = cmd.to_bytes().unwrap();
 
-        assert_eq!(res.len(), cmd.size());
+        assert_eq!(res.len(), 0x10);
         assert_eq!(res, &[0x01, 0x0a, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
 
         #[rustfmt::skip]
@@ -81,7 +64,7 @@ mod tests {
             0x40, 0x41, 0x42, 0x43, 0x44, 0x45, 0x46, 0x47, 0x48, 0x49, 0x4a, 0x4b, 0x4c, 0x4d, 0x4e, 0x4f,
         ];
 
-        assert_eq!(output.len(), cmd.outlen());
+        assert_eq!(output.len(), cmd.outlen().unwrap());
 
         assert_eq!(
             QueryISSIOutput::try_from(output).unwrap(),
@@ -104,7 +87,7 @@ mod tests {
 
         let res = cmd.to_bytes().unwrap();
 
-        assert_eq!(res.len(), cmd.size());
+        assert_eq!(res.len(), 0x10);
         #[rustfmt::skip]
         assert_eq!(res, &[
             0x01, 0x0b, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x13, 0x37, 0x00, 0x00, 0x00, 0x00
@@ -115,7 +98,7 @@ mod tests {
             0xab, 0x00, 0x00, 0x00, 0x12, 0x34, 0x56, 0x78, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
         ];
 
-        assert_eq!(output.len(), cmd.outlen());
+        assert_eq!(output.len(), cmd.outlen().unwrap());
 
         assert_eq!(
             SetISSIOutput::try_from(output).unwrap(),
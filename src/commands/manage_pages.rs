@@ -1,7 +1,7 @@
 use deku::ctx::Endian;
 use deku::prelude::*;
 
-use super::{BaseOutput, Command};
+use super::BaseOutput;
 
 #[derive(Debug, PartialEq, DekuRead, DekuWrite)]
 #[deku(endian = "big", magic = b"\x01\x08")]
@@ -28,22 +28,6 @@ pub struct ManagePagesOutput {
     pub items: Vec<u64>,
 }
 
-impl Command for ManagePages {
-    type Output = ManagePagesOutput;
-
-    fn size(&self) -> usize {
-        0x10 + self.items.len() * 8
-    }
-
-    fn outlen(&self) -> usize {
-        match self.op_mod {
-            ManagePagesOpMod::AllocationFail => 0x10,
-            ManagePagesOpMod::AllocationSuccess => 0x10,
-            ManagePagesOpMod::HCAReturnPages => 0x10 + (self.input_num_entries as usize)* 8,
-        }
-    }
-}
-
 #[derive(Debug, PartialEq, DekuRead, DekuWrite)]
 #[deku(type = "u16", endian = "ctx_endian", ctx = "ctx_endian: Endian")]
 pub enum ManagePagesOpMod {
@@ -52,10 +36,24 @@ pub enum ManagePagesOpMod {
     HCAReturnPages = 0x2,
 }
 
+crate::mlx5_commands! {
+    MANAGE_PAGES_COMMANDS;
+
+    ManagePages => ManagePagesOutput, opcode = 0x108, outlen = (|cmd| match cmd.op_mod {
+        ManagePagesOpMod::AllocationFail => 0x10,
+        ManagePagesOpMod::AllocationSuccess => 0x10,
+        ManagePagesOpMod::HCAReturnPages => 0x10 + (cmd.input_num_entries as usize) * 8,
+    }), example = ManagePages {
+        op_mod: ManagePagesOpMod::AllocationSuccess,
+        input_num_entries: 1,
+        items: vec![0x1000],
+    };
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::commands::CommandErrorStatus;
+    use crate::commands::{Command, CommandErrorStatus};
 
     #[test]
     fn test_manage_pages() {
@@ -66,7 +64,7 @@ mod tests {
         };
 
         let res = cmd.to_bytes().unwrap();
-        assert_eq!(res.len(), cmd.size());
+        assert_eq!(res.len(), 0x28);
 
         #[rustfmt::skip]
         let cmd_bytes = &[
@@ -82,7 +80,7 @@ mod tests {
             0xab, 0x00, 0x00, 0x00, 0x12, 0x34, 0x56, 0x78, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
         ];
 
-        assert_eq!(output.len(), cmd.outlen());
+        assert_eq!(output.len(), cmd.outlen().unwrap());
         assert_eq!(
             ManagePagesOutput::try_from(output).unwrap(),
             ManagePagesOutput {
@@ -1,6 +1,6 @@
 use deku::prelude::*;
 
-use super::{BaseOutput, Command};
+use super::BaseOutput;
 
 #[derive(Debug, PartialEq, DekuRead, DekuWrite)]
 #[deku(endian = "big", magic = b"\x08\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00")]
@@ -12,43 +12,27 @@ pub struct AllocPD {
 #[deku(endian = "big")]
 pub struct AllocPDOutput {
     pub base: BaseOutput,
-    #[deku(pad_bits_before = "8", bits = "24")]
+    #[deku(pad_bits_before = "8", bits = "24", pad_bytes_after = "4")]
     pub pd: u32
 }
 
-impl Command for AllocPD {
-    type Output = AllocPDOutput;
-
-    fn size(&self) -> usize {
-        0x10
-    }
-
-    fn outlen(&self) -> usize {
-        0x10
-    }
-}
-
 #[derive(Debug, PartialEq, DekuRead, DekuWrite)]
-#[deku(endian = "big", magic = b"\x08\x01\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00")]
+#[deku(endian = "big", magic = b"\x08\x01\x00\x00\x00\x00\x00\x00")]
 pub struct DeallocPD {
-    #[deku(pad_bits_before = "8", bits = "24")]
-    pd: u32
+    #[deku(pad_bits_before = "8", bits = "24", pad_bytes_after = "4")]
+    pub pd: u32
 }
 
 #[derive(Debug, PartialEq, DekuRead, DekuWrite)]
 #[deku(endian = "big")]
 pub struct DeallocPDOutput {
+    #[deku(pad_bytes_after = "8")]
     pub base: BaseOutput,
 }
 
-impl Command for DeallocPD {
-    type Output = DeallocPDOutput;
-
-    fn size(&self) -> usize {
-        0x10
-    }
+crate::mlx5_commands! {
+    PD_COMMANDS;
 
-    fn outlen(&self) -> usize {
-        0x10
-    }
-}
\ No newline at end of file
+    AllocPD => AllocPDOutput, opcode = 0x800, example = AllocPD {};
+    DeallocPD => DeallocPDOutput, opcode = 0x801, example = DeallocPD { pd: 1 };
+}
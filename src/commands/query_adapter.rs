@@ -1,7 +1,7 @@
 use deku::ctx::Endian;
 use deku::prelude::*;
 
-use super::{BaseOutput, Command};
+use super::BaseOutput;
 
 #[derive(Debug, PartialEq, DekuRead, DekuWrite)]
 #[deku(endian = "big", magic = b"\x01\x01\0\0\0\0\0\0\0\0\0\0\0\0\0\0")]
@@ -10,25 +10,13 @@ pub struct QueryAdapter(pub ());
 #[derive(Debug, PartialEq, DekuRead, DekuWrite)]
 #[deku(endian = "big")]
 pub struct QueryAdapterOutput {
-    // why are we off by 4 here????
+    // reserved_at_40, query_adapter_struct starts at 0x10
     #[deku(pad_bytes_after = "8")]
     pub base: BaseOutput,
 
     pub query_adapter: QueryAdapterStruct,
 }
 
-impl Command for QueryAdapter {
-    type Output = QueryAdapterOutput;
-
-    fn size(&self) -> usize {
-        0x10
-    }
-
-    fn outlen(&self) -> usize {
-        0x110
-    }
-}
-
 #[derive(Debug, PartialEq, DekuRead, DekuWrite)]
 #[deku(endian = "ctx_endian", ctx = "ctx_endian: Endian")]
 pub struct QueryAdapterStruct {
@@ -39,10 +27,16 @@ pub struct QueryAdapterStruct {
     pub vsd_contd_psid: [u8; 16],
 }
 
+crate::mlx5_commands! {
+    QUERY_ADAPTER_COMMANDS;
+
+    QueryAdapter => QueryAdapterOutput, opcode = 0x101, example = QueryAdapter(());
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::commands::CommandErrorStatus;
+    use crate::commands::{Command, CommandErrorStatus};
 
     #[test]
     fn test_query_adapter() {
@@ -50,7 +44,7 @@ mod tests {
 
         let res = cmd.to_bytes().unwrap();
 
-        assert_eq!(res.len(), cmd.size());
+        assert_eq!(res.len(), 0x10);
         assert_eq!(res, &[0x01, 0x01, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
 
         let query_adapter = QueryAdapterStruct {
@@ -91,7 +85,7 @@ mod tests {
 
         let out_bytes = out.to_bytes().unwrap();
 
-        assert_eq!(output.len(), cmd.outlen());
+        assert_eq!(output.len(), cmd.outlen().unwrap());
         assert_eq!(out_bytes, output, "{out_bytes:02x?}");
     }
 }
@@ -1,6 +1,6 @@
 use deku::prelude::*;
 
-use super::{BaseOutput, Command};
+use super::BaseOutput;
 
 #[derive(Debug, PartialEq, DekuRead, DekuWrite)]
 #[deku(endian = "big", magic = b"\x01\x00")]
@@ -18,16 +18,10 @@ pub struct QueryHCACapOutput {
     pub capabilities: [u8; 0x1000],
 }
 
-impl Command for QueryHCACap {
-    type Output = QueryHCACapOutput;
+crate::mlx5_commands! {
+    QUERY_HCA_CAP_COMMANDS;
 
-    fn size(&self) -> usize {
-        0x10
-    }
-
-    fn outlen(&self) -> usize {
-        0x1010
-    }
+    QueryHCACap => QueryHCACapOutput, opcode = 0x100, example = QueryHCACap { op_mod: 1 };
 }
 
 #[cfg(test)]
@@ -40,7 +34,7 @@ mod tests {
 
         let res = cmd.to_bytes().unwrap();
 
-        assert_eq!(res.len(), cmd.size());
+        assert_eq!(res.len(), 0x10);
         assert_eq!(
             res,
             &[0x01, 0x00, 0, 0, 0, 0, 0, 0x01, 0, 0, 0, 0, 0, 0, 0, 0]
@@ -1,7 +1,7 @@
 use deku::ctx::Endian;
 use deku::prelude::*;
 
-use super::{BaseOutput, Command};
+use super::BaseOutput;
 
 #[derive(Debug, PartialEq, DekuRead, DekuWrite)]
 #[deku(endian = "big", magic = b"\x01\x07")]
@@ -26,21 +26,16 @@ pub struct QueryPagesOutput {
     pub num_pages: i32,
 }
 
-impl Command for QueryPages {
-    type Output = QueryPagesOutput;
+crate::mlx5_commands! {
+    QUERY_PAGES_COMMANDS;
 
-    fn size(&self) -> usize {
-        0x10
-    }
-
-    fn outlen(&self) -> usize {
-        0x10
-    }
+    QueryPages => QueryPagesOutput, opcode = 0x107, example = QueryPages { op_mod: QueryPagesOpMod::BootPages };
 }
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::commands::CommandErrorStatus;
+    use crate::commands::{Command, CommandErrorStatus};
 
     #[test]
     fn test_query_pages() {
@@ -50,7 +45,7 @@ mod tests {
 
         let res = cmd.to_bytes().unwrap();
 
-        assert_eq!(res.len(), cmd.size());
+        assert_eq!(res.len(), 0x10);
         assert_eq!(
             res,
             &[0x01, 0x07, 0x0, 0x0, 0x0, 0x0, 0x0, 0x1, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0]
@@ -60,7 +55,7 @@ mod tests {
             0xab, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x20, 0x22,
         ];
 
-        assert_eq!(output.len(), cmd.outlen());
+        assert_eq!(output.len(), cmd.outlen().unwrap());
 
         assert_eq!(
             QueryPagesOutput::try_from(output).unwrap(),
@@ -0,0 +1,87 @@
+use deku::prelude::*;
+
+use super::{BaseOutput, Command};
+
+// Arbitrary opcode/op_mod with a raw payload following the 8 byte input header. outlen is the
+// full output length including the status/syndrome header.
+#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big")]
+pub struct RawCommand {
+    pub opcode: u16,
+    #[deku(pad_bytes_before = "4")]
+    pub op_mod: u16,
+
+    #[deku(bits_read = "deku::rest.len()")]
+    pub payload: Vec<u8>,
+
+    #[deku(skip, default = "0x10")]
+    pub outlen: usize,
+}
+
+impl RawCommand {
+    pub fn new(opcode: u16, op_mod: u16, payload: Vec<u8>, outlen: usize) -> Self {
+        Self {
+            opcode,
+            op_mod,
+            payload,
+            outlen,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big")]
+pub struct RawCommandOutput {
+    pub base: BaseOutput,
+
+    #[deku(bits_read = "deku::rest.len()")]
+    pub data: Vec<u8>,
+}
+
+impl Command for RawCommand {
+    type Output = RawCommandOutput;
+
+    fn outlen(&self) -> crate::error::Result<usize> {
+        Ok(self.outlen)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::{CommandErrorStatus, QueryISSI};
+
+    #[test]
+    fn test_raw_command() {
+        let cmd = RawCommand::new(0x10a, 0, vec![0; 8], 0x70);
+
+        let res = cmd.to_bytes().unwrap();
+
+        assert_eq!(res.len(), 0x10);
+        assert_eq!(res, QueryISSI(()).to_bytes().unwrap());
+
+        let cmd = RawCommand::new(0x1234, 0x5678, vec![0xaa, 0xbb, 0xcc, 0xdd], 0x10);
+
+        let res = cmd.to_bytes().unwrap();
+
+        assert_eq!(res.len(), 0xc);
+        assert_eq!(res, &[0x12, 0x34, 0x00, 0x00, 0x00, 0x00, 0x56, 0x78, 0xaa, 0xbb, 0xcc, 0xdd]);
+
+        #[rustfmt::skip]
+        let output: &[u8] = &[
+            0x03, 0x00, 0x00, 0x00, 0x12, 0x34, 0x56, 0x78, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x13, 0x37,
+        ];
+
+        assert_eq!(output.len(), cmd.outlen().unwrap());
+        assert_eq!(
+            RawCommandOutput::try_from(output).unwrap(),
+            RawCommandOutput {
+                base: BaseOutput {
+                    status: CommandErrorStatus::BadParameter,
+                    syndrome: 0x12345678,
+                },
+                data: vec![0, 0, 0, 0, 0, 0, 0x13, 0x37],
+            }
+        );
+    }
+}
@@ -1,6 +1,6 @@
 use deku::prelude::*;
 
-use super::{BaseOutput, Command};
+use super::BaseOutput;
 
 #[derive(Debug, PartialEq, DekuRead, DekuWrite)]
 #[deku(endian = "big", magic = b"\x01\x0d")]
@@ -12,21 +12,16 @@ pub struct SetDriverVersion {
 #[derive(Debug, PartialEq, DekuRead, DekuWrite)]
 #[deku(endian = "big")]
 pub struct SetDriverVersionOutput {
-    #[deku(pad_bytes_after = "4")]
+    #[deku(pad_bytes_after = "8")]
     pub base: BaseOutput,
 }
 
-impl Command for SetDriverVersion {
-    type Output = SetDriverVersionOutput;
+crate::mlx5_commands! {
+    SET_DRIVER_VERSION_COMMANDS;
 
-    fn size(&self) -> usize {
-        0x50
-    }
-
-    fn outlen(&self) -> usize {
-        0x10
-    }
+    SetDriverVersion => SetDriverVersionOutput, opcode = 0x10d, example = SetDriverVersion { driver_version: [0; 64] };
 }
+
 #[cfg(test)]
 mod tests {
     use std::io::Write;
@@ -45,7 +40,7 @@ mod tests {
             .unwrap();
 
         let res = cmd.to_bytes().unwrap();
-        assert_eq!(res.len(), cmd.size());
+        assert_eq!(res.len(), 0x50);
 
         assert_eq!(
             res,
@@ -0,0 +1,347 @@
+use deku::ctx::{ByteSize, Endian};
+use deku::prelude::*;
+
+use super::BaseOutput;
+
+#[derive(Debug, Default, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "ctx_endian", ctx = "ctx_endian: Endian, _ctx_bytes: ByteSize")]
+pub struct SRQContext {
+    #[deku(bits = "4")]
+    pub state: u8,
+    #[deku(bits = "4", pad_bits_after = "24")]
+    pub log_srq_size: u8,
+
+    #[deku(bits = "1")]
+    pub wq_signature: bool,
+    #[deku(bits = "1")]
+    pub cont_srq: bool,
+    #[deku(pad_bits_before = "1", bits = "1")]
+    pub rlky: bool,
+    #[deku(pad_bits_before = "1", bits = "3")]
+    pub log_rq_stride: u8,
+    #[deku(bits = "24")]
+    pub xrcd: u32,
+
+    #[deku(bits = "6")]
+    pub page_offset: u8,
+    #[deku(pad_bits_before = "2", bits = "24")]
+    pub cqn: u32,
+
+    #[deku(pad_bytes_before = "4", pad_bits_before = "2", bits = "6", pad_bits_after = "24")]
+    pub log_page_size: u8,
+
+    #[deku(pad_bytes_before = "4", pad_bits_before = "8", bits = "24")]
+    pub pd: u32,
+
+    pub lwm: u16,
+    pub wqe_cnt: u16,
+
+    #[deku(pad_bytes_before = "8", pad_bytes_after = "16")]
+    pub dbr_addr: u64,
+}
+
+#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big", magic = b"\x07\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00")]
+pub struct CreateSRQ {
+    #[deku(bytes = "64")]
+    pub ctx: SRQContext,
+
+    #[deku(pad_bytes_before = "192", bits_read = "deku::rest.len()")]
+    pub pas: Vec<u64>,
+}
+
+#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big")]
+pub struct CreateSRQOutput {
+    pub base: BaseOutput,
+
+    #[deku(pad_bits_before = "8", bits = "24", pad_bytes_after = "4")]
+    pub srqn: u32,
+}
+
+#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big", magic = b"\x07\x01\x00\x00\x00\x00\x00\x00")]
+pub struct DestroySRQ {
+    #[deku(pad_bits_before = "8", bits = "24", pad_bytes_after = "4")]
+    pub srqn: u32,
+}
+
+#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big")]
+pub struct DestroySRQOutput {
+    #[deku(pad_bytes_after = "8")]
+    pub base: BaseOutput,
+}
+
+#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big", magic = b"\x07\x02\x00\x00\x00\x00\x00\x00")]
+pub struct QuerySRQ {
+    #[deku(pad_bits_before = "8", bits = "24", pad_bytes_after = "4")]
+    pub srqn: u32,
+}
+
+#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big")]
+pub struct QuerySRQOutput {
+    #[deku(pad_bytes_after = "8")]
+    pub base: BaseOutput,
+
+    #[deku(bytes = "64", pad_bytes_after = "192")]
+    pub ctx: SRQContext,
+}
+
+// ARM_RQ with op_mod 1 arms an SRQ, ARM_XRC_SRQ uses the same layout with op_mod 1
+#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big", magic = b"\x07\x03\x00\x00\x00\x00\x00\x01")]
+pub struct ArmSRQ {
+    #[deku(pad_bits_before = "8", bits = "24")]
+    pub srqn: u32,
+    #[deku(pad_bytes_before = "2")]
+    pub lwm: u16,
+}
+
+#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big")]
+pub struct ArmSRQOutput {
+    #[deku(pad_bytes_after = "8")]
+    pub base: BaseOutput,
+}
+
+#[derive(Debug, Default, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "ctx_endian", ctx = "ctx_endian: Endian, _ctx_bytes: ByteSize")]
+pub struct XRCSRQContext {
+    #[deku(bits = "4")]
+    pub state: u8,
+    #[deku(bits = "4", pad_bits_after = "24")]
+    pub log_xrc_srq_size: u8,
+
+    #[deku(bits = "1")]
+    pub wq_signature: bool,
+    #[deku(bits = "1")]
+    pub cont_srq: bool,
+    #[deku(pad_bits_before = "1", bits = "1")]
+    pub rlky: bool,
+    #[deku(bits = "1")]
+    pub basic_cyclic_rcv_wqe: bool,
+    #[deku(bits = "3")]
+    pub log_rq_stride: u8,
+    #[deku(bits = "24")]
+    pub xrcd: u32,
+
+    #[deku(bits = "6")]
+    pub page_offset: u8,
+    #[deku(pad_bits_before = "1", bits = "1")]
+    pub dbr_umem_valid: bool,
+    #[deku(bits = "24")]
+    pub cqn: u32,
+
+    #[deku(pad_bytes_before = "4", bits = "1")]
+    pub user_index_equal_xrc_srqn: bool,
+    #[deku(pad_bits_before = "1", bits = "6")]
+    pub log_page_size: u8,
+    #[deku(bits = "24")]
+    pub user_index: u32,
+
+    #[deku(pad_bytes_before = "4", pad_bits_before = "8", bits = "24")]
+    pub pd: u32,
+
+    pub lwm: u16,
+    pub wqe_cnt: u16,
+
+    // Low two bits of the doorbell record address are reserved
+    #[deku(pad_bytes_before = "8", pad_bytes_after = "16")]
+    pub db_record_addr: u64,
+}
+
+#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big", magic = b"\x07\x05\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00")]
+pub struct CreateXRCSRQ {
+    #[deku(bytes = "64")]
+    pub ctx: XRCSRQContext,
+
+    #[deku(pad_bytes_before = "12", bits = "1", pad_bits_after = "31")]
+    pub xrc_srq_umem_valid: bool,
+
+    #[deku(pad_bytes_before = "176", bits_read = "deku::rest.len()")]
+    pub pas: Vec<u64>,
+}
+
+#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big")]
+pub struct CreateXRCSRQOutput {
+    pub base: BaseOutput,
+
+    #[deku(pad_bits_before = "8", bits = "24", pad_bytes_after = "4")]
+    pub xrc_srqn: u32,
+}
+
+#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big", magic = b"\x07\x06\x00\x00\x00\x00\x00\x00")]
+pub struct DestroyXRCSRQ {
+    #[deku(pad_bits_before = "8", bits = "24", pad_bytes_after = "4")]
+    pub xrc_srqn: u32,
+}
+
+#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big")]
+pub struct DestroyXRCSRQOutput {
+    #[deku(pad_bytes_after = "8")]
+    pub base: BaseOutput,
+}
+
+#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big", magic = b"\x07\x07\x00\x00\x00\x00\x00\x00")]
+pub struct QueryXRCSRQ {
+    #[deku(pad_bits_before = "8", bits = "24", pad_bytes_after = "4")]
+    pub xrc_srqn: u32,
+}
+
+#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big")]
+pub struct QueryXRCSRQOutput {
+    #[deku(pad_bytes_after = "8")]
+    pub base: BaseOutput,
+
+    #[deku(bytes = "64", pad_bytes_after = "192")]
+    pub ctx: XRCSRQContext,
+}
+
+#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big", magic = b"\x07\x08\x00\x00\x00\x00\x00\x01")]
+pub struct ArmXRCSRQ {
+    #[deku(pad_bits_before = "8", bits = "24")]
+    pub xrc_srqn: u32,
+    #[deku(pad_bytes_before = "2")]
+    pub lwm: u16,
+}
+
+#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big")]
+pub struct ArmXRCSRQOutput {
+    #[deku(pad_bytes_after = "8")]
+    pub base: BaseOutput,
+}
+
+#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big", magic = b"\x08\x0e\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00")]
+pub struct AllocXRCD {
+}
+
+#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big")]
+pub struct AllocXRCDOutput {
+    pub base: BaseOutput,
+
+    #[deku(pad_bits_before = "8", bits = "24", pad_bytes_after = "4")]
+    pub xrcd: u32,
+}
+
+#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big", magic = b"\x08\x0f\x00\x00\x00\x00\x00\x00")]
+pub struct DeallocXRCD {
+    #[deku(pad_bits_before = "8", bits = "24", pad_bytes_after = "4")]
+    pub xrcd: u32,
+}
+
+#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big")]
+pub struct DeallocXRCDOutput {
+    #[deku(pad_bytes_after = "8")]
+    pub base: BaseOutput,
+}
+
+crate::mlx5_commands! {
+    SRQ_COMMANDS;
+
+    CreateSRQ => CreateSRQOutput, opcode = 0x700, example = CreateSRQ { ctx: SRQContext::default(), pas: vec![0x1000] };
+    DestroySRQ => DestroySRQOutput, opcode = 0x701, example = DestroySRQ { srqn: 1 };
+    QuerySRQ => QuerySRQOutput, opcode = 0x702, example = QuerySRQ { srqn: 1 };
+    ArmSRQ => ArmSRQOutput, opcode = 0x703, example = ArmSRQ { srqn: 1, lwm: 0x10 };
+    CreateXRCSRQ => CreateXRCSRQOutput, opcode = 0x705, example = CreateXRCSRQ { ctx: XRCSRQContext::default(), xrc_srq_umem_valid: false, pas: vec![0x1000] };
+    DestroyXRCSRQ => DestroyXRCSRQOutput, opcode = 0x706, example = DestroyXRCSRQ { xrc_srqn: 1 };
+    QueryXRCSRQ => QueryXRCSRQOutput, opcode = 0x707, example = QueryXRCSRQ { xrc_srqn: 1 };
+    ArmXRCSRQ => ArmXRCSRQOutput, opcode = 0x708, example = ArmXRCSRQ { xrc_srqn: 1, lwm: 0x10 };
+    AllocXRCD => AllocXRCDOutput, opcode = 0x80e, example = AllocXRCD {};
+    DeallocXRCD => DeallocXRCDOutput, opcode = 0x80f, example = DeallocXRCD { xrcd: 1 };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create_srq() {
+        let cmd = CreateSRQ {
+            ctx: SRQContext {
+                state: 0,
+                log_srq_size: 6,
+                wq_signature: false,
+                cont_srq: false,
+                rlky: true,
+                log_rq_stride: 2,
+                xrcd: 0x123456,
+                page_offset: 0,
+                cqn: 0x000abc,
+                log_page_size: 0,
+                pd: 0x11,
+                lwm: 0x10,
+                wqe_cnt: 0,
+                dbr_addr: 0x10002000,
+            },
+            pas: vec![0x10001000],
+        };
+
+        let bytes = cmd.to_bytes().unwrap();
+
+        assert_eq!(bytes.len(), 0x118);
+        assert_eq!(&bytes[0x00..0x02], &[0x07, 0x00]);
+        #[rustfmt::skip]
+        assert_eq!(&bytes[0x10..0x2c], &[
+            0x06, 0x00, 0x00, 0x00,
+            (1 << 4) | 2, 0x12, 0x34, 0x56,
+            0x00, 0x00, 0x0a, 0xbc,
+            0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x11,
+        ]);
+        assert_eq!(&bytes[0x2c..0x30], &[0x00, 0x10, 0x00, 0x00]);
+        assert_eq!(&bytes[0x38..0x40], &[0x00, 0x00, 0x00, 0x00, 0x10, 0x00, 0x20, 0x00]);
+        assert_eq!(bytes[0x50..0x110], [0; 0xc0]);
+        assert_eq!(&bytes[0x110..], &[0x00, 0x00, 0x00, 0x00, 0x10, 0x00, 0x10, 0x00]);
+    }
+
+    #[test]
+    fn test_create_xrc_srq() {
+        let cmd = CreateXRCSRQ {
+            ctx: XRCSRQContext {
+                user_index: 0x42,
+                ..Default::default()
+            },
+            xrc_srq_umem_valid: true,
+            pas: vec![],
+        };
+
+        let bytes = cmd.to_bytes().unwrap();
+
+        assert_eq!(bytes.len(), 0x110);
+        assert_eq!(&bytes[0x20..0x24], &[0x00, 0x00, 0x00, 0x42]);
+        assert_eq!(&bytes[0x5c..0x60], &[0x80, 0x00, 0x00, 0x00]);
+    }
+
+    #[test]
+    fn test_arm_srq() {
+        let cmd = ArmSRQ {
+            srqn: 0x1234,
+            lwm: 0x20,
+        };
+        let bytes = cmd.to_bytes().unwrap();
+        #[rustfmt::skip]
+        assert_eq!(bytes, vec![
+            0x07, 0x03, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x01,
+            0x00, 0x00, 0x12, 0x34,
+            0x00, 0x00, 0x00, 0x20,
+        ]);
+    }
+}
@@ -1,7 +1,6 @@
 use deku::prelude::*;
 
-use super::{BaseOutput, Command};
-
+use super::BaseOutput;
 
 #[derive(Debug, PartialEq, DekuRead, DekuWrite)]
 #[deku(endian = "big", magic = b"\x08\x02\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00")]
@@ -13,43 +12,27 @@ pub struct AllocUAR {
 pub struct AllocUAROutput {
     pub base: BaseOutput,
 
-    #[deku(pad_bits_before = "8", bits = "24")]
+    #[deku(pad_bits_before = "8", bits = "24", pad_bytes_after = "4")]
     pub uar: u32
 }
 
-impl Command for AllocUAR {
-    type Output = AllocUAROutput;
-
-    fn size(&self) -> usize {
-        0x10
-    }
-
-    fn outlen(&self) -> usize {
-        0x10
-    }
-}
-
 #[derive(Debug, PartialEq, DekuRead, DekuWrite)]
-#[deku(endian = "big", magic = b"\x08\x03\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00")]
+#[deku(endian = "big", magic = b"\x08\x03\x00\x00\x00\x00\x00\x00")]
 pub struct DeallocUAR {
-    #[deku(pad_bits_before = "8", bits = "24")]
+    #[deku(pad_bits_before = "8", bits = "24", pad_bytes_after = "4")]
     uar: u32
 }
 
 #[derive(Debug, PartialEq, DekuRead, DekuWrite)]
 #[deku(endian = "big")]
 pub struct DeallocUAROutput {
+    #[deku(pad_bytes_after = "8")]
     pub base: BaseOutput,
 }
 
-impl Command for DeallocUAR {
-    type Output = DeallocUAROutput;
-
-    fn size(&self) -> usize {
-        0x10
-    }
+crate::mlx5_commands! {
+    UAR_COMMANDS;
 
-    fn outlen(&self) -> usize {
-        0x10
-    }
-}
\ No newline at end of file
+    AllocUAR => AllocUAROutput, opcode = 0x802, example = AllocUAR {};
+    DeallocUAR => DeallocUAROutput, opcode = 0x803, example = DeallocUAR { uar: 1 };
+}
@@ -0,0 +1,142 @@
+use deku::prelude::*;
+
+use crate::allocator::AllocationGuard;
+
+use super::create_mkey::{AccessMode, CreateMKey, MKeyContext};
+use super::BaseOutput;
+
+pub const MTT_READ_ENABLE: u64 = 1 << 0;
+pub const MTT_WRITE_ENABLE: u64 = 1 << 1;
+
+#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big", magic = b"\x0a\x08\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00")]
+pub struct CreateUmem {
+    #[deku(pad_bytes_before = "16", bits = "1")]
+    pub ats: bool,
+    #[deku(pad_bits_before = "26", bits = "5")]
+    pub log_page_size: u8,
+    pub page_offset: u32,
+
+    #[deku(update = "self.mtt.len() as u64")]
+    pub num_of_mtt: u64,
+    #[deku(count = "num_of_mtt")]
+    pub mtt: Vec<u64>,
+}
+
+#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big")]
+pub struct CreateUmemOutput {
+    pub base: BaseOutput,
+
+    #[deku(pad_bits_before = "8", bits = "24", pad_bytes_after = "4")]
+    pub umem_id: u32,
+}
+
+impl CreateUmem {
+    // One read/write MTT entry per page of the allocation, in adapter (4KiB) pages
+    pub fn from_allocation(memory: &AllocationGuard) -> Self {
+        let mtt: Vec<u64> = memory
+            .page_addresses()
+            .into_iter()
+            .map(|address| address | MTT_READ_ENABLE | MTT_WRITE_ENABLE)
+            .collect();
+        Self {
+            ats: false,
+            log_page_size: 0,
+            page_offset: 0,
+            num_of_mtt: mtt.len() as u64,
+            mtt,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big", magic = b"\x0a\x0a\x00\x00\x00\x00\x00\x00")]
+pub struct DestroyUmem {
+    #[deku(pad_bits_before = "8", bits = "24", pad_bytes_after = "4")]
+    pub umem_id: u32,
+}
+
+#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big")]
+pub struct DestroyUmemOutput {
+    #[deku(pad_bytes_after = "8")]
+    pub base: BaseOutput,
+}
+
+// MTT mkey whose translation comes from a UMEM instead of inline translation entries
+pub fn create_umem_mkey(umem_id: u32, umem_offset: u64, pd: u32, key: u8, start_addr: u64, len: u64) -> CreateMKey {
+    CreateMKey {
+        pg_access: false,
+        umem_valid: true,
+        context: MKeyContext {
+            free: false,
+            umr_en: false,
+            a: false,
+            rw: true,
+            rr: true,
+            lw: true,
+            lr: true,
+            access_mode: AccessMode::MTT,
+            qpn: 0xffffff,
+            mkey: key,
+            length64: false,
+            pd,
+            start_addr,
+            len,
+            bsf_octword_size: 0,
+            translation_octword_size: 0,
+            log_entry_size: 12,
+        },
+        translation_octwords_actual_size: 0,
+        mkey_umem_id: umem_id,
+        mkey_umem_offset: umem_offset,
+        translation_entries: vec![],
+    }
+}
+
+crate::mlx5_commands! {
+    UMEM_COMMANDS;
+
+    CreateUmem => CreateUmemOutput, opcode = 0xa08, example = CreateUmem { ats: false, log_page_size: 0, page_offset: 0, num_of_mtt: 1, mtt: vec![0x1000] };
+    DestroyUmem => DestroyUmemOutput, opcode = 0xa0a, example = DestroyUmem { umem_id: 1 };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create_umem() {
+        let cmd = CreateUmem {
+            ats: false,
+            log_page_size: 0,
+            page_offset: 0x10,
+            num_of_mtt: 2,
+            mtt: vec![0x10000003, 0x10001003],
+        };
+
+        let res = cmd.to_bytes().unwrap();
+
+        assert_eq!(res.len(), 0x40);
+        assert_eq!(&res[..0x02], &[0x0a, 0x08]);
+        assert_eq!(res[0x10..0x20], [0; 0x10]);
+        #[rustfmt::skip]
+        assert_eq!(&res[0x20..], &[
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02,
+            0x00, 0x00, 0x00, 0x00, 0x10, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00, 0x00, 0x10, 0x00, 0x10, 0x03,
+        ]);
+    }
+
+    #[test]
+    fn test_create_umem_mkey() {
+        let cmd = create_umem_mkey(0x123456, 0x1000, 5, 0x42, 0, 0x4000);
+
+        let res = cmd.to_bytes().unwrap();
+
+        assert_eq!(res.len(), 0x110);
+        assert_eq!(res[0x0c], 0x40);
+        assert_eq!(&res[0x64..0x68], &[0x00, 0x12, 0x34, 0x56]);
+        assert_eq!(&res[0x68..0x70], &[0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10, 0x00]);
+    }
+}
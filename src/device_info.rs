@@ -0,0 +1,80 @@
+use pci_driver::device::PciDevice;
+use serde::{Deserialize, Serialize};
+
+use crate::cmdif::vfio::VfioCmdIf;
+use crate::cmdif::CmdIf;
+use crate::commands::QueryAdapter;
+use crate::error::Result;
+use crate::registers::mgir::{c_string, MGIR};
+
+// Identity of the device a run was made against, so results from different firmware can be
+// told apart later.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct DeviceInfo {
+    pub pci_vendor_id: u16,
+    pub pci_device_id: u16,
+    pub pci_revision_id: u8,
+
+    pub hw_revision: u16,
+    pub hw_device_id: u16,
+
+    pub fw_version: String,
+    pub fw_build_id: u32,
+    pub fw_build_time: String,
+    pub fw_secured: bool,
+    pub fw_signed: bool,
+    pub fw_debug: bool,
+    pub fw_dev: bool,
+    pub ini_file_version: u32,
+    pub dev_branch_tag: String,
+    pub psid: String,
+
+    pub fw_rev_major: u16,
+    pub fw_rev_minor: u16,
+    pub fw_rev_subminor: u16,
+    pub cmd_interface_rev: u16,
+
+    pub ieee_vendor_id: u32,
+    pub vsd_vendor_id: u16,
+    pub vsd: String,
+    pub adapter_psid: String,
+}
+
+impl VfioCmdIf {
+    pub fn device_info(&self) -> Result<DeviceInfo> {
+        let config = self.pci_device.config();
+        let init_segment = self.init_segment();
+        let mgir = self.read_register(MGIR::default(), 0)?;
+        let adapter = self.do_command(QueryAdapter(()))?.query_adapter;
+
+        Ok(DeviceInfo {
+            pci_vendor_id: config.vendor_id().read()?,
+            pci_device_id: config.device_id().read()?,
+            pci_revision_id: config.revision_id().read()?,
+
+            hw_revision: mgir.device_hw_revision,
+            hw_device_id: mgir.device_id,
+
+            fw_version: mgir.fw_version(),
+            fw_build_id: mgir.build_id,
+            fw_build_time: mgir.build_time(),
+            fw_secured: mgir.secured,
+            fw_signed: mgir.signed_fw,
+            fw_debug: mgir.debug,
+            fw_dev: mgir.dev,
+            ini_file_version: mgir.ini_file_version,
+            dev_branch_tag: mgir.dev_branch_tag(),
+            psid: mgir.psid(),
+
+            fw_rev_major: init_segment.fw_rev_major().read()?.to_be(),
+            fw_rev_minor: init_segment.fw_rev_minor().read()?.to_be(),
+            fw_rev_subminor: init_segment.fw_rev_subminor().read()?.to_be(),
+            cmd_interface_rev: init_segment.cmd_interface_rev().read()?.to_be(),
+
+            ieee_vendor_id: adapter.ieee_vendor_id,
+            vsd_vendor_id: adapter.vsd_vendor_id,
+            vsd: c_string(&adapter.vsd),
+            adapter_psid: c_string(&adapter.vsd_contd_psid),
+        })
+    }
+}
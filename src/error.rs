@@ -1,6 +1,39 @@
 use thiserror::Error;
 
 use crate::commands::CommandErrorStatus;
+use crate::opcodes::describe_opcode;
+use crate::registers::mcc::{fsm_error_name, FsmState};
+use crate::registers::mcia::mcia_status_name;
+use crate::registers::register_name;
+
+fn command_name(opcode: &u16) -> String {
+    describe_opcode(*opcode)
+}
+
+fn syndrome_message(message: &Option<&'static str>) -> String {
+    message.map(|message| format!(" ({message})")).unwrap_or_default()
+}
+
+fn fsm_error(error_code: &u8) -> String {
+    match fsm_error_name(*error_code) {
+        Some(name) => format!("{name} ({error_code:#x})"),
+        None => format!("{error_code:#x}"),
+    }
+}
+
+fn mcia_status(status: &u8) -> String {
+    match mcia_status_name(*status) {
+        Some(name) => format!("{name} ({status:#x})"),
+        None => format!("{status:#x}"),
+    }
+}
+
+fn register_label(register_id: &u16) -> String {
+    match register_name(*register_id) {
+        Some(name) => format!("{name} ({register_id:#x})"),
+        None => format!("{register_id:#x}"),
+    }
+}
 
 #[derive(Error, Debug)]
 pub enum Error {
@@ -16,15 +49,82 @@ pub enum Error {
     #[error("Could not serialize command")]
     Deku(#[from] deku::error::DekuError),
 
-    #[error("Command error: status={status} syndrome={syndrome}")]
+    #[error("Command error: {} op_mod={op_mod:#x} status={status} syndrome={syndrome:#x}{}", command_name(.opcode), syndrome_message(.message))]
     Command {
+        opcode: u16,
+        op_mod: u16,
         status: CommandErrorStatus,
         syndrome: u32,
+        message: Option<&'static str>,
+    },
+
+    #[error("Register {register_id:#x} is declared as {size:#x} bytes but its layout is {layout_len:#x} bytes")]
+    RegisterSize {
+        register_id: u16,
+        size: usize,
+        layout_len: usize,
+    },
+
+    #[error("Register {register_id:#x} of {size:#x} bytes does not fit in the command mailboxes")]
+    RegisterTooLarge {
+        register_id: u16,
+        size: usize,
+    },
+
+    #[error("Register {} is not supported by the firmware", register_label(.register_id))]
+    UnsupportedRegister {
+        register_id: u16,
     },
 
     #[error("Out of memory")]
     OutOfMemory,
 
+    #[error("Flash verification failed at {address:#x}")]
+    FlashVerify {
+        address: u32,
+    },
+
+    #[error("Flash at {address:#x} did not erase, it is probably write protected")]
+    FlashWriteProtected {
+        address: u32,
+    },
+
+    #[error("Firmware image: {0}")]
+    FwImage(&'static str),
+
+    #[error("Firmware update failed in state {state:?}: {}", fsm_error(.error_code))]
+    FwUpdate {
+        state: FsmState,
+        error_code: u8,
+    },
+
+    #[error("Firmware update: {0}")]
+    FwUpdateRejected(&'static str),
+
+    #[error("Module {module} EEPROM access failed: {}", mcia_status(.status))]
+    ModuleEeprom {
+        module: u8,
+        status: u8,
+    },
+
+    #[error("NV configuration: {0}")]
+    NvConfig(&'static str),
+
+    #[error("Value {value:#x} does not fit NV configuration parameter {name}")]
+    NvConfigValue {
+        name: &'static str,
+        value: u32,
+    },
+
+    #[error("Firmware reset: {0}")]
+    FirmwareReset(&'static str),
+
+    #[error("Timed out waiting for {0}")]
+    Timeout(&'static str),
+
+    #[error("Firmware tracer: {0}")]
+    Tracer(&'static str),
+
     #[error("Could not find PCI capability")]
     CapabilityNotFound,
 
@@ -0,0 +1,362 @@
+use std::ops::Range;
+
+use crate::cmdif::CmdIf;
+use crate::error::{Error, Result};
+use crate::registers::flash::{MFBA, MFBE, MFPA};
+
+pub const SUB_SECTOR_SIZE: u32 = 0x1000;
+pub const BLOCK_32KB: u32 = 0x8000;
+pub const BLOCK_64KB: u32 = 0x10000;
+
+pub const MAX_MFBA_DATA: usize = 0x100;
+pub const READ_CHUNK: usize = 0x40;
+
+// The last JEDEC ID byte is log2 of the capacity in bytes, parts of 64 MiB and
+// up continue at 0x20 instead of 0x1a (as decoded by mstflint)
+pub fn flash_size(mfpa: &MFPA) -> Option<usize> {
+    match mfpa.jedec_id & 0xff {
+        capacity @ 0x10..=0x19 => Some(1 << capacity),
+        capacity @ 0x20..=0x26 => 1usize.checked_shl(capacity - 6),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FlashGeometry {
+    pub sector_size: u32,
+    pub write_size: u32,
+    pub bulk_32kb_erase: bool,
+    pub bulk_64kb_erase: bool,
+}
+
+impl From<&MFPA> for FlashGeometry {
+    // sector_size is in KiB, block_size is the largest MFBA write in bytes
+    fn from(mfpa: &MFPA) -> Self {
+        Self {
+            sector_size: match mfpa.sector_size {
+                0 => SUB_SECTOR_SIZE,
+                size => size as u32 * 0x400,
+            },
+            write_size: match mfpa.block_size {
+                0 => READ_CHUNK,
+                size => size.min(MAX_MFBA_DATA),
+            } as u32,
+            bulk_32kb_erase: mfpa.bulk_32kb_erase_en,
+            bulk_64kb_erase: mfpa.bulk_64kb_erase_en,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EraseKind {
+    Sector,
+    Bulk32KB,
+    Bulk64KB,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EraseOp {
+    pub address: u32,
+    pub size: u32,
+    pub kind: EraseKind,
+}
+
+impl FlashGeometry {
+    // Smallest sector aligned range covering `range`
+    pub fn sector_range(&self, range: Range<u32>) -> Range<u32> {
+        let start = range.start / self.sector_size * self.sector_size;
+        let end = range.end.div_ceil(self.sector_size) * self.sector_size;
+        start..end
+    }
+
+    // Erases covering `range`, using the largest supported bulk erase wherever it fits
+    pub fn plan_erase(&self, range: Range<u32>) -> Vec<EraseOp> {
+        let range = self.sector_range(range);
+        let mut ops = vec![];
+        let mut address = range.start;
+
+        while address < range.end {
+            let fits = |size: u32| size >= self.sector_size && address % size == 0 && range.end - address >= size;
+            let (size, kind) = if self.bulk_64kb_erase && fits(BLOCK_64KB) {
+                (BLOCK_64KB, EraseKind::Bulk64KB)
+            } else if self.bulk_32kb_erase && fits(BLOCK_32KB) {
+                (BLOCK_32KB, EraseKind::Bulk32KB)
+            } else {
+                (self.sector_size, EraseKind::Sector)
+            };
+            ops.push(EraseOp { address, size, kind });
+            address += size;
+        }
+        ops
+    }
+
+    // MFBA writes for `len` bytes at `address`, none crossing a write_size boundary
+    pub fn plan_write(&self, address: u32, len: usize) -> Vec<(u32, Range<usize>)> {
+        let mut chunks = vec![];
+        let mut offset = 0;
+
+        while offset < len {
+            let chunk_address = address + offset as u32;
+            let chunk_len = ((self.write_size - chunk_address % self.write_size) as usize).min(len - offset);
+            chunks.push((chunk_address, offset..offset + chunk_len));
+            offset += chunk_len;
+        }
+        chunks
+    }
+}
+
+// One flash device behind the MFPA/MFBA/MFBE registers. With dry_run set, erases and writes
+// are only logged.
+pub struct Flash<'a, C: CmdIf> {
+    cmdif: &'a C,
+    pub fs: usize,
+    pub mfpa: MFPA,
+    pub geometry: FlashGeometry,
+    pub dry_run: bool,
+    pub read_chunk: usize,
+}
+
+impl<'a, C: CmdIf> Flash<'a, C> {
+    pub fn open(cmdif: &'a C, fs: usize, dry_run: bool) -> Result<Self> {
+        let mfpa = cmdif.read_register(
+            MFPA {
+                fs,
+                ..Default::default()
+            },
+            0,
+        )?;
+        let geometry = FlashGeometry::from(&mfpa);
+        log::debug!("Flash {fs}: {geometry:x?}");
+        if fs >= mfpa.flash_num.max(1) {
+            log::warn!("Flash select {fs} is out of range, the device reports {} flashes", mfpa.flash_num);
+        }
+        if mfpa.sector_wrp_en || mfpa.sub_sector_wrp_en {
+            log::warn!("Flash {fs} has sector write protection enabled");
+        }
+
+        Ok(Self {
+            cmdif,
+            fs,
+            mfpa,
+            geometry,
+            dry_run,
+            read_chunk: READ_CHUNK,
+        })
+    }
+
+    pub fn size(&self) -> Option<usize> {
+        flash_size(&self.mfpa)
+    }
+
+    pub fn read(&self, address: u32, len: usize) -> Result<Vec<u8>> {
+        let mut data = Vec::with_capacity(len);
+        while data.len() < len {
+            let chunk = self.read_chunk.min(len - data.len());
+            let mfba = self.cmdif.read_register(
+                MFBA {
+                    fs: self.fs,
+                    size: chunk,
+                    address: address as usize + data.len(),
+                    ..MFBA::default()
+                },
+                0,
+            )?;
+            data.extend_from_slice(&mfba.data[..chunk]);
+        }
+        Ok(data)
+    }
+
+    pub fn erase(&self, range: Range<u32>) -> Result<()> {
+        for op in self.geometry.plan_erase(range) {
+            log::info!("Erasing {:#x} bytes at {:#x}", op.size, op.address);
+            if self.dry_run {
+                continue;
+            }
+            self.cmdif.write_register(
+                MFBE {
+                    fs: self.fs,
+                    address: op.address as usize,
+                    bulk_32kb_erase: op.kind == EraseKind::Bulk32KB,
+                    bulk_64kb_erase: op.kind == EraseKind::Bulk64KB,
+                    ..Default::default()
+                },
+                0,
+            )?;
+        }
+        Ok(())
+    }
+
+    // Writes into already erased flash
+    pub fn program(&self, address: u32, data: &[u8]) -> Result<()> {
+        for (chunk_address, range) in self.geometry.plan_write(address, data.len()) {
+            log::debug!("Writing {:#x} bytes at {chunk_address:#x}", range.len());
+            if self.dry_run {
+                continue;
+            }
+            let mut mfba = MFBA {
+                fs: self.fs,
+                size: range.len(),
+                address: chunk_address as usize,
+                ..MFBA::default()
+            };
+            mfba.data[..range.len()].copy_from_slice(&data[range]);
+            self.cmdif.write_register(mfba, 0)?;
+        }
+        Ok(())
+    }
+
+    pub fn verify(&self, address: u32, data: &[u8]) -> Result<()> {
+        let flash_data = self.read(address, data.len())?;
+        match flash_data.iter().zip(data).position(|(a, b)| a != b) {
+            Some(offset) => Err(Error::FlashVerify {
+                address: address + offset as u32,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    // Rewrites the sectors covering the range whose contents change, keeping the bytes around
+    // the range. Sectors that do not read back blank after the erase are write protected.
+    pub fn write(&self, address: u32, data: &[u8], verify: bool) -> Result<()> {
+        let sectors = self.geometry.sector_range(address..address + data.len() as u32);
+        let current = self.read(sectors.start, sectors.len())?;
+        let mut image = current.clone();
+        let offset = (address - sectors.start) as usize;
+        image[offset..offset + data.len()].copy_from_slice(data);
+
+        let sector_size = self.geometry.sector_size as usize;
+        let mut changed: Vec<Range<usize>> = vec![];
+        for start in (0..image.len()).step_by(sector_size) {
+            let sector = start..start + sector_size;
+            if image[sector.clone()] == current[sector.clone()] {
+                continue;
+            }
+            match changed.last_mut() {
+                Some(run) if run.end == start => run.end = sector.end,
+                _ => changed.push(sector),
+            }
+        }
+
+        for run in changed {
+            let run_address = sectors.start + run.start as u32;
+            self.erase(run_address..run_address + run.len() as u32)?;
+            if !self.dry_run {
+                let erased = self.read(run_address, run.len())?;
+                if let Some(offset) = erased.iter().position(|&b| b != 0xff) {
+                    return Err(Error::FlashWriteProtected {
+                        address: run_address + offset as u32,
+                    });
+                }
+            }
+
+            self.program(run_address, &image[run.clone()])?;
+            if verify && !self.dry_run {
+                self.verify(run_address, &image[run])?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flash_geometry() {
+        let geometry = FlashGeometry::from(&MFPA {
+            bulk_32kb_erase_en: true,
+            bulk_64kb_erase_en: true,
+            block_size: 0x80,
+            sector_size: 4,
+            ..Default::default()
+        });
+
+        assert_eq!(
+            geometry,
+            FlashGeometry {
+                sector_size: 0x1000,
+                write_size: 0x80,
+                bulk_32kb_erase: true,
+                bulk_64kb_erase: true,
+            }
+        );
+        assert_eq!(geometry.sector_range(0x1800..0x2001), 0x1000..0x3000);
+
+        assert_eq!(
+            flash_size(&MFPA {
+                jedec_id: 0xef4018,
+                ..Default::default()
+            }),
+            Some(16 * 1024 * 1024)
+        );
+        assert_eq!(
+            flash_size(&MFPA {
+                jedec_id: 0xef4019,
+                ..Default::default()
+            }),
+            Some(32 * 1024 * 1024)
+        );
+        assert_eq!(
+            flash_size(&MFPA {
+                jedec_id: 0xc22520,
+                ..Default::default()
+            }),
+            Some(64 * 1024 * 1024)
+        );
+        assert_eq!(
+            flash_size(&MFPA {
+                jedec_id: 0xc2251a,
+                ..Default::default()
+            }),
+            None
+        );
+        assert_eq!(flash_size(&MFPA::default()), None);
+    }
+
+    #[test]
+    fn test_plan_erase() {
+        let mut geometry = FlashGeometry {
+            sector_size: 0x1000,
+            write_size: 0x80,
+            bulk_32kb_erase: true,
+            bulk_64kb_erase: true,
+        };
+
+        let ops: Vec<_> = geometry
+            .plan_erase(0x7000..0x21000)
+            .iter()
+            .map(|op| (op.address, op.kind))
+            .collect();
+        assert_eq!(
+            ops,
+            vec![
+                (0x7000, EraseKind::Sector),
+                (0x8000, EraseKind::Bulk32KB),
+                (0x10000, EraseKind::Bulk64KB),
+                (0x20000, EraseKind::Sector),
+            ]
+        );
+
+        geometry.bulk_64kb_erase = false;
+        assert_eq!(geometry.plan_erase(0x10000..0x20000).len(), 2);
+
+        geometry.bulk_32kb_erase = false;
+        assert_eq!(geometry.plan_erase(0x10000..0x20000).len(), 16);
+    }
+
+    #[test]
+    fn test_plan_write() {
+        let geometry = FlashGeometry {
+            sector_size: 0x1000,
+            write_size: 0x80,
+            bulk_32kb_erase: false,
+            bulk_64kb_erase: false,
+        };
+
+        assert_eq!(
+            geometry.plan_write(0x1070, 0x120),
+            vec![(0x1070, 0..0x10), (0x1080, 0x10..0x90), (0x1100, 0x90..0x110), (0x1180, 0x110..0x120)]
+        );
+    }
+}
@@ -0,0 +1,252 @@
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use deku::DekuContainerRead;
+
+use crate::cmdif::CmdIf;
+use crate::error::{Error, Result};
+use crate::registers::mcc::{FsmState, MccInstruction, McqiCap, MCC, MCDA, MCQI, MCQI_INFO_CAPABILITIES, MCQS};
+use crate::registers::MAX_REGISTER_SIZE;
+
+pub const COMPONENT_BOOT_IMG: u16 = 0x1;
+
+const MAX_COMPONENTS: u16 = 0x40;
+const MCQI_CAP_SIZE: usize = 0x14;
+const MCDA_HEADER_SIZE: usize = 0x10;
+const FSM_TIMEOUT: Duration = Duration::from_secs(30);
+const FSM_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpdateStage {
+    Download,
+    Verify,
+    Activate,
+}
+
+// Component data goes out as host dwords converted with htonl, as mlx5_reg_mcda_set does
+fn mcda_block(update_handle: u32, offset: u32, block: &[u8]) -> MCDA {
+    let data: Vec<u8> = block
+        .chunks(4)
+        .flat_map(|chunk| {
+            let mut dword = [0; 4];
+            dword[..chunk.len()].copy_from_slice(chunk);
+            u32::from_ne_bytes(dword).to_be_bytes()
+        })
+        .collect();
+    MCDA {
+        update_handle,
+        offset,
+        size: data.len() as u16,
+        data,
+    }
+}
+
+pub fn query_components(cmdif: &impl CmdIf) -> Result<Vec<MCQS>> {
+    let mut components = vec![];
+    for component_index in 0..MAX_COMPONENTS {
+        let mcqs = cmdif.read_register(
+            MCQS {
+                component_index,
+                ..Default::default()
+            },
+            0,
+        )?;
+        let last = mcqs.last_index_flag;
+        components.push(mcqs);
+        if last {
+            break;
+        }
+    }
+    Ok(components)
+}
+
+pub fn query_capabilities(cmdif: &impl CmdIf, component_index: u16) -> Result<McqiCap> {
+    let mcqi = cmdif.read_register(
+        MCQI {
+            component_index,
+            info_type: MCQI_INFO_CAPABILITIES,
+            data_size: MCQI_CAP_SIZE as u16,
+            data: vec![0; MCQI_CAP_SIZE],
+            ..Default::default()
+        },
+        0,
+    )?;
+    Ok(McqiCap::from_bytes((&mcqi.data, 0))?.1)
+}
+
+// A locked component update FSM, driven through MCC and fed through MCDA
+pub struct FwUpdate<'a, C: CmdIf> {
+    cmdif: &'a C,
+    update_handle: u32,
+}
+
+impl<'a, C: CmdIf> FwUpdate<'a, C> {
+    pub fn lock(cmdif: &'a C) -> Result<Self> {
+        let mut update = Self {
+            cmdif,
+            update_handle: 0,
+        };
+        if update.query()?.state() != FsmState::Idle {
+            return Err(Error::FwUpdateRejected("another component update is in progress"));
+        }
+        update.update_handle = update.instruction(MccInstruction::LockUpdateHandle, 0, 0)?.update_handle;
+        log::debug!("Locked update handle {:#x}", update.update_handle);
+        Ok(update)
+    }
+
+    pub fn update_handle(&self) -> u32 {
+        self.update_handle
+    }
+
+    pub fn query(&self) -> Result<MCC> {
+        self.cmdif.read_register(
+            MCC {
+                update_handle: self.update_handle,
+                ..Default::default()
+            },
+            0,
+        )
+    }
+
+    fn instruction(&self, instruction: MccInstruction, component_index: u16, component_size: u32) -> Result<MCC> {
+        log::debug!("MCC {instruction:?} component {component_index} size {component_size:#x}");
+        self.cmdif.write_register(
+            MCC {
+                instruction: instruction as u8,
+                component_index,
+                update_handle: self.update_handle,
+                component_size,
+                ..Default::default()
+            },
+            0,
+        )
+    }
+
+    // Polls until the FSM reaches `expected`, reporting control_progress meanwhile
+    pub fn wait_state(&self, expected: FsmState, mut progress: impl FnMut(u8)) -> Result<()> {
+        let started = Instant::now();
+        loop {
+            let mcc = self.query()?;
+            if mcc.error_code != 0 {
+                return Err(Error::FwUpdate {
+                    state: mcc.state(),
+                    error_code: mcc.error_code,
+                });
+            }
+            if mcc.state() == expected {
+                return Ok(());
+            }
+            progress(mcc.control_progress);
+
+            if started.elapsed() > FSM_TIMEOUT {
+                return Err(Error::Timeout("firmware update state change"));
+            }
+            sleep(FSM_POLL_INTERVAL);
+        }
+    }
+
+    pub fn update_component(
+        &self,
+        component_index: u16,
+        data: &[u8],
+        mut progress: impl FnMut(UpdateStage, usize, usize),
+    ) -> Result<()> {
+        let cap = query_capabilities(self.cmdif, component_index)?;
+        log::debug!("Component {component_index}: {cap:x?}");
+        if cap.max_component_size != 0 && data.len() > cap.max_component_size as usize {
+            return Err(Error::FwUpdateRejected("component is larger than the device accepts"));
+        }
+
+        let word_size = (1usize << cap.log_mcda_word_size).max(4);
+        let block_size = match cap.mcda_max_write_size as usize {
+            0 => MAX_REGISTER_SIZE - MCDA_HEADER_SIZE,
+            size => size.min(MAX_REGISTER_SIZE - MCDA_HEADER_SIZE),
+        };
+        let block_size = (block_size / word_size * word_size).max(word_size);
+
+        self.instruction(MccInstruction::UpdateComponent, component_index, data.len() as u32)?;
+        self.wait_state(FsmState::Download, |_| ())?;
+
+        for (index, block) in data.chunks(block_size).enumerate() {
+            let offset = index * block_size;
+            self.cmdif
+                .write_register(mcda_block(self.update_handle, offset as u32, block), 0)?;
+            progress(UpdateStage::Download, (offset + block_size).min(data.len()), data.len());
+        }
+
+        self.instruction(MccInstruction::VerifyComponent, component_index, 0)?;
+        self.wait_state(FsmState::Locked, |percent| progress(UpdateStage::Verify, percent as usize, 100))
+    }
+
+    pub fn activate(&self, mut progress: impl FnMut(UpdateStage, usize, usize)) -> Result<()> {
+        self.instruction(MccInstruction::Activate, 0, 0)?;
+        self.wait_state(FsmState::Locked, |percent| progress(UpdateStage::Activate, percent as usize, 100))
+    }
+
+    pub fn cancel(&self) -> Result<()> {
+        self.instruction(MccInstruction::Cancel, 0, 0)?;
+        Ok(())
+    }
+
+    pub fn release(self) -> Result<()> {
+        self.instruction(MccInstruction::ReleaseUpdateHandle, 0, 0)?;
+        Ok(())
+    }
+}
+
+// Writes and verifies the component with the given identifier and optionally activates it.
+// A failed update is cancelled, the handle is released either way.
+pub fn flash_component<C: CmdIf>(
+    cmdif: &C,
+    identifier: u16,
+    data: &[u8],
+    activate: bool,
+    mut progress: impl FnMut(UpdateStage, usize, usize),
+) -> Result<()> {
+    let component = query_components(cmdif)?
+        .into_iter()
+        .find(|component| component.identifier == identifier)
+        .ok_or(Error::FwUpdateRejected("the device has no such component"))?;
+
+    let update = FwUpdate::lock(cmdif)?;
+    let result = update
+        .update_component(component.component_index, data, &mut progress)
+        .and_then(|()| if activate { update.activate(&mut progress) } else { Ok(()) });
+
+    if let Err(err) = &result {
+        log::warn!("Cancelling firmware update: {err}");
+        if let Err(err) = update.cancel() {
+            log::warn!("Could not cancel firmware update: {err}");
+        }
+        // The FSM error is the one worth returning
+        if let Err(err) = update.release() {
+            log::warn!("Could not release the update handle: {err}");
+        }
+        return result;
+    }
+    update.release()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use deku::DekuContainerWrite;
+
+    #[test]
+    #[cfg(target_endian = "little")]
+    fn test_mcda_block() {
+        let mcda = mcda_block(0x123456, 0x100, &[0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08]);
+        assert_eq!(
+            mcda.to_bytes().unwrap(),
+            [
+                0x00, 0x12, 0x34, 0x56, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x08, 0x00, 0x00, 0x00, 0x00, 0x04,
+                0x03, 0x02, 0x01, 0x08, 0x07, 0x06, 0x05,
+            ]
+        );
+
+        // A short trailing dword is zero padded before conversion
+        let mcda = mcda_block(0, 0, &[0xaa, 0xbb]);
+        assert_eq!(mcda.size, 4);
+        assert_eq!(mcda.data, [0x00, 0x00, 0xbb, 0xaa]);
+    }
+}
@@ -0,0 +1,452 @@
+use serde::Serialize;
+
+use crate::error::{Error, Result};
+use crate::registers::mgir::c_string;
+
+// Layouts follow the FS4 image format as handled by mstflint
+pub const MAGIC_PATTERN: [u32; 4] = [0x4d544657, 0xabcdef00, 0xfade1234, 0x5678dead];
+pub const MAGIC_OFFSETS: &[usize] = &[
+    0x0, 0x10000, 0x20000, 0x40000, 0x80000, 0x100000, 0x200000, 0x400000, 0x800000, 0x1000000, 0x2000000,
+];
+
+pub const HW_POINTERS_OFFSET: usize = 0x18;
+pub const HW_POINTER_NAMES: &[&str] = &[
+    "boot_record",
+    "boot2",
+    "toc",
+    "tools",
+    "authentication_start",
+    "authentication_end",
+    "digest",
+    "digest_recovery_key",
+    "fw_window_start",
+    "fw_window_end",
+    "image_info_section",
+    "image_signature",
+    "public_key",
+    "fw_security_version",
+    "gcm_iv_delta",
+    "ncore_hashes",
+];
+
+pub const ITOC_SIGNATURE: u32 = 0x49544f43;
+pub const DTOC_SIGNATURE: u32 = 0x44544f43;
+pub const TOC_SIGNATURE_REST: [u32; 3] = [0x04081516, 0x2342cafa, 0xbacafe00];
+pub const TOC_ENTRY_SIZE: usize = 0x20;
+pub const TOC_END: u8 = 0xff;
+const MAX_TOC_ENTRIES: usize = 0x100;
+
+// DTOC lives in the last 4k of the flash
+pub const DTOC_OFFSET_FROM_END: usize = 0x1000;
+
+pub const SECTION_IMAGE_INFO: u8 = 0x10;
+
+pub const SECTION_TYPES: &[(u8, &str)] = &[
+    (0x01, "BOOT_CODE"),
+    (0x02, "PCI_CODE"),
+    (0x03, "MAIN_CODE"),
+    (0x04, "PCIE_LINK_CODE"),
+    (0x05, "IRON_PREP_CODE"),
+    (0x06, "POST_IRON_BOOT_CODE"),
+    (0x07, "UPGRADE_CODE"),
+    (0x08, "HW_BOOT_CFG"),
+    (0x09, "HW_MAIN_CFG"),
+    (0x0a, "PHY_UC_CODE"),
+    (0x0b, "PHY_UC_CONSTS"),
+    (0x0c, "PCIE_PHY_UC_CODE"),
+    (0x0d, "CCIR_INFRA_CODE"),
+    (0x0e, "CCIR_ALGO_CODE"),
+    (0x10, "IMAGE_INFO"),
+    (0x11, "FW_BOOT_CFG"),
+    (0x12, "FW_MAIN_CFG"),
+    (0x13, "APU_KERNEL"),
+    (0x14, "ACE_CODE"),
+    (0x18, "ROM_CODE"),
+    (0x20, "RESET_INFO"),
+    (0x30, "DBG_FW_INI"),
+    (0x32, "DBG_FW_PARAMS"),
+    (0x33, "FW_ADB"),
+    (0xa0, "IMAGE_SIGNATURE_256"),
+    (0xa1, "PUBLIC_KEYS_2048"),
+    (0xa2, "FORBIDDEN_VERSIONS"),
+    (0xa3, "IMAGE_SIGNATURE_512"),
+    (0xa4, "PUBLIC_KEYS_4096"),
+    (0xe0, "MFG_INFO"),
+    (0xe1, "DEV_INFO"),
+    (0xe2, "NV_DATA"),
+    (0xe3, "VSD"),
+    (0xe4, "NV_LOG"),
+    (0xe5, "VPD_R0"),
+    (0xe6, "NV_DATA2"),
+    (0xe7, "FW_NV_LOG"),
+    (0xe8, "NV_DATA0"),
+    (0xe9, "FW_INTERNAL_USAGE"),
+    (0xea, "CRDUMP_MASK_DATA"),
+];
+
+pub fn section_name(section_type: u8) -> Option<&'static str> {
+    SECTION_TYPES
+        .iter()
+        .find(|(known, _)| *known == section_type)
+        .map(|(_, name)| *name)
+}
+
+pub fn describe_section(section_type: u8) -> String {
+    match section_name(section_type) {
+        Some(name) => name.to_string(),
+        None => format!("{section_type:#04x}"),
+    }
+}
+
+fn dword(data: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(data.get(offset..offset + 4)?.try_into().unwrap()))
+}
+
+// Image CRC: polynomial 0x100b over big endian dwords, inverted at the end
+pub fn crc16(data: &[u8]) -> u16 {
+    let mut crc: u32 = 0xffff;
+    for chunk in data.chunks(4) {
+        let mut word = [0u8; 4];
+        word[..chunk.len()].copy_from_slice(chunk);
+        let mut word = u32::from_be_bytes(word);
+        for _ in 0..32 {
+            crc = ((crc << 1) | (word >> 31)) ^ if crc & 0x8000 != 0 { 0x100b } else { 0 };
+            crc &= 0xffff;
+            word <<= 1;
+        }
+    }
+    for _ in 0..16 {
+        crc = (crc << 1) ^ if crc & 0x8000 != 0 { 0x100b } else { 0 };
+        crc &= 0xffff;
+    }
+    crc as u16 ^ 0xffff
+}
+
+// HW pointer CRC, mstflint's calc_hw_crc: polynomial 0x100b over bytes starting from 0xffff with
+// the first two bytes inverted, no final inversion
+pub fn hw_crc(data: &[u8]) -> u16 {
+    let mut crc: u16 = 0xffff;
+    for (i, &byte) in data.iter().enumerate() {
+        let byte = if i < 2 { !byte } else { byte };
+        crc ^= (byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 { (crc << 1) ^ 0x100b } else { crc << 1 };
+        }
+    }
+    crc
+}
+
+pub fn find_image(flash: &[u8]) -> Option<usize> {
+    MAGIC_OFFSETS.iter().copied().find(|&offset| {
+        (0..4).all(|i| dword(flash, offset + 4 * i) == Some(MAGIC_PATTERN[i]))
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct HwPointer {
+    pub name: &'static str,
+    pub ptr: u32,
+    pub crc: u16,
+    pub crc_ok: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum SectionCrc {
+    InEntry,
+    None,
+    InSection,
+    Unknown(u8),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TocEntry {
+    pub section_type: u8,
+    pub name: String,
+    pub size: usize,
+    pub param0: u32,
+    pub param1: u32,
+    pub cache_line_crc: bool,
+    pub zipped: bool,
+    pub encrypted: bool,
+    // Absolute flash offset of the section
+    pub flash_addr: usize,
+    pub crc: SectionCrc,
+    pub section_crc: u16,
+    pub entry_crc_ok: bool,
+}
+
+impl TocEntry {
+    pub fn parse(entry: &[u8], base: usize) -> Option<Self> {
+        let entry = entry.get(..TOC_ENTRY_SIZE)?;
+        let word = |index| dword(entry, 4 * index).unwrap();
+        let section_type = (word(0) >> 24) as u8;
+
+        Some(Self {
+            section_type,
+            name: describe_section(section_type),
+            size: (word(0) & 0x3fffff) as usize * 4,
+            param0: word(1) & 0x3fffffff,
+            param1: word(2),
+            cache_line_crc: word(1) >> 31 != 0,
+            zipped: word(1) >> 30 & 1 != 0,
+            encrypted: word(5) >> 19 & 1 != 0,
+            flash_addr: base + (word(4) & 0x1fffffff) as usize * 4,
+            crc: match (word(5) >> 16) as u8 & 0x7 {
+                0 => SectionCrc::InEntry,
+                1 => SectionCrc::None,
+                2 => SectionCrc::InSection,
+                other => SectionCrc::Unknown(other),
+            },
+            section_crc: word(5) as u16,
+            entry_crc_ok: crc16(&entry[..0x1c]) == word(7) as u16,
+        })
+    }
+
+    pub fn data<'a>(&self, flash: &'a [u8]) -> Option<&'a [u8]> {
+        flash.get(self.flash_addr..self.flash_addr + self.size)
+    }
+
+    // None when the section carries no checkable CRC or lies outside the flash
+    pub fn section_crc_ok(&self, flash: &[u8]) -> Option<bool> {
+        let data = self.data(flash)?;
+        match self.crc {
+            SectionCrc::InEntry => Some(crc16(data) == self.section_crc),
+            SectionCrc::InSection if data.len() >= 4 => {
+                let (data, crc) = data.split_at(data.len() - 4);
+                Some(crc16(data) == dword(crc, 0)? as u16)
+            }
+            _ => None,
+        }
+    }
+}
+
+// Table of contents at `offset`, section addresses are relative to `base`
+pub fn parse_toc(flash: &[u8], offset: usize, signature: u32, base: usize) -> Result<Vec<TocEntry>> {
+    let header = flash
+        .get(offset..offset + TOC_ENTRY_SIZE)
+        .ok_or(Error::FwImage("table of contents outside of the image"))?;
+    let signatures_ok = dword(header, 0) == Some(signature)
+        && (0..3).all(|i| dword(header, 4 + 4 * i) == Some(TOC_SIGNATURE_REST[i]));
+    if !signatures_ok {
+        return Err(Error::FwImage("bad table of contents signature"));
+    }
+    if crc16(&header[..0x1c]) != dword(header, 0x1c).unwrap() as u16 {
+        log::warn!("Table of contents header at {offset:#x} has a bad CRC");
+    }
+
+    let mut entries = vec![];
+    for index in 1..=MAX_TOC_ENTRIES {
+        let Some(entry) = flash.get(offset + index * TOC_ENTRY_SIZE..) else {
+            break;
+        };
+        let Some(entry) = TocEntry::parse(entry, base) else {
+            break;
+        };
+        if entry.section_type == TOC_END {
+            break;
+        }
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ImageInfo {
+    pub fw_version: String,
+    pub psid: String,
+}
+
+impl ImageInfo {
+    pub fn parse(section: &[u8]) -> Option<Self> {
+        // FW_VERSION has major in the high half of its first dword, minor and sub_minor in the second
+        let version = section.get(0x4..0x10)?;
+        let half = |offset: usize| u16::from_be_bytes([version[offset], version[offset + 1]]);
+        Some(Self {
+            fw_version: format!("{}.{}.{}", half(0x0), half(0x4), half(0x6)),
+            psid: c_string(section.get(0x30..0x40)?),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FwImage {
+    pub image_start: usize,
+    pub hw_pointers: Vec<HwPointer>,
+    pub itoc: Vec<TocEntry>,
+    pub dtoc: Vec<TocEntry>,
+    pub image_info: Option<ImageInfo>,
+}
+
+impl FwImage {
+    pub fn parse(flash: &[u8]) -> Result<Self> {
+        let image_start = find_image(flash).ok_or(Error::FwImage("magic pattern not found"))?;
+
+        let hw_pointers: Vec<HwPointer> = HW_POINTER_NAMES
+            .iter()
+            .enumerate()
+            .map_while(|(index, &name)| {
+                let offset = image_start + HW_POINTERS_OFFSET + 8 * index;
+                let ptr = dword(flash, offset)?;
+                let crc = dword(flash, offset + 4)? as u16;
+                Some(HwPointer {
+                    name,
+                    ptr,
+                    crc,
+                    // The pointer and the reserved half of the CRC dword
+                    crc_ok: hw_crc(&flash[offset..offset + 6]) == crc,
+                })
+            })
+            .collect();
+
+        let toc = hw_pointers
+            .iter()
+            .find(|pointer| pointer.name == "toc")
+            .ok_or(Error::FwImage("hardware pointers truncated"))?;
+        let itoc = parse_toc(flash, image_start + toc.ptr as usize, ITOC_SIGNATURE, image_start)?;
+
+        let dtoc = match flash.len().checked_sub(DTOC_OFFSET_FROM_END) {
+            Some(offset) => parse_toc(flash, offset, DTOC_SIGNATURE, 0).unwrap_or_else(|err| {
+                log::warn!("No DTOC: {err}");
+                vec![]
+            }),
+            None => vec![],
+        };
+
+        let image_info = itoc
+            .iter()
+            .find(|entry| entry.section_type == SECTION_IMAGE_INFO)
+            .and_then(|entry| entry.data(flash))
+            .and_then(ImageInfo::parse);
+
+        Ok(Self {
+            image_start,
+            hw_pointers,
+            itoc,
+            dtoc,
+            image_info,
+        })
+    }
+
+    pub fn sections(&self) -> impl Iterator<Item = &TocEntry> {
+        self.itoc.iter().chain(self.dtoc.iter())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn put(flash: &mut [u8], offset: usize, value: u32) {
+        flash[offset..offset + 4].copy_from_slice(&value.to_be_bytes());
+    }
+
+    fn put_toc_header(flash: &mut [u8], offset: usize, signature: u32) {
+        put(flash, offset, signature);
+        for (i, word) in TOC_SIGNATURE_REST.iter().enumerate() {
+            put(flash, offset + 4 + 4 * i, *word);
+        }
+        let crc = crc16(&flash[offset..offset + 0x1c]);
+        put(flash, offset + 0x1c, crc as u32);
+    }
+
+    fn put_toc_entry(flash: &mut [u8], offset: usize, section_type: u8, size: usize, addr: usize) {
+        put(flash, offset, (section_type as u32) << 24 | (size / 4) as u32);
+        put(flash, offset + 0x10, (addr / 4) as u32);
+        let section_crc = crc16(&flash[addr..addr + size]);
+        put(flash, offset + 0x14, section_crc as u32);
+        let crc = crc16(&flash[offset..offset + 0x1c]);
+        put(flash, offset + 0x1c, crc as u32);
+    }
+
+    // Expected values come from dividing the message by x^16 + x^12 + x^3 + x + 1 directly instead
+    // of running a shift register
+    #[test]
+    fn test_crc16() {
+        assert_eq!(crc16(&[]), 0x0955);
+        assert_eq!(crc16(&[0, 0, 0, 0]), 0x0009);
+        assert_eq!(crc16(&[0, 0, 0, 1]), 0x1002);
+        assert_eq!(crc16(&[0, 0]), crc16(&[0, 0, 0, 0]));
+        assert_eq!(crc16(b"123456789"), 0x9ce9);
+
+        let header: Vec<u8> = [ITOC_SIGNATURE]
+            .iter()
+            .chain(TOC_SIGNATURE_REST.iter())
+            .flat_map(|word| word.to_be_bytes())
+            .collect();
+        assert_eq!(crc16(&header), 0xd8af);
+    }
+
+    #[test]
+    fn test_hw_crc() {
+        assert_eq!(hw_crc(&[0, 0, 0, 0, 0, 0]), 0x0000);
+        assert_eq!(hw_crc(&[0, 0, 0x10, 0, 0, 0]), 0xbfc7);
+        assert_eq!(hw_crc(&[0, 0x50, 0, 0, 0, 0]), 0xae9a);
+        assert_ne!(hw_crc(&[0, 0, 0x10, 0, 0, 0]), crc16(&[0, 0, 0x10, 0]));
+    }
+
+    #[test]
+    fn test_parse_image() {
+        let image_start = 0x10000;
+        let mut flash = vec![0xffu8; 0x40000];
+
+        for (i, word) in MAGIC_PATTERN.iter().enumerate() {
+            put(&mut flash, image_start + 4 * i, *word);
+        }
+        let toc = HW_POINTERS_OFFSET + 8 * 2;
+        put(&mut flash, image_start + toc, 0x1000);
+        put(&mut flash, image_start + toc + 4, 0);
+        let crc = hw_crc(&flash[image_start + toc..image_start + toc + 6]);
+        assert_eq!(crc, 0xbfc7);
+        put(&mut flash, image_start + toc + 4, crc as u32);
+
+        let image_info = image_start + 0x2000;
+        flash[image_info..image_info + 0x400].fill(0);
+        put(&mut flash, image_info + 0x4, 16 << 16);
+        put(&mut flash, image_info + 0x8, 35 << 16 | 1012);
+        flash[image_info + 0x30..image_info + 0x3d].copy_from_slice(b"MT_0000000012");
+        flash[image_start + 0x3000..image_start + 0x3100].fill(0x5a);
+
+        put_toc_header(&mut flash, image_start + 0x1000, ITOC_SIGNATURE);
+        put_toc_entry(&mut flash, image_start + 0x1020, SECTION_IMAGE_INFO, 0x400, 0x2000 + image_start);
+        put_toc_entry(&mut flash, image_start + 0x1040, 0x03, 0x100, 0x3000 + image_start);
+
+        let dtoc = flash.len() - DTOC_OFFSET_FROM_END;
+        flash[dtoc + 0x800..dtoc + 0x840].fill(0x11);
+        put_toc_header(&mut flash, dtoc, DTOC_SIGNATURE);
+        put_toc_entry(&mut flash, dtoc + 0x20, 0xe1, 0x40, dtoc + 0x800);
+
+        // Entries are relative to the image, the helper wrote absolute addresses
+        for offset in [0x1030, 0x1050] {
+            let addr = dword(&flash, image_start + offset).unwrap() - (image_start / 4) as u32;
+            put(&mut flash, image_start + offset, addr);
+            let entry = image_start + offset - 0x10;
+            let crc = crc16(&flash[entry..entry + 0x1c]);
+            put(&mut flash, entry + 0x1c, crc as u32);
+        }
+
+        let image = FwImage::parse(&flash).unwrap();
+
+        assert_eq!(image.image_start, image_start);
+        assert!(image.hw_pointers[2].crc_ok);
+        assert_eq!(
+            image.image_info,
+            Some(ImageInfo {
+                fw_version: "16.35.1012".to_string(),
+                psid: "MT_0000000012".to_string(),
+            })
+        );
+
+        let sections: Vec<_> = image.sections().map(|entry| (entry.name.as_str(), entry.flash_addr, entry.size)).collect();
+        assert_eq!(
+            sections,
+            vec![
+                ("IMAGE_INFO", image_start + 0x2000, 0x400),
+                ("MAIN_CODE", image_start + 0x3000, 0x100),
+                ("DEV_INFO", dtoc + 0x800, 0x40),
+            ]
+        );
+        assert!(image.sections().all(|entry| entry.entry_crc_ok));
+        assert!(image.sections().all(|entry| entry.section_crc_ok(&flash) == Some(true)));
+        assert_eq!(image.sections().nth(1).unwrap().data(&flash).unwrap(), &[0x5a; 0x100]);
+    }
+}
@@ -1,9 +1,23 @@
+pub mod bits;
+pub mod capabilities;
 pub mod cqe;
+pub mod device_info;
 pub mod error;
+pub mod flash;
+pub mod fw_update;
+pub mod fwimage;
 pub mod init;
 pub mod mailbox;
 pub mod commands;
+pub mod opcodes;
+pub mod pcie;
+pub mod port;
 pub mod registers;
 pub mod allocator;
 pub mod mtcr;
+pub mod nvconfig;
 pub mod cmdif;
+pub mod sff;
+pub mod snapshot;
+pub mod syndrome;
+pub mod tracer;
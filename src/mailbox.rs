@@ -5,7 +5,13 @@ use pci_driver::{
     },
 };
 
-use crate::error::Result;
+use crate::error::{Error, Result};
+
+pub const MAILBOX_STRIDE: u64 = 0x400;
+pub const MAILBOX_DATA_SIZE: usize = 0x200;
+// Pages of DMA memory holding the input and output mailboxes of one command
+pub const MAILBOX_REGION_PAGES: usize = 256;
+pub const MAILBOX_REGION_SIZE: u64 = MAILBOX_REGION_PAGES as u64 * 0x1000;
 
 pci_struct! {
     pub struct Mailbox<'a> : 0x240 {
@@ -68,12 +74,15 @@ impl<'a> MailboxAllocator<'a> {
 
     pub fn allocate_mailbox(&mut self) -> Result<(u64, Mailbox<'a>)> {
         let mailbox_offset = self.allocation_offset;
-        self.allocation_offset += 0x400;
+        if mailbox_offset + MAILBOX_STRIDE > self.region.len() {
+            return Err(Error::OutOfMemory);
+        }
+        self.allocation_offset += MAILBOX_STRIDE;
         Ok((
             mailbox_offset,
             Mailbox::backed_by(
                 self.region
-                    .subregion(mailbox_offset..mailbox_offset + 0x400),
+                    .subregion(mailbox_offset..mailbox_offset + MAILBOX_STRIDE),
             ),
         ))
     }
@@ -81,7 +90,7 @@ impl<'a> MailboxAllocator<'a> {
     pub fn build_mailbox(&mut self, token: u8, data: &[u8]) -> Result<Vec<Mailbox<'a>>> {
         let mut mb_vec: Vec<Mailbox<'_>> = vec![];
 
-        for (block_number, chunk) in data.chunks(0x200).enumerate() {
+        for (block_number, chunk) in data.chunks(MAILBOX_DATA_SIZE).enumerate() {
             let mb = self.allocate_mailbox()?.1;
             if let Some(prev_mb) = mb_vec.last() {
                 prev_mb.set_next(mb.as_ptr().unwrap() as u64)?;
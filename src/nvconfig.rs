@@ -0,0 +1,316 @@
+use crate::bits::{get_bits, set_bits};
+use crate::cmdif::CmdIf;
+use crate::error::{Error, Result};
+use crate::registers::nvconfig::{
+    config_item_type, ACCESS_MODE_NEXT, MAX_CONFIG_ITEM_DATA, MNVDA, MNVGN, MNVIA, MNVQC, TYPE_CLASS_GLOBAL,
+    TYPE_CLASS_PER_HOST_PF, TYPE_CLASS_PHYSICAL_PORT,
+};
+
+const GLOBAL_PCI_CONF: u32 = 0x80;
+const SW_OFFLOAD_CONF: u32 = 0x10a;
+const PF_PCI_CONF: u32 = 0x80;
+const BOOT_SETTINGS: u32 = 0x2;
+const VPI_SETTINGS: u32 = 0x12;
+
+const MNVIA_TARGET_ALL: u8 = 0;
+const MAX_STORED_ITEMS: usize = 0x400;
+
+const BOOL_VALUES: &[(u32, &str)] = &[(0, "False"), (1, "True")];
+
+// A field of a configuration item, `offset` counts bits from the most significant bit of the
+// item data like the PRM layouts do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NvParam {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub type_class: u8,
+    pub port: u8,
+    pub parameter_index: u32,
+    pub data_size: usize,
+    pub offset: usize,
+    pub bits: usize,
+    // Set along with the field on writes, the firmware ignores the item without it
+    pub valid_bit: Option<usize>,
+    pub values: &'static [(u32, &'static str)],
+}
+
+const fn global_pci(name: &'static str, description: &'static str, offset: usize, bits: usize) -> NvParam {
+    NvParam {
+        name,
+        description,
+        type_class: TYPE_CLASS_GLOBAL,
+        port: 0,
+        parameter_index: GLOBAL_PCI_CONF,
+        data_size: 0xc,
+        offset,
+        bits,
+        valid_bit: Some(0),
+        values: if bits == 1 { BOOL_VALUES } else { &[] },
+    }
+}
+
+const fn sw_offload(
+    name: &'static str,
+    description: &'static str,
+    offset: usize,
+    bits: usize,
+    values: &'static [(u32, &'static str)],
+) -> NvParam {
+    NvParam {
+        name,
+        description,
+        type_class: TYPE_CLASS_GLOBAL,
+        port: 0,
+        parameter_index: SW_OFFLOAD_CONF,
+        data_size: 0x8,
+        offset,
+        bits,
+        valid_bit: None,
+        values,
+    }
+}
+
+const fn per_port(
+    name: &'static str,
+    description: &'static str,
+    port: u8,
+    parameter_index: u32,
+    offset: usize,
+    bits: usize,
+    values: &'static [(u32, &'static str)],
+) -> NvParam {
+    NvParam {
+        name,
+        description,
+        type_class: TYPE_CLASS_PHYSICAL_PORT,
+        port,
+        parameter_index,
+        data_size: 0x8,
+        offset,
+        bits,
+        valid_bit: None,
+        values,
+    }
+}
+
+const LINK_TYPES: &[(u32, &str)] = &[(1, "IB"), (2, "ETH")];
+const LEGACY_BOOT_PROTOCOLS: &[(u32, &str)] = &[(0, "NONE"), (1, "PXE"), (2, "ISCSI"), (3, "BOTH")];
+const CQE_COMPRESSION: &[(u32, &str)] = &[(0, "BALANCED"), (1, "AGGRESSIVE")];
+
+pub const NV_PARAMS: &[NvParam] = &[
+    global_pci("SRIOV_EN", "Enable SR-IOV", 0x20, 1),
+    global_pci("NUM_OF_VFS", "Number of virtual functions", 0x30, 16),
+    global_pci("PER_PF_NUM_VFS", "Configure the number of VFs per PF", 0x11, 1),
+    NvParam {
+        name: "PF_TOTAL_VF_EN",
+        description: "Use PF_NUM_OF_VFS for this PF",
+        type_class: TYPE_CLASS_PER_HOST_PF,
+        port: 0,
+        parameter_index: PF_PCI_CONF,
+        data_size: 0x10,
+        offset: 0x9,
+        bits: 1,
+        valid_bit: None,
+        values: BOOL_VALUES,
+    },
+    NvParam {
+        name: "PF_NUM_OF_VFS",
+        description: "Number of virtual functions of this PF",
+        type_class: TYPE_CLASS_PER_HOST_PF,
+        port: 0,
+        parameter_index: PF_PCI_CONF,
+        data_size: 0x10,
+        offset: 0x50,
+        bits: 16,
+        valid_bit: None,
+        values: &[],
+    },
+    sw_offload("CQE_COMPRESSION", "CQE compression mode", 0x1d, 3, CQE_COMPRESSION),
+    sw_offload("PCI_ATOMIC_MODE", "PCI atomic operations mode", 0x11, 3, &[]),
+    sw_offload("IP_OVER_VXLAN_EN", "Enable IP over VXLAN offloads", 0x1b, 1, BOOL_VALUES),
+    sw_offload("IP_OVER_VXLAN_PORT", "UDP port of IP over VXLAN", 0x0, 16, &[]),
+    sw_offload("PRIO_TAG_REQUIRED_EN", "Require priority tagged packets", 0x18, 1, BOOL_VALUES),
+    sw_offload("UCTX_EN", "Enable user contexts", 0x17, 1, BOOL_VALUES),
+    per_port("LINK_TYPE_P1", "Link type of port 1", 1, VPI_SETTINGS, 0x1e, 2, LINK_TYPES),
+    per_port("LINK_TYPE_P2", "Link type of port 2", 2, VPI_SETTINGS, 0x1e, 2, LINK_TYPES),
+    per_port("BOOT_OPTION_ROM_EN_P1", "Enable the expansion ROM on port 1", 1, BOOT_SETTINGS, 0x0, 1, BOOL_VALUES),
+    per_port("BOOT_OPTION_ROM_EN_P2", "Enable the expansion ROM on port 2", 2, BOOT_SETTINGS, 0x0, 1, BOOL_VALUES),
+    per_port("BOOT_VLAN_EN_P1", "Use BOOT_VLAN_P1 when booting", 1, BOOT_SETTINGS, 0x1, 1, BOOL_VALUES),
+    per_port("BOOT_VLAN_EN_P2", "Use BOOT_VLAN_P2 when booting", 2, BOOT_SETTINGS, 0x1, 1, BOOL_VALUES),
+    per_port("LEGACY_BOOT_PROTOCOL_P1", "Legacy boot protocol of port 1", 1, BOOT_SETTINGS, 0x5, 3, LEGACY_BOOT_PROTOCOLS),
+    per_port("LEGACY_BOOT_PROTOCOL_P2", "Legacy boot protocol of port 2", 2, BOOT_SETTINGS, 0x5, 3, LEGACY_BOOT_PROTOCOLS),
+    per_port("BOOT_VLAN_P1", "VLAN used when booting from port 1", 1, BOOT_SETTINGS, 0x14, 12, &[]),
+    per_port("BOOT_VLAN_P2", "VLAN used when booting from port 2", 2, BOOT_SETTINGS, 0x14, 12, &[]),
+];
+
+pub fn find_param(name: &str) -> Option<&'static NvParam> {
+    NV_PARAMS.iter().find(|param| param.name.eq_ignore_ascii_case(name))
+}
+
+impl NvParam {
+    pub fn item_type(&self) -> u32 {
+        config_item_type(self.type_class, self.port, self.parameter_index)
+    }
+
+    pub fn get(&self, data: &[u8]) -> u32 {
+        get_bits(data, self.offset, self.bits) as u32
+    }
+
+    pub fn set(&self, data: &mut [u8], value: u32) -> Result<()> {
+        if self.bits < 32 && value >> self.bits != 0 {
+            return Err(Error::NvConfigValue {
+                name: self.name,
+                value,
+            });
+        }
+        if data.len() < self.data_size {
+            return Err(Error::NvConfig("configuration item is shorter than its layout"));
+        }
+        set_bits(data, self.offset, self.bits, value.into());
+        if let Some(valid_bit) = self.valid_bit {
+            set_bits(data, valid_bit, 1, 1);
+        }
+        Ok(())
+    }
+
+    pub fn value_name(&self, value: u32) -> Option<&'static str> {
+        self.values.iter().find(|(known, _)| *known == value).map(|(_, name)| *name)
+    }
+
+    // Accepts the names of known values as well as decimal or 0x prefixed numbers
+    pub fn parse_value(&self, value: &str) -> Option<u32> {
+        if let Some((known, _)) = self.values.iter().find(|(_, name)| name.eq_ignore_ascii_case(value)) {
+            return Some(*known);
+        }
+        match value.strip_prefix("0x") {
+            Some(hex) => u32::from_str_radix(hex, 16).ok(),
+            None => value.parse().ok(),
+        }
+    }
+
+    pub fn format_value(&self, value: u32) -> String {
+        match self.value_name(value) {
+            Some(name) => format!("{name}({value})"),
+            None => format!("{value}"),
+        }
+    }
+}
+
+pub fn query_support(cmdif: &impl CmdIf, item_type: u32) -> Result<MNVQC> {
+    cmdif.read_register(
+        MNVQC {
+            item_type,
+            ..Default::default()
+        },
+        0,
+    )
+}
+
+pub fn read_item(cmdif: &impl CmdIf, item_type: u32, size: usize, access_mode: u8) -> Result<MNVDA> {
+    cmdif.read_register(MNVDA::new(item_type, access_mode, vec![0; size]), 0)
+}
+
+pub fn read_param(cmdif: &impl CmdIf, param: &NvParam, access_mode: u8) -> Result<u32> {
+    let item = read_item(cmdif, param.item_type(), param.data_size, access_mode)?;
+    Ok(param.get(&item.data))
+}
+
+// Sets the parameters for the next boot, parameters sharing a configuration item are written
+// with a single read-modify-write of that item.
+pub fn write_params(cmdif: &impl CmdIf, params: &[(&NvParam, u32)]) -> Result<()> {
+    let mut items: Vec<(u32, usize, Vec<(&NvParam, u32)>)> = vec![];
+    for &(param, value) in params {
+        match items.iter_mut().find(|(item_type, _, _)| *item_type == param.item_type()) {
+            Some((_, size, fields)) => {
+                *size = (*size).max(param.data_size);
+                fields.push((param, value));
+            }
+            None => items.push((param.item_type(), param.data_size, vec![(param, value)])),
+        }
+    }
+
+    for (item_type, size, fields) in items {
+        let support = query_support(cmdif, item_type)?;
+        if !support.support_wr {
+            return Err(Error::NvConfig("configuration item is not writable"));
+        }
+
+        let mut item = read_item(cmdif, item_type, size, ACCESS_MODE_NEXT)?;
+        for (param, value) in fields {
+            param.set(&mut item.data, value)?;
+        }
+        item.header.access_mode = ACCESS_MODE_NEXT;
+        item.header.length = item.data.len() as u16;
+        log::debug!("Writing configuration item {item_type:#010x}: {:02x?}", item.data);
+        cmdif.write_register(item, 0)?;
+    }
+    Ok(())
+}
+
+// Invalidates all stored configuration, the defaults apply from the next boot
+pub fn reset(cmdif: &impl CmdIf) -> Result<()> {
+    cmdif.write_register(
+        MNVIA {
+            target: MNVIA_TARGET_ALL,
+            ..Default::default()
+        },
+        0,
+    )?;
+    Ok(())
+}
+
+pub fn stored_items(cmdif: &impl CmdIf) -> Result<Vec<MNVGN>> {
+    let mut items = vec![];
+    let mut nv_pointer = 0;
+    while items.len() < MAX_STORED_ITEMS {
+        let item = cmdif.read_register(
+            MNVGN {
+                nv_pointer,
+                data: vec![0; MAX_CONFIG_ITEM_DATA],
+                ..Default::default()
+            },
+            0,
+        )?;
+        if item.header.length == 0 {
+            break;
+        }
+        nv_pointer = item.nv_pointer;
+        items.push(item);
+        if nv_pointer == 0 {
+            break;
+        }
+    }
+    Ok(items)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use deku::DekuContainerWrite;
+
+    #[test]
+    fn test_params() {
+        for (index, param) in NV_PARAMS.iter().enumerate() {
+            assert!(param.offset + param.bits <= param.data_size * 8, "{}", param.name);
+            assert!(NV_PARAMS[index + 1..].iter().all(|other| other.name != param.name));
+        }
+
+        let num_vfs = find_param("num_of_vfs").unwrap();
+        let mut data = vec![0; num_vfs.data_size];
+        num_vfs.set(&mut data, 16).unwrap();
+        assert_eq!(data, [0x80, 0, 0, 0, 0, 0, 0, 16, 0, 0, 0, 0]);
+        assert!(num_vfs.set(&mut data, 0x10000).is_err());
+
+        let link_type = find_param("LINK_TYPE_P2").unwrap();
+        assert_eq!(link_type.item_type(), 0x01020012);
+        assert_eq!(link_type.parse_value("eth"), Some(2));
+        assert_eq!(link_type.parse_value("0x1"), Some(1));
+        assert_eq!(link_type.format_value(2), "ETH(2)");
+    }
+
+    #[test]
+    fn test_mnvda_header() {
+        let mnvda = MNVDA::new(config_item_type(TYPE_CLASS_GLOBAL, 0, GLOBAL_PCI_CONF), 1, vec![0; 0xc]);
+        assert_eq!(&mnvda.to_bytes().unwrap()[..0xc], [0, 0x40, 0, 0xc, 0, 0, 0, 0x80, 0, 0, 0, 0]);
+    }
+}
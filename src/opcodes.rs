@@ -0,0 +1,385 @@
+use std::fmt::Write;
+
+use crate::commands::{registry, CommandInfo};
+
+// Command opcodes as named in the PRM / mlx5_ifc.h
+pub const OPCODES: &[(u16, &str)] = &[
+    (0x100, "QUERY_HCA_CAP"),
+    (0x101, "QUERY_ADAPTER"),
+    (0x102, "INIT_HCA"),
+    (0x103, "TEARDOWN_HCA"),
+    (0x104, "ENABLE_HCA"),
+    (0x105, "DISABLE_HCA"),
+    (0x107, "QUERY_PAGES"),
+    (0x108, "MANAGE_PAGES"),
+    (0x109, "SET_HCA_CAP"),
+    (0x10a, "QUERY_ISSI"),
+    (0x10b, "SET_ISSI"),
+    (0x10d, "SET_DRIVER_VERSION"),
+    (0x10e, "QUERY_OTHER_HCA_CAP"),
+    (0x10f, "MODIFY_OTHER_HCA_CAP"),
+    (0x110, "SET_TUNNELED_OPERATIONS"),
+    (0x111, "QUERY_SF_PARTITION"),
+    (0x113, "ALLOC_SF"),
+    (0x114, "DEALLOC_SF"),
+    (0x115, "SUSPEND_VHCA"),
+    (0x116, "RESUME_VHCA"),
+    (0x117, "QUERY_VHCA_MIGRATION_STATE"),
+    (0x118, "SAVE_VHCA_STATE"),
+    (0x119, "LOAD_VHCA_STATE"),
+    (0x200, "CREATE_MKEY"),
+    (0x201, "QUERY_MKEY"),
+    (0x202, "DESTROY_MKEY"),
+    (0x203, "QUERY_SPECIAL_CONTEXTS"),
+    (0x204, "PAGE_FAULT_RESUME"),
+    (0x205, "ALLOC_MEMIC"),
+    (0x206, "DEALLOC_MEMIC"),
+    (0x207, "MODIFY_MEMIC"),
+    (0x301, "CREATE_EQ"),
+    (0x302, "DESTROY_EQ"),
+    (0x303, "QUERY_EQ"),
+    (0x304, "GEN_EQE"),
+    (0x400, "CREATE_CQ"),
+    (0x401, "DESTROY_CQ"),
+    (0x402, "QUERY_CQ"),
+    (0x403, "MODIFY_CQ"),
+    (0x500, "CREATE_QP"),
+    (0x501, "DESTROY_QP"),
+    (0x502, "RST2INIT_QP"),
+    (0x503, "INIT2RTR_QP"),
+    (0x504, "RTR2RTS_QP"),
+    (0x505, "RTS2RTS_QP"),
+    (0x506, "SQERR2RTS_QP"),
+    (0x507, "2ERR_QP"),
+    (0x508, "2RST_QP"),
+    (0x509, "QUERY_QP"),
+    (0x50a, "SQD_RTS_QP"),
+    (0x50e, "INIT2INIT_QP"),
+    (0x600, "CREATE_PSV"),
+    (0x601, "DESTROY_PSV"),
+    (0x700, "CREATE_SRQ"),
+    (0x701, "DESTROY_SRQ"),
+    (0x702, "QUERY_SRQ"),
+    (0x703, "ARM_RQ"),
+    (0x705, "CREATE_XRC_SRQ"),
+    (0x706, "DESTROY_XRC_SRQ"),
+    (0x707, "QUERY_XRC_SRQ"),
+    (0x708, "ARM_XRC_SRQ"),
+    (0x710, "CREATE_DCT"),
+    (0x711, "DESTROY_DCT"),
+    (0x712, "DRAIN_DCT"),
+    (0x713, "QUERY_DCT"),
+    (0x714, "ARM_DCT_FOR_KEY_VIOLATION"),
+    (0x717, "CREATE_XRQ"),
+    (0x718, "DESTROY_XRQ"),
+    (0x719, "QUERY_XRQ"),
+    (0x71a, "ARM_XRQ"),
+    (0x740, "QUERY_ESW_FUNCTIONS"),
+    (0x750, "QUERY_VPORT_STATE"),
+    (0x751, "MODIFY_VPORT_STATE"),
+    (0x752, "QUERY_ESW_VPORT_CONTEXT"),
+    (0x753, "MODIFY_ESW_VPORT_CONTEXT"),
+    (0x754, "QUERY_NIC_VPORT_CONTEXT"),
+    (0x755, "MODIFY_NIC_VPORT_CONTEXT"),
+    (0x756, "QUERY_ROCE_ADDRESS"),
+    (0x757, "SET_ROCE_ADDRESS"),
+    (0x758, "QUERY_HCA_VPORT_CONTEXT"),
+    (0x759, "MODIFY_HCA_VPORT_CONTEXT"),
+    (0x760, "QUERY_HCA_VPORT_GID"),
+    (0x761, "QUERY_HCA_VPORT_PKEY"),
+    (0x762, "QUERY_VNIC_ENV"),
+    (0x770, "QUERY_VPORT_COUNTER"),
+    (0x771, "ALLOC_Q_COUNTER"),
+    (0x772, "DEALLOC_Q_COUNTER"),
+    (0x773, "QUERY_Q_COUNTER"),
+    (0x774, "SET_MONITOR_COUNTER"),
+    (0x775, "ARM_MONITOR_COUNTER"),
+    (0x780, "SET_PP_RATE_LIMIT"),
+    (0x781, "QUERY_RATE_LIMIT"),
+    (0x782, "CREATE_SCHEDULING_ELEMENT"),
+    (0x783, "DESTROY_SCHEDULING_ELEMENT"),
+    (0x784, "QUERY_SCHEDULING_ELEMENT"),
+    (0x785, "MODIFY_SCHEDULING_ELEMENT"),
+    (0x786, "CREATE_QOS_PARA_VPORT"),
+    (0x787, "DESTROY_QOS_PARA_VPORT"),
+    (0x800, "ALLOC_PD"),
+    (0x801, "DEALLOC_PD"),
+    (0x802, "ALLOC_UAR"),
+    (0x803, "DEALLOC_UAR"),
+    (0x804, "CONFIG_INT_MODERATION"),
+    (0x805, "ACCESS_REG"),
+    (0x806, "ATTACH_TO_MCG"),
+    (0x807, "DETACH_FROM_MCG"),
+    (0x808, "GET_DROPPED_PACKET_LOG"),
+    (0x80a, "MAD_IFC"),
+    (0x80b, "QUERY_MAD_DEMUX"),
+    (0x80c, "SET_MAD_DEMUX"),
+    (0x80d, "NOP"),
+    (0x80e, "ALLOC_XRCD"),
+    (0x80f, "DEALLOC_XRCD"),
+    (0x816, "ALLOC_TRANSPORT_DOMAIN"),
+    (0x817, "DEALLOC_TRANSPORT_DOMAIN"),
+    (0x820, "SET_DIAGNOSTIC_PARAMS"),
+    (0x821, "QUERY_DIAGNOSTIC_PARAMS"),
+    (0x822, "QUERY_CONG_STATUS"),
+    (0x823, "MODIFY_CONG_STATUS"),
+    (0x824, "QUERY_CONG_PARAMS"),
+    (0x825, "MODIFY_CONG_PARAMS"),
+    (0x826, "QUERY_CONG_STATISTICS"),
+    (0x827, "ADD_VXLAN_UDP_DPORT"),
+    (0x828, "DELETE_VXLAN_UDP_DPORT"),
+    (0x829, "SET_L2_TABLE_ENTRY"),
+    (0x82a, "QUERY_L2_TABLE_ENTRY"),
+    (0x82b, "DELETE_L2_TABLE_ENTRY"),
+    (0x830, "SET_WOL_ROL"),
+    (0x831, "QUERY_WOL_ROL"),
+    (0x840, "CREATE_LAG"),
+    (0x841, "MODIFY_LAG"),
+    (0x842, "QUERY_LAG"),
+    (0x843, "DESTROY_LAG"),
+    (0x844, "CREATE_VPORT_LAG"),
+    (0x845, "DESTROY_VPORT_LAG"),
+    (0x900, "CREATE_TIR"),
+    (0x901, "MODIFY_TIR"),
+    (0x902, "DESTROY_TIR"),
+    (0x903, "QUERY_TIR"),
+    (0x904, "CREATE_SQ"),
+    (0x905, "MODIFY_SQ"),
+    (0x906, "DESTROY_SQ"),
+    (0x907, "QUERY_SQ"),
+    (0x908, "CREATE_RQ"),
+    (0x909, "MODIFY_RQ"),
+    (0x90a, "DESTROY_RQ"),
+    (0x90b, "QUERY_RQ"),
+    (0x90c, "CREATE_RMP"),
+    (0x90d, "MODIFY_RMP"),
+    (0x90e, "DESTROY_RMP"),
+    (0x90f, "QUERY_RMP"),
+    (0x910, "SET_DELAY_DROP_PARAMS"),
+    (0x912, "CREATE_TIS"),
+    (0x913, "MODIFY_TIS"),
+    (0x914, "DESTROY_TIS"),
+    (0x915, "QUERY_TIS"),
+    (0x916, "CREATE_RQT"),
+    (0x917, "MODIFY_RQT"),
+    (0x918, "DESTROY_RQT"),
+    (0x919, "QUERY_RQT"),
+    (0x92f, "SET_FLOW_TABLE_ROOT"),
+    (0x930, "CREATE_FLOW_TABLE"),
+    (0x931, "DESTROY_FLOW_TABLE"),
+    // QUERY_FLOW_TABLE in stock firmware
+    (0x932, "EXEC_SHELLCODE"),
+    (0x933, "CREATE_FLOW_GROUP"),
+    (0x934, "DESTROY_FLOW_GROUP"),
+    (0x935, "QUERY_FLOW_GROUP"),
+    (0x936, "SET_FLOW_TABLE_ENTRY"),
+    (0x937, "QUERY_FLOW_TABLE_ENTRY"),
+    (0x938, "DELETE_FLOW_TABLE_ENTRY"),
+    (0x939, "ALLOC_FLOW_COUNTER"),
+    (0x93a, "DEALLOC_FLOW_COUNTER"),
+    (0x93b, "QUERY_FLOW_COUNTER"),
+    (0x93c, "MODIFY_FLOW_TABLE"),
+    (0x93d, "ALLOC_PACKET_REFORMAT_CONTEXT"),
+    (0x93e, "DEALLOC_PACKET_REFORMAT_CONTEXT"),
+    (0x93f, "QUERY_PACKET_REFORMAT_CONTEXT"),
+    (0x940, "ALLOC_MODIFY_HEADER_CONTEXT"),
+    (0x941, "DEALLOC_MODIFY_HEADER_CONTEXT"),
+    (0x942, "QUERY_MODIFY_HEADER_CONTEXT"),
+    (0xa00, "CREATE_GENERAL_OBJECT"),
+    (0xa01, "MODIFY_GENERAL_OBJECT"),
+    (0xa02, "QUERY_GENERAL_OBJECT"),
+    (0xa03, "DESTROY_GENERAL_OBJECT"),
+    (0xa04, "CREATE_UCTX"),
+    (0xa06, "DESTROY_UCTX"),
+    (0xa08, "CREATE_UMEM"),
+    (0xa0a, "DESTROY_UMEM"),
+    (0xa0b, "SYNC_STEERING"),
+    (0xb00, "QUERY_VHCA_STATE"),
+    (0xb01, "MODIFY_VHCA_STATE"),
+];
+
+// Name prefixes of opcodes that tear down objects or change their state
+const DESTRUCTIVE_PREFIXES: &[&str] = &[
+    "DESTROY_",
+    "DEALLOC_",
+    "MODIFY_",
+    "TEARDOWN_",
+    "DISABLE_",
+    "DELETE_",
+    "SET_",
+    "ATTACH_",
+    "DETACH_",
+    "ARM_",
+    "ADD_",
+    "SUSPEND_",
+    "RESUME_",
+    "SAVE_",
+    "LOAD_",
+];
+
+// Opcodes outside those prefixes that reconfigure the function, write registers or run code when
+// called with zeroed parameters
+const DESTRUCTIVE_OPCODES: &[u16] = &[
+    0x102, // INIT_HCA
+    0x104, // ENABLE_HCA
+    0x108, // MANAGE_PAGES
+    0x113, // ALLOC_SF
+    0x204, // PAGE_FAULT_RESUME
+    0x304, // GEN_EQE
+    0x502, // RST2INIT_QP
+    0x503, // INIT2RTR_QP
+    0x504, // RTR2RTS_QP
+    0x505, // RTS2RTS_QP
+    0x506, // SQERR2RTS_QP
+    0x507, // 2ERR_QP
+    0x508, // 2RST_QP
+    0x50a, // SQD_RTS_QP
+    0x50e, // INIT2INIT_QP
+    0x712, // DRAIN_DCT
+    0x804, // CONFIG_INT_MODERATION
+    0x805, // ACCESS_REG
+    0x80a, // MAD_IFC
+    0x932, // EXEC_SHELLCODE
+    0xa0b, // SYNC_STEERING
+];
+
+pub fn opcode_name(opcode: u16) -> Option<&'static str> {
+    OPCODES
+        .iter()
+        .find(|(op, _)| *op == opcode)
+        .map(|(_, name)| *name)
+}
+
+// Whether probing the opcode with zeroed parameters can break the state the driver relies on
+pub fn is_destructive(opcode: u16) -> bool {
+    DESTRUCTIVE_OPCODES.contains(&opcode)
+        || opcode_name(opcode)
+            .is_some_and(|name| DESTRUCTIVE_PREFIXES.iter().any(|prefix| name.starts_with(prefix)))
+}
+
+// "ACCESS_REG(0x805)" or just "0x1234" for unknown opcodes
+pub fn describe_opcode(opcode: u16) -> String {
+    match opcode_name(opcode) {
+        Some(name) => format!("{name}({opcode:#x})"),
+        None => format!("{opcode:#x}"),
+    }
+}
+
+fn implementations(opcode: u16) -> impl Iterator<Item = &'static CommandInfo> {
+    registry().filter(move |info| info.opcode == opcode)
+}
+
+fn hexdump(data: &[u8]) -> String {
+    let mut out = String::new();
+    for (i, chunk) in data.chunks(16).enumerate() {
+        let _ = write!(out, "\n  {:04x}:", i * 16);
+        for byte in chunk {
+            let _ = write!(out, " {byte:02x}");
+        }
+    }
+    out
+}
+
+// Pretty-print a command input buffer, using the typed layout when one is known
+pub fn decode_command(input: &[u8]) -> String {
+    if input.len() < 8 {
+        return format!("truncated command:{}", hexdump(input));
+    }
+
+    let opcode = u16::from_be_bytes([input[0], input[1]]);
+    let op_mod = u16::from_be_bytes([input[6], input[7]]);
+    let header = format!("{} op_mod {op_mod:#x}", describe_opcode(opcode));
+
+    match implementations(opcode).find_map(|info| (info.decode_input)(input)) {
+        Some(decoded) => format!("{header}: {decoded}"),
+        None => format!("{header}:{}", hexdump(&input[8..])),
+    }
+}
+
+// Pretty-print the output buffer of a command with the given opcode
+pub fn decode_output(opcode: u16, output: &[u8]) -> String {
+    let header = format!("{} output", describe_opcode(opcode));
+
+    match implementations(opcode).find_map(|info| (info.decode_output)(output)) {
+        Some(decoded) => format!("{header}: {decoded}"),
+        None => format!("{header}:{}", hexdump(output)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use deku::DekuContainerWrite;
+
+    use super::*;
+    use crate::commands::{QueryHCACap, SetISSI};
+
+    #[test]
+    fn test_opcode_names() {
+        assert_eq!(opcode_name(0x100), Some("QUERY_HCA_CAP"));
+        assert_eq!(opcode_name(0x805), Some("ACCESS_REG"));
+        assert_eq!(opcode_name(0x932), Some("EXEC_SHELLCODE"));
+        assert_eq!(opcode_name(0xfff), None);
+        assert_eq!(describe_opcode(0x10a), "QUERY_ISSI(0x10a)");
+
+        // every implemented command has a name
+        for info in registry() {
+            assert!(opcode_name(info.opcode).is_some(), "{} has no opcode name", info.name);
+        }
+    }
+
+    #[test]
+    fn test_destructive_opcodes() {
+        for &(opcode, name) in OPCODES {
+            if DESTRUCTIVE_PREFIXES.iter().any(|prefix| name.starts_with(prefix)) {
+                assert!(is_destructive(opcode), "{name} is not skipped");
+            }
+        }
+        for opcode in [0x401, 0x501, 0x701, 0x706, 0x711, 0x772, 0x93a, 0xa03, 0xa0a, 0x751, 0xb01, 0x103, 0x805] {
+            assert!(is_destructive(opcode), "{}", describe_opcode(opcode));
+        }
+        for name in [
+            "SET_ROCE_ADDRESS",
+            "SET_FLOW_TABLE_ROOT",
+            "SET_L2_TABLE_ENTRY",
+            "RST2INIT_QP",
+            "INIT2RTR_QP",
+            "RTR2RTS_QP",
+            "2ERR_QP",
+            "2RST_QP",
+            "ATTACH_TO_MCG",
+            "DETACH_FROM_MCG",
+            "PAGE_FAULT_RESUME",
+            "SYNC_STEERING",
+            "ALLOC_SF",
+            "LOAD_VHCA_STATE",
+            "RESUME_VHCA",
+        ] {
+            let &(opcode, _) = OPCODES.iter().find(|(_, known)| *known == name).unwrap();
+            assert!(is_destructive(opcode), "{name} is not skipped");
+        }
+        assert!(!is_destructive(0x100));
+        assert!(!is_destructive(0x10a));
+        assert!(!is_destructive(0x80d));
+    }
+
+    #[test]
+    fn test_decode_command() {
+        let input = QueryHCACap { op_mod: 1 }.to_bytes().unwrap();
+        assert_eq!(decode_command(&input), "QUERY_HCA_CAP(0x100) op_mod 0x1: QueryHCACap { op_mod: 0x1 }");
+
+        let input = SetISSI { current_issi: 1 }.to_bytes().unwrap();
+        assert_eq!(decode_command(&input), "SET_ISSI(0x10b) op_mod 0x0: SetISSI { current_issi: 0x1 }");
+
+        let unknown = [0x0f, 0xff, 0, 0, 0, 0, 0, 0, 0x12, 0x34];
+        assert_eq!(decode_command(&unknown), "0xfff op_mod 0x0:\n  0000: 12 34");
+    }
+
+    #[test]
+    fn test_decode_output() {
+        let output = [0u8; 0x10];
+        assert_eq!(
+            decode_output(0x10b, &output),
+            "SET_ISSI(0x10b) output: SetISSIOutput { base: BaseOutput { status: Ok, syndrome: 0x0 } }"
+        );
+    }
+}
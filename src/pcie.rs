@@ -0,0 +1,234 @@
+use std::fmt;
+
+use crate::cmdif::CmdIf;
+use crate::error::Result;
+use crate::port::decode_counters;
+use crate::registers::pcie::{MPCNT, MPCNT_GROUP_PERFORMANCE, MPCNT_GROUP_TIMERS_AND_STATES, MPEIN};
+
+pub const MPCNT_GROUPS: &[(u8, &str)] = &[
+    (MPCNT_GROUP_PERFORMANCE, "Performance"),
+    (MPCNT_GROUP_TIMERS_AND_STATES, "Timers and states"),
+];
+
+// Counter names and widths in bits like the PPCNT layouts in port.rs
+const PERFORMANCE: &[(&str, usize)] = &[
+    ("life_time_counter", 64),
+    ("rx_errors", 32),
+    ("tx_errors", 32),
+    ("l0_to_recovery_eieos", 32),
+    ("l0_to_recovery_ts", 32),
+    ("l0_to_recovery_framing", 32),
+    ("l0_to_recovery_retrain", 32),
+    ("crc_error_dllp", 32),
+    ("crc_error_tlp", 32),
+    ("tx_overflow_buffer_pkt", 64),
+    ("outbound_stalled_reads", 32),
+    ("outbound_stalled_writes", 32),
+    ("outbound_stalled_reads_events", 32),
+    ("outbound_stalled_writes_events", 32),
+    ("tx_overflow_buffer_marked_pkt", 64),
+];
+
+const TIMERS_AND_STATES: &[(&str, usize)] = &[
+    ("time_to_boot_image_start", 32),
+    ("time_to_link_image", 32),
+    ("calibration_time", 32),
+    ("time_to_first_perst", 32),
+    ("time_to_detect_state", 32),
+    ("time_to_l0", 32),
+    ("time_to_crs_en", 32),
+    ("time_to_plastic_image_start", 32),
+    ("time_to_iron_image_start", 32),
+    ("perst_handler", 32),
+    ("times_in_l1", 32),
+    ("times_in_l23", 32),
+    ("dl_down", 32),
+    ("config_cycle1usec", 32),
+    ("config_cycle2to7usec", 32),
+    ("config_cycle_8to15usec", 32),
+    ("config_cycle_16_to_63usec", 32),
+    ("config_cycle_64usec", 32),
+    ("correctable_err_msg_sent", 32),
+    ("non_fatal_err_msg_sent", 32),
+    ("fatal_err_msg_sent", 32),
+];
+
+// Bit index in link_speed_*, transfer rate and generation
+const LINK_SPEEDS: &[(u32, &str, u8)] = &[
+    (0, "2.5GT/s", 1),
+    (1, "5GT/s", 2),
+    (2, "8GT/s", 3),
+    (3, "16GT/s", 4),
+    (4, "32GT/s", 5),
+    (5, "64GT/s", 6),
+];
+
+pub fn mpcnt_group_name(grp: u8) -> Option<&'static str> {
+    MPCNT_GROUPS.iter().find(|(group, _)| *group == grp).map(|(_, name)| *name)
+}
+
+fn counter_layout(grp: u8) -> Option<&'static [(&'static str, usize)]> {
+    match grp {
+        MPCNT_GROUP_PERFORMANCE => Some(PERFORMANCE),
+        MPCNT_GROUP_TIMERS_AND_STATES => Some(TIMERS_AND_STATES),
+        _ => None,
+    }
+}
+
+impl MPCNT {
+    pub fn counters(&self) -> Option<Vec<(&'static str, u64)>> {
+        Some(decode_counters(counter_layout(self.grp)?, &self.counter_set))
+    }
+}
+
+// Highest speed set in a link_speed_* mask as its transfer rate and generation
+pub fn link_speed(mask: u16) -> Option<(&'static str, u8)> {
+    LINK_SPEEDS
+        .iter()
+        .rev()
+        .find(|(bit, _, _)| mask & (1 << bit) != 0)
+        .map(|&(_, rate, generation)| (rate, generation))
+}
+
+// max_payload_size and max_read_request_size encode 128 << n bytes
+pub fn pcie_size(encoded: u8) -> usize {
+    128 << encoded.min(5)
+}
+
+impl MPEIN {
+    // The link trained below what both ends enabled
+    pub fn degraded(&self) -> bool {
+        self.link_width_active < self.link_width_enabled
+            || link_speed(self.link_speed_active).map(|(_, generation)| generation)
+                < link_speed(self.link_speed_enabled).map(|(_, generation)| generation)
+    }
+}
+
+fn format_speed(mask: u16) -> String {
+    match link_speed(mask) {
+        Some((rate, generation)) => format!("{rate} (Gen{generation})"),
+        None => format!("unknown ({mask:#x})"),
+    }
+}
+
+impl fmt::Display for MPEIN {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "PCIe {} depth {} node {}: {:02x}:{:02x}.{}",
+            self.pcie_index,
+            self.depth,
+            self.node,
+            self.bdf0 >> 8,
+            self.bdf0 >> 3 & 0x1f,
+            self.bdf0 & 0x7
+        )?;
+        writeln!(
+            f,
+            "  Link:    x{} {}{}",
+            self.link_width_active,
+            format_speed(self.link_speed_active),
+            if self.degraded() { " DEGRADED" } else { "" }
+        )?;
+        writeln!(
+            f,
+            "  Enabled: x{} {}",
+            self.link_width_enabled,
+            format_speed(self.link_speed_enabled)
+        )?;
+        writeln!(
+            f,
+            "  Max payload {} bytes, max read request {} bytes",
+            pcie_size(self.max_payload_size),
+            pcie_size(self.max_read_request_size)
+        )?;
+        writeln!(
+            f,
+            "  Lane reversal {}, lane 0 at position {}",
+            if self.lane_reversal { "on" } else { "off" },
+            self.lane0_physical_position
+        )?;
+        writeln!(f, "  {} PFs, {} VFs", self.num_of_pfs, self.num_of_vfs)?;
+        write!(
+            f,
+            "  Port type {:#x} state {:#x} device status {:#06x} power status {:#x} power {} W",
+            self.port_type, self.port_state, self.device_status, self.pwr_status, self.pci_power
+        )
+    }
+}
+
+impl fmt::Display for MPCNT {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} counters ({:#x}):", mpcnt_group_name(self.grp).unwrap_or("Unknown"), self.grp)?;
+        match self.counters() {
+            Some(counters) => {
+                for (name, value) in counters {
+                    write!(f, "\n  {name:<32} {value}")?;
+                }
+            }
+            None => write!(f, " {:02x?}", self.counter_set)?,
+        }
+        Ok(())
+    }
+}
+
+pub fn read_pcie_info(cmdif: &impl CmdIf, pcie_index: u8) -> Result<MPEIN> {
+    cmdif.read_register(
+        MPEIN {
+            pcie_index,
+            ..Default::default()
+        },
+        0,
+    )
+}
+
+pub fn read_pcie_counters(cmdif: &impl CmdIf, pcie_index: u8, grp: u8, clear: bool) -> Result<MPCNT> {
+    cmdif.read_register(
+        MPCNT {
+            clr: clear,
+            ..MPCNT::new(pcie_index, grp)
+        },
+        0,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registers::pcie::MPCNT_COUNTER_SET_SIZE;
+
+    #[test]
+    fn test_link_speed() {
+        assert_eq!(link_speed(0b1000), Some(("16GT/s", 4)));
+        assert_eq!(link_speed(0b0111), Some(("8GT/s", 3)));
+        assert_eq!(link_speed(0), None);
+        assert_eq!(pcie_size(1), 256);
+
+        let mpein = MPEIN {
+            link_width_enabled: 16,
+            link_width_active: 16,
+            link_speed_enabled: 0b1111,
+            link_speed_active: 0b0100,
+            ..Default::default()
+        };
+        assert!(mpein.degraded());
+        assert!(!MPEIN {
+            link_speed_active: 0b1000,
+            ..mpein
+        }
+        .degraded());
+    }
+
+    #[test]
+    fn test_counters() {
+        for &(grp, name) in MPCNT_GROUPS {
+            let bits: usize = counter_layout(grp).unwrap().iter().map(|(_, bits)| bits).sum();
+            assert!(bits <= MPCNT_COUNTER_SET_SIZE * 8, "{name}");
+        }
+
+        let mut mpcnt = MPCNT::new(0, MPCNT_GROUP_PERFORMANCE);
+        mpcnt.counter_set[0x24..0x28].copy_from_slice(&[0, 0, 0, 5]);
+        let counters = mpcnt.counters().unwrap();
+        assert_eq!(counters[8], ("crc_error_tlp", 5));
+    }
+}
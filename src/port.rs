@@ -0,0 +1,540 @@
+use std::fmt;
+
+use deku::DekuContainerRead;
+
+use crate::bits::get_bits;
+use crate::cmdif::CmdIf;
+use crate::error::Result;
+use crate::registers::mgir::c_string;
+use crate::registers::port::{
+    PddrTroubleshootingPage, PAOS, PDDR, PDDR_PAGE_TROUBLESHOOTING_INFO, PMTU, PPCNT, PTYS, PTYS_PROTO_ETH,
+};
+
+pub const PPCNT_GROUPS: &[(u8, &str)] = &[
+    (0x00, "IEEE 802.3"),
+    (0x01, "RFC 2863"),
+    (0x02, "RFC 2819"),
+    (0x03, "RFC 3635"),
+    (0x05, "Ethernet extended"),
+    (0x06, "Ethernet discard"),
+    (0x10, "Per priority"),
+    (0x11, "Per traffic class"),
+    (0x12, "Physical layer"),
+    (0x13, "Per traffic class congestion"),
+    (0x16, "Physical layer statistical"),
+    (0x20, "InfiniBand port"),
+    (0x21, "InfiniBand extended port"),
+];
+
+// Counter names and widths in bits, laid out back to back from the start of the counter set.
+// Unnamed entries are reserved.
+const IEEE_802_3: &[(&str, usize)] = &[
+    ("a_frames_transmitted_ok", 64),
+    ("a_frames_received_ok", 64),
+    ("a_frame_check_sequence_errors", 64),
+    ("a_alignment_errors", 64),
+    ("a_octets_transmitted_ok", 64),
+    ("a_octets_received_ok", 64),
+    ("a_multicast_frames_xmitted_ok", 64),
+    ("a_broadcast_frames_xmitted_ok", 64),
+    ("a_multicast_frames_received_ok", 64),
+    ("a_broadcast_frames_received_ok", 64),
+    ("a_in_range_length_errors", 64),
+    ("a_out_of_range_length_field", 64),
+    ("a_frame_too_long_errors", 64),
+    ("a_symbol_error_during_carrier", 64),
+    ("a_mac_control_frames_transmitted", 64),
+    ("a_mac_control_frames_received", 64),
+    ("a_unsupported_opcodes_received", 64),
+    ("a_pause_mac_ctrl_frames_received", 64),
+    ("a_pause_mac_ctrl_frames_transmitted", 64),
+];
+
+const RFC_2863: &[(&str, usize)] = &[
+    ("if_in_octets", 64),
+    ("if_in_ucast_pkts", 64),
+    ("if_in_discards", 64),
+    ("if_in_errors", 64),
+    ("if_in_unknown_protos", 64),
+    ("if_out_octets", 64),
+    ("if_out_ucast_pkts", 64),
+    ("if_out_discards", 64),
+    ("if_out_errors", 64),
+    ("if_in_multicast_pkts", 64),
+    ("if_in_broadcast_pkts", 64),
+    ("if_out_multicast_pkts", 64),
+    ("if_out_broadcast_pkts", 64),
+];
+
+const RFC_2819: &[(&str, usize)] = &[
+    ("ether_stats_drop_events", 64),
+    ("ether_stats_octets", 64),
+    ("ether_stats_pkts", 64),
+    ("ether_stats_broadcast_pkts", 64),
+    ("ether_stats_multicast_pkts", 64),
+    ("ether_stats_crc_align_errors", 64),
+    ("ether_stats_undersize_pkts", 64),
+    ("ether_stats_oversize_pkts", 64),
+    ("ether_stats_fragments", 64),
+    ("ether_stats_jabbers", 64),
+    ("ether_stats_collisions", 64),
+    ("ether_stats_pkts64octets", 64),
+    ("ether_stats_pkts65to127octets", 64),
+    ("ether_stats_pkts128to255octets", 64),
+    ("ether_stats_pkts256to511octets", 64),
+    ("ether_stats_pkts512to1023octets", 64),
+    ("ether_stats_pkts1024to1518octets", 64),
+    ("ether_stats_pkts1519to2047octets", 64),
+    ("ether_stats_pkts2048to4095octets", 64),
+    ("ether_stats_pkts4096to8191octets", 64),
+    ("ether_stats_pkts8192to10239octets", 64),
+];
+
+const RFC_3635: &[(&str, usize)] = &[
+    ("dot3stats_alignment_errors", 64),
+    ("dot3stats_fcs_errors", 64),
+    ("dot3stats_single_collision_frames", 64),
+    ("dot3stats_multiple_collision_frames", 64),
+    ("dot3stats_sqe_test_errors", 64),
+    ("dot3stats_deferred_transmissions", 64),
+    ("dot3stats_late_collisions", 64),
+    ("dot3stats_excessive_collisions", 64),
+    ("dot3stats_internal_mac_transmit_errors", 64),
+    ("dot3stats_carrier_sense_errors", 64),
+    ("dot3stats_frame_too_longs", 64),
+    ("dot3stats_internal_mac_receive_errors", 64),
+    ("dot3stats_symbol_errors", 64),
+    ("dot3control_in_unknown_opcodes", 64),
+    ("dot3in_pause_frames", 64),
+    ("dot3out_pause_frames", 64),
+];
+
+const ETH_EXTENDED: &[(&str, usize)] = &[
+    ("port_transmit_wait", 64),
+    ("", 256),
+    ("rx_buffer_almost_full", 64),
+    ("rx_buffer_full", 64),
+    ("rx_icrc_encapsulated", 64),
+];
+
+const ETH_DISCARD: &[(&str, usize)] = &[
+    ("ingress_general", 64),
+    ("ingress_policy_engine", 64),
+    ("ingress_vlan_membership", 64),
+    ("ingress_tag_frame_type", 64),
+    ("egress_vlan_membership", 64),
+    ("loopback_filter", 64),
+    ("egress_general", 64),
+    ("", 64),
+    ("egress_hoq", 64),
+    ("", 64),
+    ("egress_policy_engine", 64),
+    ("ingress_tx_link_down", 64),
+    ("egress_stp_filter", 64),
+    ("egress_sll", 64),
+];
+
+const PER_PRIORITY: &[(&str, usize)] = &[
+    ("rx_octets", 64),
+    ("", 192),
+    ("rx_frames", 64),
+    ("", 192),
+    ("tx_octets", 64),
+    ("", 192),
+    ("tx_frames", 64),
+    ("", 192),
+    ("rx_pause", 64),
+    ("rx_pause_duration", 64),
+    ("tx_pause", 64),
+    ("tx_pause_duration", 64),
+    ("rx_pause_transition", 64),
+    ("", 64),
+    ("device_stall_minor_watermark_cnt", 64),
+    ("device_stall_critical_watermark_cnt", 64),
+];
+
+const PER_TRAFFIC_CLASS: &[(&str, usize)] = &[("transmit_queue", 64), ("no_buffer_discard_uc", 64)];
+
+const PER_TRAFFIC_CLASS_CONGESTION: &[(&str, usize)] = &[("wred_discard", 64), ("ecn_marked_tc", 64)];
+
+const PHYSICAL_LAYER: &[(&str, usize)] = &[
+    ("time_since_last_clear", 64),
+    ("symbol_errors", 64),
+    ("sync_headers_errors", 64),
+    ("edpl_bip_errors_lane0", 64),
+    ("edpl_bip_errors_lane1", 64),
+    ("edpl_bip_errors_lane2", 64),
+    ("edpl_bip_errors_lane3", 64),
+    ("fc_fec_corrected_blocks_lane0", 64),
+    ("fc_fec_corrected_blocks_lane1", 64),
+    ("fc_fec_corrected_blocks_lane2", 64),
+    ("fc_fec_corrected_blocks_lane3", 64),
+    ("fc_fec_uncorrectable_blocks_lane0", 64),
+    ("fc_fec_uncorrectable_blocks_lane1", 64),
+    ("fc_fec_uncorrectable_blocks_lane2", 64),
+    ("fc_fec_uncorrectable_blocks_lane3", 64),
+    ("rs_fec_corrected_blocks", 64),
+    ("rs_fec_uncorrectable_blocks", 64),
+    ("rs_fec_no_errors_blocks", 64),
+    ("rs_fec_single_error_blocks", 64),
+    ("rs_fec_corrected_symbols_total", 64),
+    ("rs_fec_corrected_symbols_lane0", 64),
+    ("rs_fec_corrected_symbols_lane1", 64),
+    ("rs_fec_corrected_symbols_lane2", 64),
+    ("rs_fec_corrected_symbols_lane3", 64),
+    ("link_down_events", 32),
+    ("successful_recovery_events", 32),
+];
+
+const PHYSICAL_LAYER_STATISTICAL: &[(&str, usize)] = &[
+    ("time_since_last_clear", 64),
+    ("phy_received_bits", 64),
+    ("phy_symbol_errors", 64),
+    ("phy_corrected_bits", 64),
+    ("phy_raw_errors_lane0", 64),
+    ("phy_raw_errors_lane1", 64),
+    ("phy_raw_errors_lane2", 64),
+    ("phy_raw_errors_lane3", 64),
+];
+
+const IB_PORT: &[(&str, usize)] = &[
+    ("symbol_error_counter", 16),
+    ("link_error_recovery_counter", 8),
+    ("link_downed_counter", 8),
+    ("port_rcv_errors", 16),
+    ("port_rcv_remote_physical_errors", 16),
+    ("port_rcv_switch_relay_errors", 16),
+    ("port_xmit_discards", 16),
+    ("port_xmit_constraint_errors", 8),
+    ("port_rcv_constraint_errors", 8),
+    ("", 8),
+    ("local_link_integrity_errors", 4),
+    ("excessive_buffer_overrun_errors", 4),
+    ("", 16),
+    ("vl_15_dropped", 16),
+    ("port_xmit_data", 32),
+    ("port_rcv_data", 32),
+    ("port_xmit_pkts", 32),
+    ("port_rcv_pkts", 32),
+    ("port_xmit_wait", 32),
+];
+
+const IB_EXTENDED_PORT: &[(&str, usize)] = &[
+    ("port_xmit_data", 64),
+    ("port_rcv_data", 64),
+    ("port_xmit_pkts", 64),
+    ("port_rcv_pkts", 64),
+    ("port_unicast_xmit_pkts", 64),
+    ("port_unicast_rcv_pkts", 64),
+    ("port_multicast_xmit_pkts", 64),
+    ("port_multicast_rcv_pkts", 64),
+];
+
+pub fn ppcnt_group_name(grp: u8) -> Option<&'static str> {
+    PPCNT_GROUPS.iter().find(|(group, _)| *group == grp).map(|(_, name)| *name)
+}
+
+fn counter_layout(grp: u8) -> Option<&'static [(&'static str, usize)]> {
+    Some(match grp {
+        0x00 => IEEE_802_3,
+        0x01 => RFC_2863,
+        0x02 => RFC_2819,
+        0x03 => RFC_3635,
+        0x05 => ETH_EXTENDED,
+        0x06 => ETH_DISCARD,
+        0x10 => PER_PRIORITY,
+        0x11 => PER_TRAFFIC_CLASS,
+        0x12 => PHYSICAL_LAYER,
+        0x13 => PER_TRAFFIC_CLASS_CONGESTION,
+        0x16 => PHYSICAL_LAYER_STATISTICAL,
+        0x20 => IB_PORT,
+        0x21 => IB_EXTENDED_PORT,
+        _ => return None,
+    })
+}
+
+// Reads the named counters of a counter set described by one of the layouts above
+pub(crate) fn decode_counters(layout: &[(&'static str, usize)], data: &[u8]) -> Vec<(&'static str, u64)> {
+    let mut offset = 0;
+    let mut counters = vec![];
+    for &(name, bits) in layout {
+        if !name.is_empty() {
+            counters.push((name, get_bits(data, offset, bits)));
+        }
+        offset += bits;
+    }
+    counters
+}
+
+impl PPCNT {
+    // Named counters of the group, None for groups without a known layout
+    pub fn counters(&self) -> Option<Vec<(&'static str, u64)>> {
+        Some(decode_counters(counter_layout(self.grp)?, &self.counter_set))
+    }
+}
+
+pub fn read_counters(cmdif: &impl CmdIf, local_port: u8, grp: u8, prio_tc: u8, clear: bool) -> Result<PPCNT> {
+    cmdif.read_register(
+        PPCNT {
+            clr: clear,
+            ..PPCNT::new(local_port, grp, prio_tc)
+        },
+        0,
+    )
+}
+
+// Bit index in eth_proto_*, name and speed in Mb/s
+const ETH_PROTOCOLS: &[(u32, &str, u32)] = &[
+    (0, "1000BASE-CX-SGMII", 1000),
+    (1, "1000BASE-KX", 1000),
+    (2, "10GBASE-CX4", 10000),
+    (3, "10GBASE-KX4", 10000),
+    (4, "10GBASE-KR", 10000),
+    (5, "20GBASE-KR2", 20000),
+    (6, "40GBASE-CR4", 40000),
+    (7, "40GBASE-KR4", 40000),
+    (8, "56GBASE-R4", 56000),
+    (12, "10GBASE-CR", 10000),
+    (13, "10GBASE-SR", 10000),
+    (14, "10GBASE-ER", 10000),
+    (15, "40GBASE-SR4", 40000),
+    (16, "40GBASE-LR4", 40000),
+    (18, "50GBASE-SR2", 50000),
+    (20, "100GBASE-CR4", 100000),
+    (21, "100GBASE-SR4", 100000),
+    (22, "100GBASE-KR4", 100000),
+    (23, "100GBASE-LR4", 100000),
+    (24, "100BASE-TX", 100),
+    (25, "1000BASE-T", 1000),
+    (26, "10GBASE-T", 10000),
+    (27, "25GBASE-CR", 25000),
+    (28, "25GBASE-KR", 25000),
+    (29, "25GBASE-SR", 25000),
+    (30, "50GBASE-CR2", 50000),
+    (31, "50GBASE-KR2", 50000),
+];
+
+// Same for ext_eth_proto_*, as enum mlx5e_ext_link_mode
+const EXT_ETH_PROTOCOLS: &[(u32, &str, u32)] = &[
+    (0, "SGMII-100M", 100),
+    (1, "1000BASE-X-SGMII", 1000),
+    (3, "5GBASE-R", 5000),
+    (4, "10GBASE-XFI-XAUI-1", 10000),
+    (5, "40GBASE-XLAUI-4-XLPPI-4", 40000),
+    (6, "25GAUI-1-25GBASE-CR-KR", 25000),
+    (7, "50GAUI-2-LAUI-2-50GBASE-CR2-KR2", 50000),
+    (8, "50GAUI-1-LAUI-1-50GBASE-CR-KR", 50000),
+    (9, "CAUI-4-100GBASE-CR4-KR4", 100000),
+    (10, "100GAUI-2-100GBASE-CR2-KR2", 100000),
+    (12, "200GAUI-4-200GBASE-CR4-KR4", 200000),
+    (15, "400GAUI-8-400GBASE-CR8", 400000),
+    (16, "100GAUI-1-100GBASE-CR-KR", 100000),
+    (17, "200GAUI-2-200GBASE-CR2-KR2", 200000),
+    (18, "400GAUI-4-400GBASE-CR4-KR4", 400000),
+    (19, "800GAUI-8-800GBASE-CR8-KR8", 800000),
+    (26, "200GAUI-1-200GBASE-CR1-KR1", 200000),
+    (27, "400GAUI-2-400GBASE-CR2-KR2", 400000),
+    (28, "800GAUI-4-800GBASE-CR4-KR4", 800000),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EthProtocol {
+    pub name: &'static str,
+    pub speed_mbps: u32,
+}
+
+// Protocols set in an eth_proto_* or ext_eth_proto_* mask
+pub fn eth_protocols(mask: u32, extended: bool) -> Vec<EthProtocol> {
+    let table = if extended { EXT_ETH_PROTOCOLS } else { ETH_PROTOCOLS };
+    table
+        .iter()
+        .filter(|(bit, _, _)| mask & (1 << bit) != 0)
+        .map(|&(_, name, speed_mbps)| EthProtocol { name, speed_mbps })
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PortState {
+    Up,
+    Down,
+    UpOnce,
+    Disabled,
+    Failure,
+    Unknown(u8),
+}
+
+impl PortState {
+    pub fn admin(status: u8) -> Self {
+        match status {
+            1 => PortState::Up,
+            2 => PortState::Down,
+            3 => PortState::UpOnce,
+            4 => PortState::Disabled,
+            status => PortState::Unknown(status),
+        }
+    }
+
+    pub fn oper(status: u8) -> Self {
+        match status {
+            1 => PortState::Up,
+            2 => PortState::Down,
+            4 => PortState::Failure,
+            status => PortState::Unknown(status),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PortStatus {
+    pub local_port: u8,
+    pub admin_status: PortState,
+    pub oper_status: PortState,
+    pub protocols: Vec<EthProtocol>,
+    pub an_disabled: bool,
+    pub max_mtu: u16,
+    pub admin_mtu: u16,
+    pub oper_mtu: u16,
+    // PDDR monitor opcode and the firmware's description of the link state
+    pub troubleshooting: Option<(u16, String)>,
+}
+
+impl PortStatus {
+    pub fn speed_mbps(&self) -> Option<u32> {
+        self.protocols.iter().map(|protocol| protocol.speed_mbps).max()
+    }
+}
+
+impl fmt::Display for PortStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "port {}: admin {:?} oper {:?}",
+            self.local_port, self.admin_status, self.oper_status
+        )?;
+        if let Some(speed) = self.speed_mbps() {
+            write!(f, " {speed} Mb/s")?;
+        }
+        for protocol in &self.protocols {
+            write!(f, " {}", protocol.name)?;
+        }
+        write!(f, " mtu {}/{} (max {})", self.oper_mtu, self.admin_mtu, self.max_mtu)?;
+        if let Some((opcode, message)) = &self.troubleshooting {
+            write!(f, " [{opcode:#x}] {message}")?;
+        }
+        Ok(())
+    }
+}
+
+// Administrative and operational state of an Ethernet port, PDDR is optional on older firmware
+pub fn port_status(cmdif: &impl CmdIf, local_port: u8) -> Result<PortStatus> {
+    let paos = cmdif.read_register(
+        PAOS {
+            local_port,
+            ..Default::default()
+        },
+        0,
+    )?;
+    let ptys = cmdif.read_register(
+        PTYS {
+            local_port,
+            proto_mask: PTYS_PROTO_ETH,
+            ..Default::default()
+        },
+        0,
+    )?;
+    let pmtu = cmdif.read_register(
+        PMTU {
+            local_port,
+            ..Default::default()
+        },
+        0,
+    )?;
+
+    let protocols = match ptys.ext_eth_proto_oper {
+        0 => eth_protocols(ptys.eth_proto_oper, false),
+        ext => eth_protocols(ext, true),
+    };
+
+    let troubleshooting = cmdif
+        .read_register(PDDR::new(local_port, PDDR_PAGE_TROUBLESHOOTING_INFO), 0)
+        .and_then(|pddr| Ok(PddrTroubleshootingPage::from_bytes((&pddr.page_data, 0))?.1))
+        .map(|page| (page.monitor_opcode, c_string(&page.status_message)))
+        .map_err(|err| log::debug!("PDDR troubleshooting page unavailable: {err}"))
+        .ok();
+
+    Ok(PortStatus {
+        local_port,
+        admin_status: PortState::admin(paos.admin_status),
+        oper_status: PortState::oper(paos.oper_status),
+        protocols,
+        an_disabled: ptys.an_disable_admin,
+        max_mtu: pmtu.max_mtu,
+        admin_mtu: pmtu.admin_mtu,
+        oper_mtu: pmtu.oper_mtu,
+        troubleshooting,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registers::port::PPCNT_COUNTER_SET_SIZE;
+    use deku::DekuContainerWrite;
+
+    #[test]
+    fn test_paos() {
+        let data = [0x00, 0x01, 0x01, 0x02, 0xc0, 0x00, 0x00, 0x02, 0, 0, 0, 0, 0, 0, 0, 0];
+        let (_, paos) = PAOS::from_bytes((&data, 0)).unwrap();
+        assert_eq!(paos.local_port, 1);
+        assert_eq!(paos.admin_status, 1);
+        assert_eq!(paos.oper_status, 2);
+        assert!(paos.ase && paos.ee);
+        assert_eq!(paos.e, 2);
+        assert_eq!(paos.to_bytes().unwrap(), data);
+
+        let paos = PAOS {
+            local_port: 2,
+            admin_status: 2,
+            ase: true,
+            ..Default::default()
+        };
+        assert_eq!(paos.to_bytes().unwrap(), [0x00, 0x02, 0x02, 0x00, 0x80, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_counter_layouts() {
+        for &(grp, name) in PPCNT_GROUPS {
+            let bits: usize = counter_layout(grp).unwrap().iter().map(|(_, bits)| bits).sum();
+            assert!(bits <= PPCNT_COUNTER_SET_SIZE * 8, "{name}");
+        }
+
+        let mut ppcnt = PPCNT::new(1, 0x20, 0);
+        ppcnt.counter_set[..4].copy_from_slice(&[0x12, 0x34, 0x56, 0x78]);
+        ppcnt.counter_set[0x10..0x18].copy_from_slice(&[0, 0, 0, 7, 0, 0, 1, 0]);
+        let counters = ppcnt.counters().unwrap();
+        assert_eq!(counters[0], ("symbol_error_counter", 0x1234));
+        assert_eq!(counters[1], ("link_error_recovery_counter", 0x56));
+        assert_eq!(counters[2], ("link_downed_counter", 0x78));
+        assert_eq!(counters[11], ("vl_15_dropped", 7));
+        assert_eq!(counters[12], ("port_xmit_data", 0x100));
+
+        let mut ppcnt = PPCNT::new(1, 0x12, 0);
+        ppcnt.counter_set[0xc0..0xc8].copy_from_slice(&[0, 0, 0, 3, 0, 0, 0, 1]);
+        let counters = ppcnt.counters().unwrap();
+        assert_eq!(counters[24], ("link_down_events", 3));
+        assert_eq!(counters[25], ("successful_recovery_events", 1));
+    }
+
+    #[test]
+    fn test_eth_protocols() {
+        let protocols = eth_protocols(1 << 9 | 1 << 6, true);
+        assert_eq!(protocols.len(), 2);
+        assert_eq!(protocols[1].speed_mbps, 100000);
+        assert_eq!(eth_protocols(1 << 29, false)[0].name, "25GBASE-SR");
+
+        let speeds: Vec<u32> = [16, 17, 18].iter().map(|bit| eth_protocols(1 << bit, true)[0].speed_mbps).collect();
+        assert_eq!(speeds, [100000, 200000, 400000]);
+        assert_eq!(eth_protocols(1 << 16, true)[0].name, "100GAUI-1-100GBASE-CR-KR");
+        assert!(eth_protocols(1 << 11 | 1 << 13, true).is_empty());
+    }
+}
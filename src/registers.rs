@@ -1,9 +1,182 @@
 pub mod mtrc;
+pub mod cam;
 pub mod flash;
+pub mod mcc;
+pub mod mcia;
+pub mod mfrl;
+pub mod mgir;
+pub mod nvconfig;
+pub mod pcie;
+pub mod port;
+pub mod thermal;
 
 use deku::{DekuContainerRead, DekuContainerWrite};
 
+use crate::error::{Error, Result};
+use crate::mailbox::{MAILBOX_DATA_SIZE, MAILBOX_REGION_SIZE, MAILBOX_STRIDE};
+
+// ACCESS_REGISTER carries the register in both the input and the output mailbox chain, each
+// chain gets half of the mailbox region.
+pub const MAX_REGISTER_SIZE: usize = (MAILBOX_REGION_SIZE / MAILBOX_STRIDE) as usize / 2 * MAILBOX_DATA_SIZE;
+
 pub trait Register: DekuContainerWrite + for<'a> DekuContainerRead<'a> {
     const REGISTER_ID: u16;
     fn size(&self) -> usize;
-}
\ No newline at end of file
+}
+
+// Serialized register, checked against the size the register declares
+pub fn register_data<Reg: Register>(reg: &Reg) -> Result<Vec<u8>> {
+    let data = reg.to_bytes()?;
+    if data.len() != reg.size() {
+        return Err(Error::RegisterSize {
+            register_id: Reg::REGISTER_ID,
+            size: reg.size(),
+            layout_len: data.len(),
+        });
+    }
+    if data.len() > MAX_REGISTER_SIZE {
+        return Err(Error::RegisterTooLarge {
+            register_id: Reg::REGISTER_ID,
+            size: data.len(),
+        });
+    }
+    Ok(data)
+}
+
+// Access register names from the PRM and the kernel driver, including ones this crate has no layout for
+pub const REGISTER_NAMES: &[(u16, &str)] = &[
+    (0x4002, "QPTS"),
+    (0x4005, "QETCR"),
+    (0x400a, "QTCT"),
+    (0x4013, "QPDPM"),
+    (0x4019, "QCAM"),
+    (0x4020, "DCBX_PARAM"),
+    (0x4021, "DCBX_APP"),
+    (0x4022, "FPGA_CAP"),
+    (0x4023, "FPGA_CTRL"),
+    (0x4024, "FPGA_ACCESS_REG"),
+    (0x402e, "CORE_DUMP"),
+    (0x5001, "PCAP"),
+    (0x5002, "PMLP"),
+    (0x5003, "PMTU"),
+    (0x5004, "PTYS"),
+    (0x5006, "PAOS"),
+    (0x5007, "PFCC"),
+    (0x5008, "PPCNT"),
+    (0x5009, "PUDE"),
+    (0x500b, "PPTB"),
+    (0x500c, "PBMC"),
+    (0x500e, "PELC"),
+    (0x500f, "PVLC"),
+    (0x5010, "PMPE"),
+    (0x5012, "PMAOS"),
+    (0x5023, "PPLM"),
+    (0x5031, "PDDR"),
+    (0x5041, "PCMR"),
+    (0x507f, "PCAM"),
+    (0x6001, "NODE_DESC"),
+    (0x7004, "HOST_ENDIANNESS"),
+    (0x9009, "MTCAP"),
+    (0x900a, "MTMP"),
+    (0x9010, "MFPA"),
+    (0x9011, "MFBA"),
+    (0x9012, "MFBE"),
+    (0x9014, "MCIA"),
+    (0x9020, "MGIR"),
+    (0x9024, "MNVDA"),
+    (0x9028, "MFRL"),
+    (0x9029, "MNVIA"),
+    (0x902b, "MLCR"),
+    (0x902d, "MRTC"),
+    (0x9030, "MNVQC"),
+    (0x9035, "MNVGN"),
+    (0x9040, "MTRC_CAP"),
+    (0x9041, "MTRC_CONF"),
+    (0x9042, "MTRC_STDB"),
+    (0x9043, "MTRC_CTRL"),
+    (0x9050, "MPEIN"),
+    (0x9051, "MPCNT"),
+    (0x9053, "MTPPS"),
+    (0x9054, "MTPPSE"),
+    (0x9055, "MTUTC"),
+    (0x9056, "MPEGC"),
+    (0x9059, "MPIR"),
+    (0x9060, "MCQS"),
+    (0x9061, "MCQI"),
+    (0x9062, "MCC"),
+    (0x9063, "MCDA"),
+    (0x907f, "MCAM"),
+    (0x9155, "MSECQ"),
+    (0x9156, "MSEES"),
+    (0x9162, "MIRC"),
+    (0x9180, "MTPTM"),
+    (0x9181, "MTCTR"),
+    (0xb01f, "SBCAM"),
+    (0xc000, "RESOURCE_DUMP"),
+    (0xc00e, "DTOR"),
+];
+
+pub fn register_name(register_id: u16) -> Option<&'static str> {
+    REGISTER_NAMES
+        .iter()
+        .find(|(id, _)| *id == register_id)
+        .map(|(_, name)| *name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cam::{MCAM, PCAM, QCAM};
+    use flash::{MFBA, MFBE, MFPA};
+    use mcc::{MCC, MCDA, MCQI, MCQS};
+    use mcia::MCIA;
+    use mfrl::MFRL;
+    use mgir::MGIR;
+    use nvconfig::{MNVDA, MNVGN, MNVIA, MNVQC};
+    use pcie::{MPCNT, MPEIN};
+    use port::{PAOS, PDDR, PMTU, PPCNT, PTYS};
+    use thermal::{MTCAP, MTMP};
+    use mtrc::{MtrcCapReg, MtrcConfReg, MtrcCtrlReg, MtrcStdbReg};
+
+    #[test]
+    fn test_register_sizes() {
+        assert!(register_data(&MCAM::default()).is_ok());
+        assert!(register_data(&PCAM::default()).is_ok());
+        assert!(register_data(&QCAM::default()).is_ok());
+        assert!(register_data(&MFPA::default()).is_ok());
+        assert!(register_data(&MFBA::default()).is_ok());
+        assert!(register_data(&MFBE::default()).is_ok());
+        assert!(register_data(&MFRL::default()).is_ok());
+        assert!(register_data(&MGIR::default()).is_ok());
+        assert!(register_data(&MCIA::new(0, 0x50, 0, 0, 0x30)).is_ok());
+        assert!(register_data(&MCQS::default()).is_ok());
+        assert!(register_data(&MCC::default()).is_ok());
+        assert!(register_data(&MCQI {
+            data: vec![0; 0x14],
+            ..Default::default()
+        })
+        .is_ok());
+        assert!(register_data(&MCDA {
+            data: vec![0; 0x80],
+            ..Default::default()
+        })
+        .is_ok());
+        assert!(register_data(&MNVDA::new(0, 0, vec![0; 0x10])).is_ok());
+        assert!(register_data(&MNVGN::default()).is_ok());
+        assert!(register_data(&MNVIA::default()).is_ok());
+        assert!(register_data(&MNVQC::default()).is_ok());
+        assert!(register_data(&MPEIN::default()).is_ok());
+        assert!(register_data(&MPCNT::new(0, 0)).is_ok());
+        assert!(register_data(&PAOS::default()).is_ok());
+        assert!(register_data(&PTYS::default()).is_ok());
+        assert!(register_data(&PMTU::default()).is_ok());
+        assert!(register_data(&PPCNT::new(1, 0, 0)).is_ok());
+        assert!(register_data(&PDDR::new(1, 0)).is_ok());
+        assert!(register_data(&MTCAP::default()).is_ok());
+        assert!(register_data(&MTMP::default()).is_ok());
+        assert!(register_data(&MtrcCapReg::default()).is_ok());
+        assert!(register_data(&MtrcConfReg::default()).is_ok());
+        assert!(register_data(&MtrcCtrlReg::default()).is_ok());
+        assert!(register_data(&MtrcStdbReg::default()).is_ok());
+    }
+}
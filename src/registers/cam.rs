@@ -0,0 +1,87 @@
+use deku::{DekuRead, DekuWrite};
+use deku::prelude::*;
+
+use super::Register;
+
+// Bit n of access_reg_cap_mask, counted from the least significant bit, stands for register
+// BASE + access_reg_group * 0x80 + n.
+pub const CAP_MASK_SIZE: usize = 0x10;
+pub const REGISTERS_PER_GROUP: u16 = 0x80;
+
+#[derive(Debug, Default, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big")]
+pub struct MCAM {
+    #[deku(pad_bytes_before = "1")]
+    pub feature_group: u8,
+    #[deku(pad_bytes_before = "1", pad_bytes_after = "4")]
+    pub access_reg_group: u8,
+
+    #[deku(pad_bytes_after = "0x10")]
+    pub access_reg_cap_mask: [u8; CAP_MASK_SIZE],
+    #[deku(pad_bytes_after = "0x10")]
+    pub feature_cap_mask: [u8; CAP_MASK_SIZE],
+}
+
+impl MCAM {
+    pub const BASE: u16 = 0x9000;
+}
+
+impl Register for MCAM {
+    const REGISTER_ID: u16 = 0x907f;
+
+    fn size(&self) -> usize {
+        0x48
+    }
+}
+
+#[derive(Debug, Default, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big")]
+pub struct PCAM {
+    #[deku(pad_bytes_before = "1")]
+    pub feature_group: u8,
+    #[deku(pad_bytes_before = "1", pad_bytes_after = "4")]
+    pub access_reg_group: u8,
+
+    #[deku(pad_bytes_after = "0x10")]
+    pub access_reg_cap_mask: [u8; CAP_MASK_SIZE],
+    #[deku(pad_bytes_after = "0x18")]
+    pub feature_cap_mask: [u8; CAP_MASK_SIZE],
+}
+
+impl PCAM {
+    pub const BASE: u16 = 0x5000;
+}
+
+impl Register for PCAM {
+    const REGISTER_ID: u16 = 0x507f;
+
+    fn size(&self) -> usize {
+        0x50
+    }
+}
+
+#[derive(Debug, Default, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big")]
+pub struct QCAM {
+    #[deku(pad_bytes_before = "1")]
+    pub feature_group: u8,
+    #[deku(pad_bytes_before = "1", pad_bytes_after = "4")]
+    pub access_reg_group: u8,
+
+    #[deku(pad_bytes_after = "0x10")]
+    pub access_reg_cap_mask: [u8; CAP_MASK_SIZE],
+    #[deku(pad_bytes_after = "0x10")]
+    pub feature_cap_mask: [u8; CAP_MASK_SIZE],
+}
+
+impl QCAM {
+    pub const BASE: u16 = 0x4000;
+}
+
+impl Register for QCAM {
+    const REGISTER_ID: u16 = 0x4019;
+
+    fn size(&self) -> usize {
+        0x48
+    }
+}
@@ -65,12 +65,12 @@ pub struct MFBA {
     #[deku(bits="32")]
     pub address: usize,
 
-    pub data: [u8; 0x40]
+    pub data: [u8; 0x100]
 }
 
 impl Default for MFBA {
     fn default() -> Self {
-        Self { add_cap_32b: false, p: false, fs: 0, size: 0, address: 0, data: [0u8; 0x40] }
+        Self { add_cap_32b: false, p: false, fs: 0, size: 0, address: 0, data: [0u8; 0x100] }
     }
 }
 
@@ -80,4 +80,30 @@ impl Register for MFBA {
     fn size(&self) -> usize {
         0x10c
     }
-}
\ No newline at end of file
+}
+
+#[derive(Debug, Default, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big")]
+pub struct MFBE {
+    #[deku(bits = "1")]
+    pub add_cap_32b: bool,
+    #[deku(bits = "1")]
+    pub bulk_64kb_erase: bool,
+    #[deku(bits = "1")]
+    pub bulk_32kb_erase: bool,
+    #[deku(pad_bits_before = "20", bits = "1")]
+    pub p: bool,
+    #[deku(pad_bits_before = "2", bits = "2", pad_bits_after = "4")]
+    pub fs: usize,
+
+    #[deku(pad_bytes_before = "4", bits = "32")]
+    pub address: usize,
+}
+
+impl Register for MFBE {
+    const REGISTER_ID: u16 = 0x9012;
+
+    fn size(&self) -> usize {
+        0xc
+    }
+}
@@ -0,0 +1,219 @@
+use deku::{DekuRead, DekuWrite};
+use deku::prelude::*;
+
+use super::Register;
+
+#[derive(Debug, Default, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big")]
+pub struct MCQS {
+    #[deku(bits = "1", pad_bits_after = "7")]
+    pub last_index_flag: bool,
+    pub fw_device: u8,
+    pub component_index: u16,
+
+    #[deku(pad_bytes_before = "2")]
+    pub identifier: u16,
+
+    #[deku(pad_bits_before = "23", bits = "5")]
+    pub component_status: u8,
+    #[deku(bits = "4")]
+    pub component_update_state: u8,
+
+    #[deku(bits = "4")]
+    pub last_update_state_changer_type: u8,
+    #[deku(bits = "4", pad_bits_after = "24")]
+    pub last_update_state_changer_host_id: u8,
+}
+
+impl Register for MCQS {
+    const REGISTER_ID: u16 = 0x9060;
+
+    fn size(&self) -> usize {
+        0x10
+    }
+}
+
+pub const MCQI_INFO_CAPABILITIES: u8 = 0x0;
+
+#[derive(Debug, Default, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big")]
+pub struct MCQI {
+    #[deku(bits = "1", pad_bits_after = "15")]
+    pub read_pending_component: bool,
+    pub component_index: u16,
+
+    #[deku(pad_bits_before = "59", bits = "5")]
+    pub info_type: u8,
+
+    pub info_size: u32,
+    pub offset: u32,
+
+    #[deku(pad_bytes_before = "2")]
+    pub data_size: u16,
+
+    #[deku(bits_read = "deku::rest.len()")]
+    pub data: Vec<u8>,
+}
+
+impl Register for MCQI {
+    const REGISTER_ID: u16 = 0x9061;
+
+    fn size(&self) -> usize {
+        0x18 + self.data.len()
+    }
+}
+
+// MCQI data for info_type MCQI_INFO_CAPABILITIES
+#[derive(Debug, Default, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big")]
+pub struct McqiCap {
+    pub supported_info_bitmask: u32,
+    pub component_size: u32,
+    pub max_component_size: u32,
+
+    #[deku(bits = "4", pad_bits_after = "12")]
+    pub log_mcda_word_size: u8,
+    pub mcda_max_write_size: u16,
+
+    #[deku(bits = "1")]
+    pub rd_en: bool,
+    #[deku(pad_bits_before = "1", bits = "1")]
+    pub match_chip_id: bool,
+    #[deku(bits = "1")]
+    pub match_psid: bool,
+    #[deku(bits = "1")]
+    pub check_user_timestamp: bool,
+    #[deku(bits = "1", pad_bits_after = "26")]
+    pub match_base_guid_mac: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum MccInstruction {
+    LockUpdateHandle = 0x1,
+    ReleaseUpdateHandle = 0x2,
+    UpdateComponent = 0x3,
+    VerifyComponent = 0x4,
+    ActivateComponent = 0x6,
+    Activate = 0x7,
+    Cancel = 0x8,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FsmState {
+    Idle,
+    Locked,
+    Initialize,
+    Download,
+    Verify,
+    Apply,
+    Activate,
+    Unknown(u8),
+}
+
+impl From<u8> for FsmState {
+    fn from(state: u8) -> Self {
+        match state {
+            0 => FsmState::Idle,
+            1 => FsmState::Locked,
+            2 => FsmState::Initialize,
+            3 => FsmState::Download,
+            4 => FsmState::Verify,
+            5 => FsmState::Apply,
+            6 => FsmState::Activate,
+            state => FsmState::Unknown(state),
+        }
+    }
+}
+
+pub const FSM_ERRORS: &[(u8, &str)] = &[
+    (0x1, "error"),
+    (0x2, "digest error"),
+    (0x3, "not applicable"),
+    (0x4, "unknown key"),
+    (0x5, "authentication failed"),
+    (0x6, "unsigned image"),
+    (0x7, "key not applicable"),
+    (0x8, "bad format"),
+    (0x9, "blocked pending reset"),
+    (0xa, "not a secured firmware"),
+    (0xb, "base MAC not listed"),
+    (0xc, "no debug token"),
+    (0xd, "version number mismatch"),
+    (0xe, "user timestamp mismatch"),
+    (0xf, "forbidden version"),
+    (0x10, "flash write protected"),
+    (0x11, "image can not boot from partition"),
+];
+
+pub fn fsm_error_name(error_code: u8) -> Option<&'static str> {
+    FSM_ERRORS
+        .iter()
+        .find(|(code, _)| *code == error_code)
+        .map(|(_, name)| *name)
+}
+
+#[derive(Debug, Default, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big")]
+pub struct MCC {
+    #[deku(pad_bits_before = "4", bits = "12")]
+    pub time_elapsed_since_last_cmd: u16,
+    #[deku(pad_bytes_before = "1")]
+    pub instruction: u8,
+
+    #[deku(pad_bytes_before = "2")]
+    pub component_index: u16,
+
+    #[deku(pad_bits_before = "8", bits = "24")]
+    pub update_handle: u32,
+
+    #[deku(bits = "4")]
+    pub handle_owner_type: u8,
+    #[deku(bits = "4")]
+    pub handle_owner_host_id: u8,
+    #[deku(pad_bits_before = "1", bits = "7")]
+    pub control_progress: u8,
+    pub error_code: u8,
+    #[deku(pad_bits_before = "4", bits = "4")]
+    pub control_state: u8,
+
+    #[deku(pad_bytes_after = "0xc")]
+    pub component_size: u32,
+}
+
+impl MCC {
+    pub fn state(&self) -> FsmState {
+        FsmState::from(self.control_state)
+    }
+}
+
+impl Register for MCC {
+    const REGISTER_ID: u16 = 0x9062;
+
+    fn size(&self) -> usize {
+        0x20
+    }
+}
+
+#[derive(Debug, Default, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big")]
+pub struct MCDA {
+    #[deku(pad_bits_before = "8", bits = "24")]
+    pub update_handle: u32,
+
+    pub offset: u32,
+
+    #[deku(pad_bytes_before = "2", pad_bytes_after = "4")]
+    pub size: u16,
+
+    #[deku(bits_read = "deku::rest.len()")]
+    pub data: Vec<u8>,
+}
+
+impl Register for MCDA {
+    const REGISTER_ID: u16 = 0x9063;
+
+    fn size(&self) -> usize {
+        0x10 + self.data.len()
+    }
+}
@@ -0,0 +1,65 @@
+use deku::{DekuRead, DekuWrite};
+use deku::prelude::*;
+
+use super::Register;
+
+pub const MCIA_MAX_DATA: usize = 0x30;
+
+pub const MCIA_STATUSES: &[(u8, &str)] = &[
+    (0x0, "good"),
+    (0x1, "no EEPROM module"),
+    (0x2, "module not supported"),
+    (0x3, "module not connected"),
+    (0x4, "module type invalid"),
+    (0x9, "I2C error"),
+    (0x10, "module disabled"),
+];
+
+pub fn mcia_status_name(status: u8) -> Option<&'static str> {
+    MCIA_STATUSES
+        .iter()
+        .find(|(code, _)| *code == status)
+        .map(|(_, name)| *name)
+}
+
+#[derive(Debug, Default, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big")]
+pub struct MCIA {
+    #[deku(bits = "1", pad_bits_after = "7")]
+    pub l: bool,
+    pub module: u8,
+    #[deku(pad_bytes_before = "1")]
+    pub status: u8,
+
+    pub i2c_device_address: u8,
+    pub page_number: u8,
+    pub device_address: u16,
+
+    #[deku(pad_bytes_before = "2", pad_bytes_after = "4")]
+    pub size: u16,
+
+    #[deku(bits_read = "deku::rest.len()")]
+    pub data: Vec<u8>,
+}
+
+impl MCIA {
+    pub fn new(module: u8, i2c_device_address: u8, page_number: u8, device_address: u16, size: u16) -> Self {
+        Self {
+            module,
+            i2c_device_address,
+            page_number,
+            device_address,
+            size,
+            data: vec![0; MCIA_MAX_DATA],
+            ..Default::default()
+        }
+    }
+}
+
+impl Register for MCIA {
+    const REGISTER_ID: u16 = 0x9014;
+
+    fn size(&self) -> usize {
+        0x10 + self.data.len()
+    }
+}
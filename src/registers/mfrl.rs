@@ -0,0 +1,71 @@
+use deku::{DekuRead, DekuWrite};
+use deku::prelude::*;
+
+use super::Register;
+
+// reset_level bits
+pub const MFRL_RESET_LEVEL0: u8 = 1 << 0;
+pub const MFRL_RESET_LEVEL3: u8 = 1 << 3;
+
+// reset_type bits, rst_type_sel picks one of them by bit index
+pub const MFRL_RESET_TYPE_FULL_CHIP: u8 = 0;
+pub const MFRL_RESET_TYPE_NET_PORT_ALIVE: u8 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResetState {
+    Idle,
+    InNegotiation,
+    InProgress,
+    NegotiationTimeout,
+    Nack,
+    UnloadTimeout,
+    Unknown(u8),
+}
+
+impl From<u8> for ResetState {
+    fn from(state: u8) -> Self {
+        match state {
+            0 => ResetState::Idle,
+            1 => ResetState::InNegotiation,
+            2 => ResetState::InProgress,
+            3 => ResetState::NegotiationTimeout,
+            4 => ResetState::Nack,
+            5 => ResetState::UnloadTimeout,
+            state => ResetState::Unknown(state),
+        }
+    }
+}
+
+#[derive(Debug, Default, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big")]
+pub struct MFRL {
+    #[deku(pad_bits_before = "34", bits = "1")]
+    pub pci_sync_for_fw_update_start: bool,
+    #[deku(bits = "2")]
+    pub pci_sync_for_fw_update_resp: u8,
+    #[deku(bits = "3")]
+    pub rst_type_sel: u8,
+    #[deku(pad_bits_before = "4", bits = "4")]
+    pub reset_state: u8,
+    pub reset_type: u8,
+    pub reset_level: u8,
+}
+
+impl MFRL {
+    pub fn state(&self) -> ResetState {
+        ResetState::from(self.reset_state)
+    }
+
+    pub fn supports_type(&self, rst_type_sel: u8) -> bool {
+        // Full chip reset predates the reset_type capability field
+        rst_type_sel == MFRL_RESET_TYPE_FULL_CHIP || self.reset_type & (1 << rst_type_sel) != 0
+    }
+}
+
+impl Register for MFRL {
+    const REGISTER_ID: u16 = 0x9028;
+
+    fn size(&self) -> usize {
+        0x8
+    }
+}
@@ -0,0 +1,119 @@
+use deku::{DekuRead, DekuWrite};
+use deku::prelude::*;
+
+use super::Register;
+
+#[derive(Debug, Default, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big")]
+pub struct MGIR {
+    // hardware_info
+    pub device_hw_revision: u16,
+    pub device_id: u16,
+
+    #[deku(pad_bits_before = "27", bits = "5")]
+    pub pvs: u8,
+
+    #[deku(pad_bytes_before = "2")]
+    pub hw_dev_id: u16,
+
+    #[deku(pad_bytes_before = "0x10")]
+    pub uptime: u32,
+
+    // fw_info, string_tlv and latency_tlv are in the top bits
+    #[deku(pad_bits_before = "4", bits = "1")]
+    pub dev: bool,
+    #[deku(bits = "1")]
+    pub debug: bool,
+    #[deku(bits = "1")]
+    pub signed_fw: bool,
+    #[deku(bits = "1")]
+    pub secured: bool,
+    pub major: u8,
+    pub minor: u8,
+    pub sub_minor: u8,
+
+    pub build_id: u32,
+
+    // Build date and time are BCD encoded
+    pub day: u8,
+    pub month: u8,
+    pub year: u16,
+
+    pub seconds: u8,
+    pub minutes: u8,
+    #[deku(pad_bytes_after = "1")]
+    pub hour: u8,
+
+    pub psid: [u8; 16],
+
+    pub ini_file_version: u32,
+    pub extended_major: u32,
+    pub extended_minor: u32,
+    #[deku(pad_bytes_after = "0x10")]
+    pub extended_sub_minor: u32,
+
+    // sw_info is skipped, dev_info starts at 0x80
+    #[deku(pad_bytes_before = "0x24")]
+    pub dev_branch_tag: [u8; 28],
+}
+
+impl MGIR {
+    pub fn fw_version(&self) -> String {
+        if self.extended_major != 0 || self.extended_minor != 0 || self.extended_sub_minor != 0 {
+            format!("{}.{}.{}", self.extended_major, self.extended_minor, self.extended_sub_minor)
+        } else {
+            format!("{}.{}.{}", self.major, self.minor, self.sub_minor)
+        }
+    }
+
+    pub fn build_time(&self) -> String {
+        format!(
+            "{:04x}-{:02x}-{:02x} {:02x}:{:02x}:{:02x}",
+            self.year, self.month, self.day, self.hour, self.minutes, self.seconds
+        )
+    }
+
+    pub fn psid(&self) -> String {
+        c_string(&self.psid)
+    }
+
+    pub fn dev_branch_tag(&self) -> String {
+        c_string(&self.dev_branch_tag)
+    }
+}
+
+// NUL padded ASCII field
+pub fn c_string(bytes: &[u8]) -> String {
+    let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..end]).trim().to_string()
+}
+
+impl Register for MGIR {
+    const REGISTER_ID: u16 = 0x9020;
+
+    fn size(&self) -> usize {
+        0xa0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mgir() {
+        let mut data = [0u8; 0xa0];
+        data[0x20..0x24].copy_from_slice(&[0x39, 0x10, 0x23, 0x05]);
+        data[0x24..0x28].copy_from_slice(&[0x00, 0x00, 0x12, 0x34]);
+        data[0x28..0x30].copy_from_slice(&[0x18, 0x10, 0x20, 0x24, 0x56, 0x34, 0x12, 0x00]);
+        data[0x30..0x3d].copy_from_slice(b"MT_0000000001");
+
+        let (_, mgir) = MGIR::from_bytes((&data, 0)).unwrap();
+        assert!(mgir.dev && mgir.secured);
+        assert!(!mgir.debug && !mgir.signed_fw);
+        assert_eq!(mgir.fw_version(), "16.35.5");
+        assert_eq!(mgir.build_id, 0x1234);
+        assert_eq!(mgir.build_time(), "2024-10-18 12:34:56");
+        assert_eq!(mgir.psid(), "MT_0000000001");
+    }
+}
@@ -31,14 +31,14 @@ pub struct MtrcCapReg {
     #[deku(pad_bytes_before = "5")]
     pub log_max_trace_buffer_size: u8,
 
-    #[deku(pad_bytes_before = "4")]
+    #[deku(pad_bytes_before = "4", pad_bytes_after = "0x30")]
     pub string_db_param: [StringDbParam; 8],
 }
 
 impl Register for MtrcCapReg {
     const REGISTER_ID: u16 = 0x9040;
     fn size(&self) -> usize {
-        80
+        0x80
     }
 }
 
@@ -83,13 +83,14 @@ pub struct MtrcConfReg {
     #[deku(pad_bits_before = "24", bits = "8")]
     pub log_trace_buffer_size: u8,
 
+    #[deku(pad_bytes_after = "0x74")]
     pub trace_mkey: u32,
 }
 
 impl Register for MtrcConfReg {
     const REGISTER_ID: u16 = 0x9041;
     fn size(&self) -> usize {
-        4 + 4 + 4
+        0x80
     }
 }
 
@@ -107,12 +108,13 @@ pub struct MtrcCtrlReg {
 
     #[deku(pad_bytes_before = "4")]
     pub timestamp_hi: u32,
+    #[deku(pad_bytes_after = "0x30")]
     pub timestamp_lo: u32,
 }
 
 impl Register for MtrcCtrlReg {
     const REGISTER_ID: u16 = 0x9043;
     fn size(&self) -> usize {
-        4 + 4 + 4
+        0x40
     }
 }
\ No newline at end of file
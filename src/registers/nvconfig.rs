@@ -0,0 +1,143 @@
+use deku::ctx::Endian;
+use deku::{DekuRead, DekuWrite};
+use deku::prelude::*;
+
+use super::Register;
+
+pub const ACCESS_MODE_NEXT: u8 = 0;
+pub const ACCESS_MODE_CURRENT: u8 = 1;
+pub const ACCESS_MODE_DEFAULT: u8 = 2;
+
+pub const TYPE_CLASS_GLOBAL: u8 = 0;
+pub const TYPE_CLASS_PHYSICAL_PORT: u8 = 1;
+pub const TYPE_CLASS_PER_HOST_PF: u8 = 3;
+
+pub const MAX_CONFIG_ITEM_DATA: usize = 0x100;
+
+// Configuration item type, the layout below type_class depends on the class
+pub fn config_item_type(type_class: u8, port: u8, parameter_index: u32) -> u32 {
+    match type_class {
+        TYPE_CLASS_PHYSICAL_PORT => (type_class as u32) << 24 | (port as u32) << 16 | (parameter_index & 0xffff),
+        _ => (type_class as u32) << 24 | (parameter_index & 0xffffff),
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "ctx_endian", ctx = "ctx_endian: Endian")]
+pub struct ConfigItemHeader {
+    #[deku(bits = "2")]
+    pub valid: u8,
+    #[deku(bits = "2")]
+    pub priority: u8,
+    #[deku(bits = "2")]
+    pub header_type: u8,
+    #[deku(bits = "1")]
+    pub ovr_en: bool,
+    #[deku(bits = "1")]
+    pub rd_en: bool,
+    #[deku(bits = "2", pad_bits_after = "1")]
+    pub access_mode: u8,
+    #[deku(bits = "5")]
+    pub writer_id: u8,
+    #[deku(bits = "4", pad_bits_after = "2")]
+    pub version: u8,
+    #[deku(bits = "1")]
+    pub host_id_valid: bool,
+    #[deku(bits = "9")]
+    pub length: u16,
+
+    pub item_type: u32,
+
+    #[deku(pad_bytes_before = "2")]
+    pub crc16: u16,
+}
+
+#[derive(Debug, Default, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big")]
+pub struct MNVDA {
+    pub header: ConfigItemHeader,
+
+    #[deku(bits_read = "deku::rest.len()")]
+    pub data: Vec<u8>,
+}
+
+impl MNVDA {
+    pub fn new(item_type: u32, access_mode: u8, data: Vec<u8>) -> Self {
+        Self {
+            header: ConfigItemHeader {
+                access_mode,
+                length: data.len() as u16,
+                item_type,
+                ..Default::default()
+            },
+            data,
+        }
+    }
+}
+
+impl Register for MNVDA {
+    const REGISTER_ID: u16 = 0x9024;
+
+    fn size(&self) -> usize {
+        0xc + self.data.len()
+    }
+}
+
+#[derive(Debug, Default, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big")]
+pub struct MNVIA {
+    #[deku(pad_bits_before = "23", bits = "5")]
+    pub writer_id: u8,
+    #[deku(pad_bits_before = "1", bits = "3", pad_bytes_after = "4")]
+    pub target: u8,
+}
+
+impl Register for MNVIA {
+    const REGISTER_ID: u16 = 0x9029;
+
+    fn size(&self) -> usize {
+        0x8
+    }
+}
+
+#[derive(Debug, Default, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big")]
+pub struct MNVQC {
+    pub item_type: u32,
+
+    #[deku(pad_bits_before = "24", bits = "4")]
+    pub version: u8,
+    #[deku(pad_bits_before = "2", bits = "1")]
+    pub support_wr: bool,
+    #[deku(bits = "1")]
+    pub support_rd: bool,
+}
+
+impl Register for MNVQC {
+    const REGISTER_ID: u16 = 0x9030;
+
+    fn size(&self) -> usize {
+        0x8
+    }
+}
+
+// Walks the stored configuration items, each read returns the pointer to the next one
+#[derive(Debug, Default, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big")]
+pub struct MNVGN {
+    #[deku(pad_bytes_after = "0xc")]
+    pub nv_pointer: u32,
+
+    pub header: ConfigItemHeader,
+
+    #[deku(bits_read = "deku::rest.len()")]
+    pub data: Vec<u8>,
+}
+
+impl Register for MNVGN {
+    const REGISTER_ID: u16 = 0x9035;
+
+    fn size(&self) -> usize {
+        0x1c + self.data.len()
+    }
+}
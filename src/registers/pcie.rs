@@ -0,0 +1,98 @@
+use deku::{DekuRead, DekuWrite};
+use deku::prelude::*;
+
+use super::Register;
+
+pub const MPCNT_COUNTER_SET_SIZE: usize = 0xf8;
+
+pub const MPCNT_GROUP_PERFORMANCE: u8 = 0x0;
+pub const MPCNT_GROUP_TIMERS_AND_STATES: u8 = 0x2;
+
+#[derive(Debug, Default, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big")]
+pub struct MPEIN {
+    #[deku(pad_bits_before = "2", bits = "6")]
+    pub depth: u8,
+    pub pcie_index: u8,
+    #[deku(pad_bytes_after = "1")]
+    pub node: u8,
+
+    pub capability_mask: u32,
+
+    #[deku(pad_bytes_before = "1")]
+    pub link_width_enabled: u8,
+    pub link_speed_enabled: u16,
+
+    pub lane0_physical_position: u8,
+    pub link_width_active: u8,
+    pub link_speed_active: u16,
+
+    pub num_of_pfs: u16,
+    pub num_of_vfs: u16,
+
+    #[deku(pad_bytes_after = "2")]
+    pub bdf0: u16,
+
+    #[deku(bits = "4")]
+    pub max_read_request_size: u8,
+    #[deku(bits = "4")]
+    pub max_payload_size: u8,
+    #[deku(pad_bits_before = "5", bits = "3")]
+    pub pwr_status: u8,
+    #[deku(bits = "4")]
+    pub port_type: u8,
+    #[deku(pad_bits_before = "11", bits = "1")]
+    pub lane_reversal: bool,
+
+    #[deku(pad_bits_before = "20", bits = "12", pad_bytes_after = "4")]
+    pub pci_power: u16,
+
+    pub device_status: u16,
+    #[deku(pad_bytes_after = "1")]
+    pub port_state: u8,
+
+    #[deku(pad_bytes_before = "2", pad_bytes_after = "4")]
+    pub receiver_detect_result: u16,
+}
+
+impl Register for MPEIN {
+    const REGISTER_ID: u16 = 0x9050;
+
+    fn size(&self) -> usize {
+        0x30
+    }
+}
+
+#[derive(Debug, Default, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big")]
+pub struct MPCNT {
+    #[deku(pad_bytes_before = "1")]
+    pub pcie_index: u8,
+    #[deku(pad_bits_before = "10", bits = "6")]
+    pub grp: u8,
+
+    #[deku(bits = "1", pad_bits_after = "31")]
+    pub clr: bool,
+
+    #[deku(bits_read = "deku::rest.len()")]
+    pub counter_set: Vec<u8>,
+}
+
+impl MPCNT {
+    pub fn new(pcie_index: u8, grp: u8) -> Self {
+        Self {
+            pcie_index,
+            grp,
+            counter_set: vec![0; MPCNT_COUNTER_SET_SIZE],
+            ..Default::default()
+        }
+    }
+}
+
+impl Register for MPCNT {
+    const REGISTER_ID: u16 = 0x9051;
+
+    fn size(&self) -> usize {
+        0x8 + self.counter_set.len()
+    }
+}
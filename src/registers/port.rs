@@ -0,0 +1,197 @@
+use deku::{DekuRead, DekuWrite};
+use deku::prelude::*;
+
+use super::Register;
+
+pub const PTYS_PROTO_IB: u8 = 0x1;
+pub const PTYS_PROTO_ETH: u8 = 0x4;
+
+pub const PPCNT_COUNTER_SET_SIZE: usize = 0xf8;
+pub const PDDR_PAGE_DATA_SIZE: usize = 0xf8;
+
+pub const PDDR_PAGE_OPERATIONAL_INFO: u8 = 0x0;
+pub const PDDR_PAGE_TROUBLESHOOTING_INFO: u8 = 0x1;
+pub const PDDR_PAGE_MODULE_INFO: u8 = 0x3;
+pub const PDDR_PAGE_LINK_DOWN_INFO: u8 = 0x6;
+
+#[derive(Debug, Default, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big")]
+pub struct PAOS {
+    pub swid: u8,
+    pub local_port: u8,
+    #[deku(bits = "2")]
+    pub pnat: u8,
+    #[deku(bits = "2")]
+    pub lp_msb: u8,
+    #[deku(bits = "4")]
+    pub admin_status: u8,
+    #[deku(pad_bits_before = "4", bits = "4")]
+    pub oper_status: u8,
+
+    #[deku(bits = "1")]
+    pub ase: bool,
+    #[deku(bits = "1")]
+    pub ee: bool,
+    #[deku(pad_bits_before = "28", bits = "2", pad_bytes_after = "8")]
+    pub e: u8,
+}
+
+impl Register for PAOS {
+    const REGISTER_ID: u16 = 0x5006;
+
+    fn size(&self) -> usize {
+        0x10
+    }
+}
+
+#[derive(Debug, Default, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big")]
+pub struct PTYS {
+    #[deku(pad_bits_before = "1", bits = "1")]
+    pub an_disable_admin: bool,
+    #[deku(bits = "1", pad_bits_after = "5")]
+    pub an_disable_cap: bool,
+    pub local_port: u8,
+    #[deku(pad_bits_before = "13", bits = "3")]
+    pub proto_mask: u8,
+
+    #[deku(bits = "4", pad_bits_after = "12")]
+    pub an_status: u8,
+    pub data_rate_oper: u16,
+
+    pub ext_eth_proto_capability: u32,
+    pub eth_proto_capability: u32,
+    pub ib_link_width_capability: u16,
+    pub ib_proto_capability: u16,
+
+    pub ext_eth_proto_admin: u32,
+    pub eth_proto_admin: u32,
+    pub ib_link_width_admin: u16,
+    pub ib_proto_admin: u16,
+
+    pub ext_eth_proto_oper: u32,
+    pub eth_proto_oper: u32,
+    pub ib_link_width_oper: u16,
+    pub ib_proto_oper: u16,
+
+    #[deku(pad_bits_before = "28", bits = "4")]
+    pub connector_type: u8,
+    #[deku(pad_bytes_after = "0xc")]
+    pub eth_proto_lp_advertise: u32,
+}
+
+impl Register for PTYS {
+    const REGISTER_ID: u16 = 0x5004;
+
+    fn size(&self) -> usize {
+        0x40
+    }
+}
+
+#[derive(Debug, Default, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big")]
+pub struct PMTU {
+    #[deku(pad_bytes_before = "1", pad_bytes_after = "2")]
+    pub local_port: u8,
+
+    #[deku(pad_bytes_after = "2")]
+    pub max_mtu: u16,
+    #[deku(pad_bytes_after = "2")]
+    pub admin_mtu: u16,
+    #[deku(pad_bytes_after = "2")]
+    pub oper_mtu: u16,
+}
+
+impl Register for PMTU {
+    const REGISTER_ID: u16 = 0x5003;
+
+    fn size(&self) -> usize {
+        0x10
+    }
+}
+
+#[derive(Debug, Default, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big")]
+pub struct PPCNT {
+    pub swid: u8,
+    pub local_port: u8,
+    #[deku(bits = "2")]
+    pub pnat: u8,
+    #[deku(pad_bits_before = "8", bits = "6")]
+    pub grp: u8,
+
+    #[deku(bits = "1")]
+    pub clr: bool,
+    #[deku(pad_bits_before = "28", bits = "3")]
+    pub prio_tc: u8,
+
+    #[deku(bits_read = "deku::rest.len()")]
+    pub counter_set: Vec<u8>,
+}
+
+impl PPCNT {
+    pub fn new(local_port: u8, grp: u8, prio_tc: u8) -> Self {
+        Self {
+            local_port,
+            grp,
+            prio_tc,
+            counter_set: vec![0; PPCNT_COUNTER_SET_SIZE],
+            ..Default::default()
+        }
+    }
+}
+
+impl Register for PPCNT {
+    const REGISTER_ID: u16 = 0x5008;
+
+    fn size(&self) -> usize {
+        0x8 + self.counter_set.len()
+    }
+}
+
+#[derive(Debug, Default, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big")]
+pub struct PDDR {
+    #[deku(pad_bytes_before = "1")]
+    pub local_port: u8,
+    #[deku(bits = "2", pad_bits_after = "14")]
+    pub pnat: u8,
+
+    #[deku(pad_bytes_before = "3")]
+    pub page_select: u8,
+
+    #[deku(bits_read = "deku::rest.len()")]
+    pub page_data: Vec<u8>,
+}
+
+impl PDDR {
+    pub fn new(local_port: u8, page_select: u8) -> Self {
+        Self {
+            local_port,
+            page_select,
+            page_data: vec![0; PDDR_PAGE_DATA_SIZE],
+            ..Default::default()
+        }
+    }
+}
+
+impl Register for PDDR {
+    const REGISTER_ID: u16 = 0x5031;
+
+    fn size(&self) -> usize {
+        0x8 + self.page_data.len()
+    }
+}
+
+// PDDR page data for PDDR_PAGE_TROUBLESHOOTING_INFO
+#[derive(Debug, Default, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big")]
+pub struct PddrTroubleshootingPage {
+    #[deku(pad_bytes_before = "2")]
+    pub group_opcode: u16,
+    #[deku(pad_bytes_before = "2")]
+    pub monitor_opcode: u16,
+
+    #[deku(pad_bytes_before = "4", bits_read = "deku::rest.len()")]
+    pub status_message: Vec<u8>,
+}
@@ -0,0 +1,131 @@
+use deku::{DekuRead, DekuWrite};
+use deku::prelude::*;
+
+use super::mgir::c_string;
+use super::Register;
+
+// The ASIC sensor is always present, sensor_map only lists the additional ones
+pub const ASIC_SENSOR: u16 = 0;
+// Module sensors are indexed from here by module number
+pub const MODULE_SENSOR_BASE: u16 = 64;
+
+const TEMPERATURE_UNIT: f64 = 0.125;
+
+#[derive(Debug, Default, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big")]
+pub struct MTCAP {
+    #[deku(pad_bits_before = "25", bits = "7", pad_bytes_after = "4")]
+    pub sensor_count: u8,
+
+    pub sensor_map: u64,
+}
+
+impl MTCAP {
+    pub fn sensors(&self) -> Vec<u16> {
+        let mut sensors = vec![ASIC_SENSOR];
+        sensors.extend((1..64).filter(|bit| self.sensor_map & (1 << bit) != 0));
+        sensors
+    }
+}
+
+impl Register for MTCAP {
+    const REGISTER_ID: u16 = 0x9009;
+
+    fn size(&self) -> usize {
+        0x10
+    }
+}
+
+#[derive(Debug, Default, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big")]
+pub struct MTMP {
+    #[deku(bits = "1")]
+    pub i: bool,
+    #[deku(pad_bits_before = "19", bits = "12")]
+    pub sensor_index: u16,
+
+    #[deku(pad_bytes_before = "2")]
+    pub temperature: i16,
+
+    #[deku(bits = "1")]
+    pub mte: bool,
+    #[deku(bits = "1", pad_bits_after = "14")]
+    pub mtr: bool,
+    pub max_temperature: i16,
+
+    #[deku(bits = "2", pad_bits_after = "14")]
+    pub tee: u8,
+    pub temp_threshold_hi: i16,
+
+    #[deku(pad_bytes_before = "2", pad_bytes_after = "4")]
+    pub temp_threshold_lo: i16,
+
+    pub sensor_name: [u8; 8],
+}
+
+impl MTMP {
+    pub fn temperature_celsius(&self) -> f64 {
+        self.temperature as f64 * TEMPERATURE_UNIT
+    }
+
+    pub fn max_temperature_celsius(&self) -> f64 {
+        self.max_temperature as f64 * TEMPERATURE_UNIT
+    }
+
+    pub fn threshold_hi_celsius(&self) -> f64 {
+        self.temp_threshold_hi as f64 * TEMPERATURE_UNIT
+    }
+
+    pub fn threshold_lo_celsius(&self) -> f64 {
+        self.temp_threshold_lo as f64 * TEMPERATURE_UNIT
+    }
+
+    pub fn name(&self) -> String {
+        c_string(&self.sensor_name)
+    }
+}
+
+impl Register for MTMP {
+    const REGISTER_ID: u16 = 0x900a;
+
+    fn size(&self) -> usize {
+        0x20
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mtcap() {
+        let data = [0, 0, 0, 0x03, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x06];
+        let (_, mtcap) = MTCAP::from_bytes((&data, 0)).unwrap();
+        assert_eq!(mtcap.sensor_count, 3);
+        assert_eq!(mtcap.sensors(), [0, 1, 2]);
+    }
+
+    #[test]
+    fn test_mtmp() {
+        #[rustfmt::skip]
+        let data = [
+            0x00, 0x00, 0x00, 0x40, 0x00, 0x00, 0x01, 0x90, 0xc0, 0x00, 0x01, 0xe0, 0x40, 0x00, 0x02, 0x80,
+            0x00, 0x00, 0x02, 0x30, 0x00, 0x00, 0x00, 0x00, b'm', b'o', b'd', b'u', b'l', b'e', b'0', 0x00,
+        ];
+        let (_, mtmp) = MTMP::from_bytes((&data, 0)).unwrap();
+        assert_eq!(mtmp.sensor_index, 64);
+        assert_eq!(mtmp.temperature_celsius(), 50.0);
+        assert!(mtmp.mte && mtmp.mtr);
+        assert_eq!(mtmp.max_temperature_celsius(), 60.0);
+        assert_eq!(mtmp.tee, 1);
+        assert_eq!(mtmp.threshold_hi_celsius(), 80.0);
+        assert_eq!(mtmp.threshold_lo_celsius(), 70.0);
+        assert_eq!(mtmp.name(), "module0");
+        assert_eq!(mtmp.to_bytes().unwrap(), data);
+
+        let mut data = data;
+        data[0x06..0x08].copy_from_slice(&[0xff, 0xf0]);
+        let (_, mtmp) = MTMP::from_bytes((&data, 0)).unwrap();
+        assert_eq!(mtmp.temperature_celsius(), -2.0);
+    }
+}
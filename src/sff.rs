@@ -0,0 +1,363 @@
+use serde::Serialize;
+
+use crate::cmdif::CmdIf;
+use crate::error::{Error, Result};
+use crate::registers::mcia::{MCIA, MCIA_MAX_DATA};
+
+// Lower memory and diagnostics of SFF-8472 modules live at i2c addresses A0h and A2h
+pub const I2C_ADDRESS_LOW: u8 = 0x50;
+pub const I2C_ADDRESS_HIGH: u8 = 0x51;
+
+pub const EEPROM_PAGE_SIZE: usize = 0x100;
+const UPPER_PAGE_OFFSET: usize = 0x80;
+
+const SFF_8472_DDM_IMPLEMENTED: u8 = 1 << 6;
+const SFF_8472_EXTERNALLY_CALIBRATED: u8 = 1 << 4;
+const CMIS_FLAT_MEMORY: u8 = 1 << 7;
+const CMIS_LANE_PAGE: u8 = 0x11;
+const CMIS_LANES: usize = 8;
+const SFF_8636_LANES: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum EepromLayout {
+    Sff8472,
+    Sff8636,
+    Cmis,
+}
+
+pub const IDENTIFIERS: &[(u8, &str, Option<EepromLayout>)] = &[
+    (0x00, "Unknown", None),
+    (0x01, "GBIC", None),
+    (0x02, "Soldered", None),
+    (0x03, "SFP/SFP+/SFP28", Some(EepromLayout::Sff8472)),
+    (0x0c, "QSFP", Some(EepromLayout::Sff8636)),
+    (0x0d, "QSFP+", Some(EepromLayout::Sff8636)),
+    (0x11, "QSFP28", Some(EepromLayout::Sff8636)),
+    (0x18, "QSFP-DD", Some(EepromLayout::Cmis)),
+    (0x19, "OSFP", Some(EepromLayout::Cmis)),
+    (0x1a, "SFP-DD", Some(EepromLayout::Cmis)),
+    (0x1b, "DSFP", Some(EepromLayout::Cmis)),
+    (0x1e, "QSFP+ (CMIS)", Some(EepromLayout::Cmis)),
+];
+
+pub fn identifier_name(identifier: u8) -> &'static str {
+    IDENTIFIERS
+        .iter()
+        .find(|(id, _, _)| *id == identifier)
+        .map_or("Reserved", |(_, name, _)| name)
+}
+
+pub fn eeprom_layout(identifier: u8) -> Option<EepromLayout> {
+    IDENTIFIERS.iter().find(|(id, _, _)| *id == identifier)?.2
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Diagnostics {
+    // Degrees Celsius and Volts
+    pub temperature: f64,
+    pub vcc: f64,
+    // Per lane, mA and mW
+    pub tx_bias: Vec<f64>,
+    pub tx_power: Vec<f64>,
+    pub rx_power: Vec<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ModuleInfo {
+    pub identifier: u8,
+    pub identifier_name: &'static str,
+    pub layout: Option<EepromLayout>,
+    pub vendor_name: String,
+    pub vendor_oui: String,
+    pub part_number: String,
+    pub revision: String,
+    pub serial_number: String,
+    pub date_code: String,
+    pub diagnostics: Option<Diagnostics>,
+}
+
+impl ModuleInfo {
+    fn new(identifier: u8) -> Self {
+        Self {
+            identifier,
+            identifier_name: identifier_name(identifier),
+            layout: eeprom_layout(identifier),
+            vendor_name: String::new(),
+            vendor_oui: String::new(),
+            part_number: String::new(),
+            revision: String::new(),
+            serial_number: String::new(),
+            date_code: String::new(),
+            diagnostics: None,
+        }
+    }
+
+    // The part number is followed by the revision in all layouts
+    fn with_vendor_fields(mut self, page: &[u8], vendor: usize, oui: usize, part: usize, rev_len: usize) -> Self {
+        self.vendor_name = ascii(field(page, vendor, 16));
+        self.vendor_oui = field(page, oui, 3)
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect::<Vec<_>>()
+            .join(":");
+        self.part_number = ascii(field(page, part, 16));
+        self.revision = ascii(field(page, part + 16, rev_len));
+        self
+    }
+}
+
+fn identifier(page: &[u8]) -> u8 {
+    page.first().copied().unwrap_or_default()
+}
+
+fn field(data: &[u8], offset: usize, len: usize) -> &[u8] {
+    data.get(offset..offset + len).unwrap_or(&[])
+}
+
+fn be16(data: &[u8], offset: usize) -> u16 {
+    match field(data, offset, 2) {
+        [high, low] => u16::from_be_bytes([*high, *low]),
+        _ => 0,
+    }
+}
+
+fn ascii(data: &[u8]) -> String {
+    String::from_utf8_lossy(data)
+        .trim_end_matches(|c: char| c == ' ' || c == '\0')
+        .to_string()
+}
+
+fn temperature(data: &[u8], offset: usize) -> f64 {
+    be16(data, offset) as i16 as f64 / 256.0
+}
+
+fn vcc(data: &[u8], offset: usize) -> f64 {
+    be16(data, offset) as f64 * 100e-6
+}
+
+// Bias in 2 uA units, power in 0.1 uW units
+fn lanes(data: &[u8], offset: usize, count: usize, scale: f64) -> Vec<f64> {
+    (0..count).map(|lane| be16(data, offset + 2 * lane) as f64 * scale).collect()
+}
+
+const BIAS_SCALE: f64 = 2e-3;
+const POWER_SCALE: f64 = 1e-4;
+
+// A0h page and, if the module implements digital diagnostics, the A2h page
+pub fn decode_sff8472(a0: &[u8], a2: Option<&[u8]>) -> ModuleInfo {
+    let mut info = ModuleInfo::new(identifier(a0)).with_vendor_fields(a0, 20, 37, 40, 4);
+    info.serial_number = ascii(field(a0, 68, 16));
+    info.date_code = ascii(field(a0, 84, 8));
+
+    let diagnostic_type = a0.get(92).copied().unwrap_or_default();
+    if diagnostic_type & SFF_8472_EXTERNALLY_CALIBRATED != 0 {
+        log::warn!("Module uses external calibration, diagnostics are uncalibrated");
+    }
+    if let Some(a2) = a2.filter(|_| diagnostic_type & SFF_8472_DDM_IMPLEMENTED != 0) {
+        info.diagnostics = Some(Diagnostics {
+            temperature: temperature(a2, 96),
+            vcc: vcc(a2, 98),
+            tx_bias: lanes(a2, 100, 1, BIAS_SCALE),
+            tx_power: lanes(a2, 102, 1, POWER_SCALE),
+            rx_power: lanes(a2, 104, 1, POWER_SCALE),
+        });
+    }
+    info
+}
+
+// Lower memory followed by upper page 00h
+pub fn decode_sff8636(page0: &[u8]) -> ModuleInfo {
+    let mut info = ModuleInfo::new(identifier(page0)).with_vendor_fields(page0, 148, 165, 168, 2);
+    info.serial_number = ascii(field(page0, 196, 16));
+    info.date_code = ascii(field(page0, 212, 8));
+    info.diagnostics = Some(Diagnostics {
+        temperature: temperature(page0, 22),
+        vcc: vcc(page0, 26),
+        rx_power: lanes(page0, 34, SFF_8636_LANES, POWER_SCALE),
+        tx_bias: lanes(page0, 42, SFF_8636_LANES, BIAS_SCALE),
+        tx_power: lanes(page0, 50, SFF_8636_LANES, POWER_SCALE),
+    });
+    info
+}
+
+// Lower memory followed by upper page 00h, lane monitors come from upper page 11h
+pub fn decode_cmis(page0: &[u8], page11: Option<&[u8]>) -> ModuleInfo {
+    let mut info = ModuleInfo::new(identifier(page0)).with_vendor_fields(page0, 129, 145, 148, 2);
+    info.serial_number = ascii(field(page0, 166, 16));
+    info.date_code = ascii(field(page0, 182, 8));
+
+    let mut diagnostics = Diagnostics {
+        temperature: temperature(page0, 14),
+        vcc: vcc(page0, 16),
+        ..Default::default()
+    };
+    if let Some(page11) = page11 {
+        diagnostics.tx_power = lanes(page11, 154, CMIS_LANES, POWER_SCALE);
+        diagnostics.tx_bias = lanes(page11, 170, CMIS_LANES, BIAS_SCALE);
+        diagnostics.rx_power = lanes(page11, 186, CMIS_LANES, POWER_SCALE);
+    }
+    info.diagnostics = Some(diagnostics);
+    info
+}
+
+pub fn decode(page0: &[u8], diagnostics: Option<&[u8]>) -> ModuleInfo {
+    match eeprom_layout(identifier(page0)) {
+        Some(EepromLayout::Sff8472) => decode_sff8472(page0, diagnostics),
+        Some(EepromLayout::Sff8636) => decode_sff8636(page0),
+        Some(EepromLayout::Cmis) => decode_cmis(page0, diagnostics),
+        None => ModuleInfo::new(identifier(page0)),
+    }
+}
+
+fn check_status(mcia: &MCIA) -> Result<()> {
+    match mcia.status {
+        0 => Ok(()),
+        status => Err(Error::ModuleEeprom {
+            module: mcia.module,
+            status,
+        }),
+    }
+}
+
+// Accesses are split so that none crosses from the lower into the upper half of a page
+fn eeprom_chunks(offset: usize, len: usize) -> impl Iterator<Item = (usize, usize)> {
+    let end = offset + len;
+    let mut offset = offset;
+    std::iter::from_fn(move || {
+        if offset >= end {
+            return None;
+        }
+        let boundary = if offset < UPPER_PAGE_OFFSET { UPPER_PAGE_OFFSET } else { EEPROM_PAGE_SIZE };
+        let chunk = (end - offset).min(MCIA_MAX_DATA).min(boundary.max(offset + 1) - offset);
+        let start = offset;
+        offset += chunk;
+        Some((start, chunk))
+    })
+}
+
+pub fn read_eeprom(
+    cmdif: &impl CmdIf,
+    module: u8,
+    i2c_address: u8,
+    page: u8,
+    offset: usize,
+    len: usize,
+) -> Result<Vec<u8>> {
+    let mut data = Vec::with_capacity(len);
+    for (offset, chunk) in eeprom_chunks(offset, len) {
+        let mcia = cmdif.read_register(MCIA::new(module, i2c_address, page, offset as u16, chunk as u16), 0)?;
+        check_status(&mcia)?;
+        data.extend_from_slice(&mcia.data[..chunk]);
+    }
+    Ok(data)
+}
+
+pub fn write_eeprom(
+    cmdif: &impl CmdIf,
+    module: u8,
+    i2c_address: u8,
+    page: u8,
+    offset: usize,
+    data: &[u8],
+) -> Result<()> {
+    for (start, chunk) in eeprom_chunks(offset, data.len()) {
+        let mut mcia = MCIA::new(module, i2c_address, page, start as u16, chunk as u16);
+        mcia.data[..chunk].copy_from_slice(&data[start - offset..start - offset + chunk]);
+        check_status(&cmdif.write_register(mcia, 0)?)?;
+    }
+    Ok(())
+}
+
+pub fn read_page(cmdif: &impl CmdIf, module: u8, i2c_address: u8, page: u8) -> Result<Vec<u8>> {
+    read_eeprom(cmdif, module, i2c_address, page, 0, EEPROM_PAGE_SIZE)
+}
+
+pub fn module_info(cmdif: &impl CmdIf, module: u8) -> Result<ModuleInfo> {
+    let page0 = read_page(cmdif, module, I2C_ADDRESS_LOW, 0)?;
+    let diagnostics = match eeprom_layout(identifier(&page0)) {
+        Some(EepromLayout::Sff8472) if page0[92] & SFF_8472_DDM_IMPLEMENTED != 0 => {
+            Some(read_page(cmdif, module, I2C_ADDRESS_HIGH, 0)?)
+        }
+        Some(EepromLayout::Cmis) if page0[2] & CMIS_FLAT_MEMORY == 0 => {
+            read_eeprom(cmdif, module, I2C_ADDRESS_LOW, CMIS_LANE_PAGE, UPPER_PAGE_OFFSET, UPPER_PAGE_OFFSET)
+                .map(|upper| [vec![0; UPPER_PAGE_OFFSET], upper].concat())
+                .map_err(|err| log::warn!("Could not read CMIS page {CMIS_LANE_PAGE:#x}: {err}"))
+                .ok()
+        }
+        _ => None,
+    };
+    Ok(decode(&page0, diagnostics.as_deref()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn put(page: &mut [u8], offset: usize, data: &[u8]) {
+        page[offset..offset + data.len()].copy_from_slice(data);
+    }
+
+    #[test]
+    fn test_eeprom_chunks() {
+        let chunks: Vec<_> = eeprom_chunks(0, EEPROM_PAGE_SIZE).collect();
+        assert_eq!(chunks[..3], [(0, 0x30), (0x30, 0x30), (0x60, 0x20)]);
+        assert_eq!(chunks[3], (0x80, 0x30));
+        assert_eq!(chunks.iter().map(|(_, len)| len).sum::<usize>(), EEPROM_PAGE_SIZE);
+        assert_eq!(eeprom_chunks(0x7f, 2).collect::<Vec<_>>(), [(0x7f, 1), (0x80, 1)]);
+    }
+
+    #[test]
+    fn test_sff8472() {
+        let mut a0 = vec![0; EEPROM_PAGE_SIZE];
+        a0[0] = 0x03;
+        put(&mut a0, 20, b"MELLANOX        ");
+        put(&mut a0, 37, &[0x00, 0x02, 0xc9]);
+        put(&mut a0, 40, b"MFM1T02A-SR     ");
+        put(&mut a0, 68, b"MT1234ABCD      ");
+        a0[92] = SFF_8472_DDM_IMPLEMENTED;
+        let mut a2 = vec![0; EEPROM_PAGE_SIZE];
+        put(&mut a2, 96, &[0x19, 0x80, 0x80, 0xe8, 0x0f, 0xa0, 0x13, 0x88, 0x27, 0x10]);
+
+        let info = decode(&a0, Some(&a2));
+        assert_eq!(info.identifier_name, "SFP/SFP+/SFP28");
+        assert_eq!(info.vendor_name, "MELLANOX");
+        assert_eq!(info.vendor_oui, "00:02:c9");
+        assert_eq!(info.part_number, "MFM1T02A-SR");
+        assert_eq!(info.serial_number, "MT1234ABCD");
+        let diagnostics = info.diagnostics.unwrap();
+        assert_eq!(diagnostics.temperature, 25.5);
+        assert!((diagnostics.vcc - 3.3).abs() < 1e-9);
+        assert!((diagnostics.tx_bias[0] - 8.0).abs() < 1e-9);
+        assert!((diagnostics.tx_power[0] - 0.5).abs() < 1e-9);
+        assert!((diagnostics.rx_power[0] - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_sff8636_and_cmis() {
+        let mut page0 = vec![0; EEPROM_PAGE_SIZE];
+        page0[0] = 0x11;
+        put(&mut page0, 22, &[0xff, 0x00]);
+        put(&mut page0, 148, b"Mellanox        ");
+        put(&mut page0, 168, b"MCP1600-C001    ");
+        let info = decode(&page0, None);
+        assert_eq!(info.layout, Some(EepromLayout::Sff8636));
+        assert_eq!(info.part_number, "MCP1600-C001");
+        assert_eq!(info.diagnostics.unwrap().temperature, -1.0);
+
+        let mut page0 = vec![0; EEPROM_PAGE_SIZE];
+        page0[0] = 0x18;
+        put(&mut page0, 14, &[0x20, 0x00]);
+        put(&mut page0, 129, b"NVIDIA          ");
+        put(&mut page0, 166, b"MT2250XZ00001   ");
+        let mut page11 = vec![0; EEPROM_PAGE_SIZE];
+        put(&mut page11, 186, &[0x27, 0x10]);
+        let info = decode(&page0, Some(&page11));
+        assert_eq!(info.identifier_name, "QSFP-DD");
+        assert_eq!(info.vendor_name, "NVIDIA");
+        assert_eq!(info.serial_number, "MT2250XZ00001");
+        let diagnostics = info.diagnostics.unwrap();
+        assert_eq!(diagnostics.temperature, 32.0);
+        assert_eq!(diagnostics.rx_power.len(), CMIS_LANES);
+        assert!((diagnostics.rx_power[0] - 1.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,126 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    cmdif::CmdIf,
+    commands::{QueryFlowCounter, QueryQCounter, QueryVportCounter},
+    error::Result,
+};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CounterSet {
+    pub q_counters: Vec<u8>,
+    pub flow_counters: Vec<u32>,
+    pub vport: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CounterSnapshot {
+    pub values: BTreeMap<String, u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CounterDelta {
+    pub name: String,
+    pub before: u64,
+    pub after: u64,
+}
+
+impl CounterDelta {
+    pub fn delta(&self) -> u64 {
+        self.after.wrapping_sub(self.before)
+    }
+}
+
+impl CounterSnapshot {
+    pub fn take(cmdif: &impl CmdIf, counters: &CounterSet) -> Result<Self> {
+        let mut values = BTreeMap::new();
+
+        for &counter_set_id in counters.q_counters.iter() {
+            let out = cmdif.do_command(QueryQCounter {
+                clear: false,
+                aggregate: false,
+                counter_set_id,
+            })?;
+            for (name, value) in out.named() {
+                values.insert(format!("q_counter[{counter_set_id:#x}].{name}"), value as u64);
+            }
+        }
+
+        for &flow_counter_id in counters.flow_counters.iter() {
+            let out = cmdif.do_command(QueryFlowCounter {
+                clear: false,
+                num_of_counters: 0,
+                flow_counter_id,
+            })?;
+            if let Some(stats) = out.flow_statistics.first() {
+                values.insert(format!("flow_counter[{flow_counter_id:#x}].packets"), stats.packets);
+                values.insert(format!("flow_counter[{flow_counter_id:#x}].octets"), stats.octets);
+            }
+        }
+
+        if counters.vport {
+            let out = cmdif.do_command(QueryVportCounter {
+                other_vport: false,
+                port_num: 0,
+                vport_number: 0,
+                clear: false,
+            })?;
+            for (name, counter) in out.named() {
+                values.insert(format!("vport.{name}.packets"), counter.packets);
+                values.insert(format!("vport.{name}.octets"), counter.octets);
+            }
+        }
+
+        Ok(Self { values })
+    }
+
+    // Counters that changed between self and later, counters missing from one side count as 0
+    pub fn diff(&self, later: &CounterSnapshot) -> Vec<CounterDelta> {
+        let mut names: Vec<&String> = self.values.keys().chain(later.values.keys()).collect();
+        names.sort();
+        names.dedup();
+
+        names
+            .into_iter()
+            .map(|name| CounterDelta {
+                name: name.clone(),
+                before: self.values.get(name).copied().unwrap_or(0),
+                after: later.values.get(name).copied().unwrap_or(0),
+            })
+            .filter(|delta| delta.before != delta.after)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counter_diff() {
+        let before = CounterSnapshot {
+            values: BTreeMap::from([
+                ("a".to_string(), 1),
+                ("b".to_string(), 5),
+                ("c".to_string(), 7),
+            ]),
+        };
+        let after = CounterSnapshot {
+            values: BTreeMap::from([
+                ("a".to_string(), 1),
+                ("b".to_string(), 9),
+                ("d".to_string(), 2),
+            ]),
+        };
+
+        let diff = before.diff(&after);
+        assert_eq!(diff, vec![
+            CounterDelta { name: "b".to_string(), before: 5, after: 9 },
+            CounterDelta { name: "c".to_string(), before: 7, after: 0 },
+            CounterDelta { name: "d".to_string(), before: 0, after: 2 },
+        ]);
+        assert_eq!(diff[0].delta(), 4);
+    }
+}
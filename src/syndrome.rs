@@ -0,0 +1,93 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use crate::error::Result;
+
+pub const SYNDROME_DB_ENV: &str = "MLX5_SYNDROME_DB";
+
+static SYNDROME_DB: OnceLock<SyndromeDb> = OnceLock::new();
+
+// Syndrome to message table, one "0x<syndrome> <message>" per line, '#' starts a comment
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SyndromeDb {
+    messages: HashMap<u32, String>,
+}
+
+impl SyndromeDb {
+    pub fn parse(text: &str) -> Self {
+        let mut messages = HashMap::new();
+
+        for (lineno, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let (syndrome, message) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+            match u32::from_str_radix(syndrome.trim_start_matches("0x"), 16) {
+                Ok(syndrome) => {
+                    messages.insert(syndrome, message.trim().to_string());
+                }
+                Err(_) => log::warn!("Ignoring syndrome database line {}: {line}", lineno + 1),
+            }
+        }
+
+        Self { messages }
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(Self::parse(&std::fs::read_to_string(path)?))
+    }
+
+    pub fn lookup(&self, syndrome: u32) -> Option<&str> {
+        self.messages.get(&syndrome).map(String::as_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.messages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+}
+
+// Installs the process wide database, fails if one was already installed or loaded
+pub fn install(db: SyndromeDb) -> bool {
+    SYNDROME_DB.set(db).is_ok()
+}
+
+// Looks up a syndrome in the installed database, or the one named by MLX5_SYNDROME_DB
+pub fn lookup(syndrome: u32) -> Option<&'static str> {
+    SYNDROME_DB
+        .get_or_init(|| match std::env::var_os(SYNDROME_DB_ENV) {
+            Some(path) => SyndromeDb::load(&path).unwrap_or_else(|err| {
+                log::warn!("Could not load syndrome database {path:?}: {err}");
+                SyndromeDb::default()
+            }),
+            None => SyndromeDb::default(),
+        })
+        .lookup(syndrome)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_syndrome_db() {
+        let db = SyndromeDb::parse(
+            "# comment\n\
+             0x1b2f2 first message\n\
+             \n\
+             0x0aabbcc   second message   # trailing comment\n\
+             not-a-syndrome\n",
+        );
+
+        assert_eq!(db.len(), 2);
+        assert_eq!(db.lookup(0x1b2f2), Some("first message"));
+        assert_eq!(db.lookup(0xaabbcc), Some("second message"));
+        assert_eq!(db.lookup(0x1234), None);
+    }
+}
@@ -0,0 +1,722 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use pci_driver::regions::PciRegion;
+
+use crate::allocator::AllocationGuard;
+use crate::cmdif::vfio::VfioCmdIf;
+use crate::cmdif::CmdIf;
+use crate::commands::create_mkey::{create_mtt_mkey, DestroyMKey};
+use crate::commands::{AllocPD, DeallocPD};
+use crate::error::{Error, Result};
+use crate::registers::mtrc::{MtrcCapReg, MtrcConfReg, MtrcCtrlReg, MtrcStdbReg};
+
+pub const TRACE_EVENT_SIZE: usize = 8;
+pub const TIMESTAMP_EVENT_ID: u8 = 0xff;
+pub const TRACE_BLOCK_SIZE: usize = 0x100;
+
+const TRACE_MODE_MEMORY: u8 = 1;
+const TRACE_MKEY_KEY: u8 = 0x42;
+
+const STRING_DB_READ_SIZE: usize = 64;
+
+// Format strings referenced by string events, one section per string database at the base
+// address the firmware reports in MTRC_CAP.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StringDb {
+    sections: Vec<(u32, Vec<u8>)>,
+}
+
+impl StringDb {
+    pub fn new(sections: Vec<(u32, Vec<u8>)>) -> Self {
+        Self { sections }
+    }
+
+    pub fn read(cmdif: &impl CmdIf, cap: &MtrcCapReg) -> Result<Self> {
+        let mut sections = vec![];
+        for (index, param) in cap.string_db_param.iter().enumerate().take(cap.num_string_db as usize) {
+            let mut data = Vec::with_capacity(param.size as usize);
+            while data.len() < param.size as usize {
+                let stdb = cmdif.read_register(
+                    MtrcStdbReg {
+                        index: index as u8,
+                        size: STRING_DB_READ_SIZE as u32,
+                        offset: data.len() as u32,
+                        ..Default::default()
+                    },
+                    0,
+                )?;
+                let remaining = param.size as usize - data.len();
+                data.extend_from_slice(&stdb.data[..remaining.min(STRING_DB_READ_SIZE)]);
+            }
+            log::debug!("String database {index}: {:#x} bytes at {:#x}", data.len(), param.address);
+            sections.push((param.address, data));
+        }
+        Ok(Self { sections })
+    }
+
+    pub fn lookup(&self, address: u32) -> Option<&str> {
+        let (base, data) = self
+            .sections
+            .iter()
+            .find(|(base, data)| address >= *base && ((address - base) as usize) < data.len())?;
+        let string = &data[(address - base) as usize..];
+        let end = string.iter().position(|&b| b == 0).unwrap_or(string.len());
+        std::str::from_utf8(&string[..end]).ok()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraceEvent {
+    Timestamp {
+        timestamp: u64,
+        unreliable: bool,
+    },
+    String {
+        lost: bool,
+        timestamp: u8,
+        event_id: u8,
+        tmsn: u16,
+        tdsn: u8,
+        string_param: u32,
+    },
+    Unknown {
+        lost: bool,
+        event_id: u8,
+        data: u64,
+    },
+}
+
+// The parts of MTRC_CAP needed to tell events apart
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TraceFormat {
+    pub trc_ver: u8,
+    pub first_string_trace: u8,
+    pub num_string_trace: u8,
+}
+
+impl From<&MtrcCapReg> for TraceFormat {
+    fn from(cap: &MtrcCapReg) -> Self {
+        Self {
+            trc_ver: cap.trc_ver,
+            first_string_trace: cap.first_string_trace,
+            num_string_trace: cap.num_string_trace,
+        }
+    }
+}
+
+impl TraceEvent {
+    // Events are big endian 64 bit words
+    pub fn parse(raw: u64, format: &TraceFormat) -> Self {
+        let lost = raw >> 63 != 0;
+        let event_id = (raw >> 48) as u8;
+
+        if event_id == TIMESTAMP_EVENT_ID {
+            let urts = (raw >> 45) & 0x7;
+            return TraceEvent::Timestamp {
+                timestamp: ((raw >> 32) & 0x1fff) << 40 | (raw & 0xffffffff) << 8 | raw >> 56,
+                unreliable: if format.trc_ver == 0 { urts >> 2 != 0 } else { urts & 1 != 0 },
+            };
+        }
+
+        let first = format.first_string_trace as u16;
+        if (first..first + format.num_string_trace as u16).contains(&(event_id as u16)) {
+            return TraceEvent::String {
+                lost,
+                timestamp: (raw >> 56) as u8 & 0x7f,
+                event_id,
+                tmsn: ((raw >> 35) & 0x1fff) as u16,
+                tdsn: ((raw >> 32) & 0x7) as u8,
+                string_param: raw as u32,
+            };
+        }
+
+        TraceEvent::Unknown {
+            lost,
+            event_id,
+            data: raw & 0xffff_ffff_ffff,
+        }
+    }
+
+    // Zero words are parts of a block the firmware has not written
+    pub fn parse_buffer<'a>(buffer: &'a [u8], format: &'a TraceFormat) -> impl Iterator<Item = TraceEvent> + 'a {
+        buffer
+            .chunks_exact(TRACE_EVENT_SIZE)
+            .map(|event| u64::from_be_bytes(event.try_into().unwrap()))
+            .filter(|raw| *raw != 0)
+            .map(|raw| TraceEvent::parse(raw, format))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceMessage {
+    pub timestamp: u64,
+    pub lost: bool,
+    pub event_id: u8,
+    pub message: String,
+}
+
+// Same layout as the kernel's mlx5_fw trace point
+impl fmt::Display for TraceMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{:#x}] {} [{:#x}] {}", self.timestamp, self.lost as u8, self.event_id, self.message)
+    }
+}
+
+#[derive(Debug)]
+struct PendingMessage {
+    timestamp: u64,
+    lost: bool,
+    event_id: u8,
+    format: String,
+    num_params: usize,
+    params: Vec<u32>,
+}
+
+impl PendingMessage {
+    fn finish(self) -> TraceMessage {
+        TraceMessage {
+            timestamp: self.timestamp,
+            lost: self.lost,
+            event_id: self.event_id,
+            message: format_message(&self.format, &self.params),
+        }
+    }
+}
+
+// Turns trace events into messages. A message starts with a tdsn 0 event pointing at its
+// format string and collects one parameter from each following event with the same tmsn.
+#[derive(Debug)]
+pub struct TraceDecoder {
+    strings: StringDb,
+    format: TraceFormat,
+    last_timestamp: u64,
+    pending: HashMap<u16, PendingMessage>,
+}
+
+impl TraceDecoder {
+    pub fn new(strings: StringDb, format: TraceFormat) -> Self {
+        Self {
+            strings,
+            format,
+            last_timestamp: 0,
+            pending: HashMap::new(),
+        }
+    }
+
+    pub fn format(&self) -> &TraceFormat {
+        &self.format
+    }
+
+    pub fn last_timestamp(&self) -> u64 {
+        self.last_timestamp
+    }
+
+    pub fn set_timestamp(&mut self, timestamp: u64) {
+        self.last_timestamp = timestamp;
+    }
+
+    // String events only carry the low 7 bits of the timestamp
+    fn expand_timestamp(&self, timestamp: u8) -> u64 {
+        let base = self.last_timestamp & !0x7f;
+        if self.last_timestamp & 0x7f <= timestamp as u64 {
+            base | timestamp as u64
+        } else {
+            (base + 0x80) | timestamp as u64
+        }
+    }
+
+    pub fn push(&mut self, event: TraceEvent) -> Option<TraceMessage> {
+        match event {
+            TraceEvent::Timestamp { timestamp, .. } => {
+                self.last_timestamp = timestamp;
+                None
+            }
+            TraceEvent::String {
+                lost,
+                timestamp,
+                event_id,
+                tmsn,
+                tdsn: 0,
+                string_param,
+            } => {
+                let timestamp = self.expand_timestamp(timestamp);
+                let Some(format) = self.strings.lookup(string_param) else {
+                    return Some(TraceMessage {
+                        timestamp,
+                        lost,
+                        event_id,
+                        message: format!("Unknown format string {string_param:#x}"),
+                    });
+                };
+                let message = PendingMessage {
+                    timestamp,
+                    lost,
+                    event_id,
+                    format: format.to_string(),
+                    num_params: count_params(format),
+                    params: vec![],
+                };
+                if message.num_params == 0 {
+                    return Some(message.finish());
+                }
+                // An unfinished message with the same tmsn lost its remaining parameters
+                self.pending.insert(tmsn, message).map(PendingMessage::finish)
+            }
+            TraceEvent::String {
+                lost,
+                tmsn,
+                string_param,
+                ..
+            } => {
+                let message = self.pending.get_mut(&tmsn)?;
+                message.lost |= lost;
+                message.params.push(string_param);
+                if message.params.len() < message.num_params {
+                    return None;
+                }
+                self.pending.remove(&tmsn).map(PendingMessage::finish)
+            }
+            // Dropped like the kernel's tracer does
+            TraceEvent::Unknown { lost, event_id, data } => {
+                log::trace!("Unrecognized trace event {event_id:#x} {data:#014x} lost {lost}");
+                None
+            }
+        }
+    }
+
+    pub fn decode(&mut self, buffer: &[u8]) -> Vec<TraceMessage> {
+        let format = self.format;
+        TraceEvent::parse_buffer(buffer, &format)
+            .filter_map(|event| self.push(event))
+            .collect()
+    }
+
+    // Messages still waiting for parameters, rendered with what they have
+    pub fn flush(&mut self) -> Vec<TraceMessage> {
+        let mut messages: Vec<_> = self.pending.drain().map(|(_, message)| message.finish()).collect();
+        messages.sort_by_key(|message| message.timestamp);
+        messages
+    }
+}
+
+// PD and mkey the trace ring is mapped through. Released by FwTracer::stop, or when dropped on
+// the error paths.
+struct TraceMkey<'a> {
+    cmdif: &'a VfioCmdIf,
+    pd: Option<u32>,
+    mkey_index: Option<u32>,
+}
+
+impl TraceMkey<'_> {
+    fn release(&mut self) -> Result<()> {
+        if let Some(mkey_index) = self.mkey_index.take() {
+            self.cmdif.do_command(DestroyMKey { mkey_index })?;
+        }
+        if let Some(pd) = self.pd.take() {
+            self.cmdif.do_command(DeallocPD { pd })?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for TraceMkey<'_> {
+    fn drop(&mut self) {
+        if let Err(err) = self.release() {
+            log::warn!("Could not release the trace buffer mkey: {err}");
+        }
+    }
+}
+
+// Firmware tracer owned by this function. The firmware fills a ring of 256 byte blocks, each
+// closed by a timestamp event, so a block is new once its timestamp passes the last one seen.
+pub struct FwTracer<'a> {
+    cmdif: &'a VfioCmdIf,
+    // Declared before memory so the mkey is gone before its pages are freed
+    mkey: TraceMkey<'a>,
+    memory: AllocationGuard,
+    decoder: TraceDecoder,
+    block: usize,
+    block_timestamp: u64,
+}
+
+impl<'a> FwTracer<'a> {
+    // Takes ownership of the tracer and points it at a fresh ring of 1 << log_pages pages
+    pub fn start(cmdif: &'a VfioCmdIf, log_pages: u8) -> Result<Self> {
+        cmdif.write_register(
+            MtrcCapReg {
+                trace_owner: true,
+                ..Default::default()
+            },
+            0,
+        )?;
+        let cap = cmdif.read_register(MtrcCapReg::default(), 0)?;
+        if !cap.trace_owner {
+            return Err(Error::Tracer("ownership was not granted"));
+        }
+        if !cap.trace_to_memory {
+            return Err(Error::Tracer("tracing to memory is not supported"));
+        }
+        if log_pages > cap.log_max_trace_buffer_size {
+            return Err(Error::Tracer("trace buffer is larger than supported"));
+        }
+
+        let strings = StringDb::read(cmdif, &cap)?;
+
+        let memory = cmdif.dma_allocator.alloc(1 << log_pages).ok_or(Error::OutOfMemory)?;
+        memory.write_bytes(0, &vec![0u8; memory.len() as usize])?;
+        let pd = cmdif.do_command(AllocPD {})?.pd;
+        let mut mkey = TraceMkey {
+            cmdif,
+            pd: Some(pd),
+            mkey_index: None,
+        };
+        let mkey_index = cmdif
+            .do_command(create_mtt_mkey(pd, TRACE_MKEY_KEY, memory.page_addresses()))?
+            .mkey_index;
+        mkey.mkey_index = Some(mkey_index);
+
+        cmdif.write_register(
+            MtrcConfReg {
+                trace_mode: TRACE_MODE_MEMORY,
+                log_trace_buffer_size: log_pages,
+                trace_mkey: (mkey_index << 8) | TRACE_MKEY_KEY as u32,
+            },
+            0,
+        )?;
+
+        let mut tracer = Self {
+            cmdif,
+            mkey,
+            memory,
+            decoder: TraceDecoder::new(strings, TraceFormat::from(&cap)),
+            block: 0,
+            block_timestamp: 0,
+        };
+        let timestamp = tracer.device_timestamp()?;
+        tracer.decoder.set_timestamp(timestamp);
+        tracer.set_ctrl(1, true)?;
+        Ok(tracer)
+    }
+
+    fn set_ctrl(&self, trace_status: u8, arm_event: bool) -> Result<()> {
+        self.cmdif.write_register(
+            MtrcCtrlReg {
+                trace_status,
+                arm_event,
+                modify_field_select: 1,
+                ..Default::default()
+            },
+            0,
+        )?;
+        Ok(())
+    }
+
+    pub fn device_timestamp(&self) -> Result<u64> {
+        let ctrl = self.cmdif.read_register(MtrcCtrlReg::default(), 0)?;
+        Ok((ctrl.timestamp_hi as u64) << 32 | ctrl.timestamp_lo as u64)
+    }
+
+    pub fn num_blocks(&self) -> usize {
+        self.memory.len() as usize / TRACE_BLOCK_SIZE
+    }
+
+    // Decodes the blocks written since the last poll and re-arms the tracer
+    pub fn poll(&mut self) -> Result<Vec<TraceMessage>> {
+        let num_blocks = self.num_blocks();
+        let mut messages = vec![];
+        let mut block = vec![0u8; TRACE_BLOCK_SIZE];
+        let mut processed = 0;
+
+        while processed < num_blocks {
+            self.memory.read_bytes((self.block * TRACE_BLOCK_SIZE) as u64, &mut block)?;
+            let last_event = u64::from_be_bytes(block[TRACE_BLOCK_SIZE - TRACE_EVENT_SIZE..].try_into().unwrap());
+            let timestamp = match TraceEvent::parse(last_event, self.decoder.format()) {
+                TraceEvent::Timestamp { timestamp, .. } if timestamp > self.block_timestamp => timestamp,
+                _ => break,
+            };
+
+            messages.extend(self.decoder.decode(&block));
+            self.block_timestamp = timestamp;
+            self.block = (self.block + 1) % num_blocks;
+            processed += 1;
+        }
+
+        if processed == num_blocks {
+            log::warn!("Trace ring filled up between polls, messages may have been overwritten");
+        }
+        log::trace!("Decoded {processed} trace blocks, next block {}", self.block);
+
+        self.set_ctrl(1, true)?;
+        Ok(messages)
+    }
+
+    // Stops tracing and gives up ownership, returning what was left in the ring including
+    // messages that never got all of their parameters
+    pub fn stop(mut self) -> Result<Vec<TraceMessage>> {
+        let mut messages = self.poll()?;
+        self.set_ctrl(0, false)?;
+        self.cmdif.write_register(MtrcCapReg::default(), 0)?;
+        self.mkey.release()?;
+        messages.extend(self.decoder.flush());
+        Ok(messages)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum FormatPiece<'a> {
+    Literal(&'a str),
+    Spec {
+        flags: &'a str,
+        width: usize,
+        long_long: bool,
+        conversion: char,
+    },
+}
+
+fn parse_format(format: &str) -> Vec<FormatPiece> {
+    let mut pieces = vec![];
+    let mut rest = format;
+
+    while let Some(start) = rest.find('%') {
+        if start > 0 {
+            pieces.push(FormatPiece::Literal(&rest[..start]));
+        }
+        let spec = &rest[start + 1..];
+        if let Some(spec) = spec.strip_prefix('%') {
+            pieces.push(FormatPiece::Literal("%"));
+            rest = spec;
+            continue;
+        }
+
+        let flags_len = spec.find(|c: char| !"-+ #0".contains(c)).unwrap_or(spec.len());
+        let (flags, spec) = spec.split_at(flags_len);
+        let width_len = spec.find(|c: char| !c.is_ascii_digit()).unwrap_or(spec.len());
+        let (width, spec) = spec.split_at(width_len);
+        let length_len = spec.find(|c: char| !"hlzjt".contains(c)).unwrap_or(spec.len());
+        let (length, spec) = spec.split_at(length_len);
+
+        let Some(conversion) = spec.chars().next() else {
+            pieces.push(FormatPiece::Literal(&rest[start..]));
+            rest = "";
+            break;
+        };
+        pieces.push(FormatPiece::Spec {
+            flags,
+            width: width.parse().unwrap_or(0),
+            long_long: length.matches('l').count() >= 2,
+            conversion,
+        });
+        rest = &spec[conversion.len_utf8()..];
+    }
+
+    if !rest.is_empty() {
+        pieces.push(FormatPiece::Literal(rest));
+    }
+    pieces
+}
+
+// %ll conversions take two parameters, high word first
+pub fn count_params(format: &str) -> usize {
+    parse_format(format)
+        .iter()
+        .map(|piece| match piece {
+            FormatPiece::Literal(_) => 0,
+            FormatPiece::Spec { long_long: true, .. } => 2,
+            FormatPiece::Spec { .. } => 1,
+        })
+        .sum()
+}
+
+pub fn format_message(format: &str, params: &[u32]) -> String {
+    let mut params = params.iter().copied();
+    let mut message = String::new();
+
+    for piece in parse_format(format) {
+        let (flags, width, long_long, conversion) = match piece {
+            FormatPiece::Literal(literal) => {
+                message.push_str(literal);
+                continue;
+            }
+            FormatPiece::Spec {
+                flags,
+                width,
+                long_long,
+                conversion,
+            } => (flags, width, long_long, conversion),
+        };
+
+        let value = match (long_long, params.next()) {
+            (false, Some(value)) => value as u64,
+            (true, Some(hi)) => match params.next() {
+                Some(lo) => (hi as u64) << 32 | lo as u64,
+                None => {
+                    message.push('?');
+                    continue;
+                }
+            },
+            (_, None) => {
+                message.push('?');
+                continue;
+            }
+        };
+
+        let alternate = flags.contains('#');
+        let (prefix, digits) = match conversion {
+            'd' | 'i' => {
+                let value = if long_long { value as i64 } else { value as u32 as i32 as i64 };
+                let sign = if value < 0 {
+                    "-"
+                } else if flags.contains('+') {
+                    "+"
+                } else {
+                    ""
+                };
+                (sign, value.unsigned_abs().to_string())
+            }
+            'u' => ("", value.to_string()),
+            'x' => (if alternate { "0x" } else { "" }, format!("{value:x}")),
+            'X' => (if alternate { "0X" } else { "" }, format!("{value:X}")),
+            'o' => (if alternate { "0" } else { "" }, format!("{value:o}")),
+            'p' => ("0x", format!("{value:x}")),
+            'c' => ("", char::from(value as u8).to_string()),
+            _ => ("0x", format!("{value:x}")),
+        };
+
+        let len = prefix.len() + digits.len();
+        let padding = width.saturating_sub(len);
+        if flags.contains('-') {
+            message.push_str(prefix);
+            message.push_str(&digits);
+            message.push_str(&" ".repeat(padding));
+        } else if flags.contains('0') {
+            message.push_str(prefix);
+            message.push_str(&"0".repeat(padding));
+            message.push_str(&digits);
+        } else {
+            message.push_str(&" ".repeat(padding));
+            message.push_str(prefix);
+            message.push_str(&digits);
+        }
+    }
+
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FORMAT: TraceFormat = TraceFormat {
+        trc_ver: 1,
+        first_string_trace: 0x10,
+        num_string_trace: 0x20,
+    };
+
+    fn string_event(timestamp: u8, event_id: u8, tmsn: u16, tdsn: u8, param: u32) -> u64 {
+        (timestamp as u64) << 56 | (event_id as u64) << 48 | (tmsn as u64) << 35 | (tdsn as u64) << 32 | param as u64
+    }
+
+    #[test]
+    fn test_parse_events() {
+        assert_eq!(
+            TraceEvent::parse(0x34ff_2123_89ab_cdef, &FORMAT),
+            TraceEvent::Timestamp {
+                timestamp: 0x0123_89ab_cdef_34,
+                unreliable: true,
+            }
+        );
+        assert_eq!(
+            TraceEvent::parse(string_event(0x85, 0x12, 0x1abc, 3, 0xdeadbeef), &FORMAT),
+            TraceEvent::String {
+                lost: true,
+                timestamp: 0x05,
+                event_id: 0x12,
+                tmsn: 0x1abc,
+                tdsn: 3,
+                string_param: 0xdeadbeef,
+            }
+        );
+        assert_eq!(
+            TraceEvent::parse(0x0042_0000_1234_5678, &FORMAT),
+            TraceEvent::Unknown {
+                lost: false,
+                event_id: 0x42,
+                data: 0x1234_5678,
+            }
+        );
+    }
+
+    #[test]
+    fn test_format_message() {
+        assert_eq!(count_params("no params 100%%"), 0);
+        assert_eq!(count_params("%d %llx %08x"), 4);
+        assert_eq!(format_message("no params 100%%", &[]), "no params 100%");
+        assert_eq!(format_message("%d %u %x", &[0xffffffff, 0xffffffff, 0xabc]), "-1 4294967295 abc");
+        assert_eq!(format_message("%08x|%-4d|%4d|%#x", &[0x1234, 7, 7, 0x10]), "00001234|7   |   7|0x10");
+        assert_eq!(format_message("%llx", &[0x1, 0x2]), "100000002");
+        assert_eq!(format_message("%d %d", &[1]), "1 ?");
+    }
+
+    #[test]
+    fn test_decode_messages() {
+        let strings = StringDb::new(vec![(0x1000, b"first\0value %d and %llx\0".to_vec())]);
+        let mut decoder = TraceDecoder::new(strings, FORMAT);
+
+        let events = [
+            0x10ff_0000_0000_0001,
+            string_event(0x20, 0x10, 1, 0, 0x1000),
+            string_event(0x21, 0x11, 2, 0, 0x1006),
+            string_event(0x22, 0x11, 2, 1, 42),
+            0x00ff_0000_0000_0002,
+            string_event(0x23, 0x11, 2, 2, 0x1),
+            string_event(0x24, 0x11, 2, 3, 0x2),
+            string_event(0x05, 0x10, 1, 0, 0x2000),
+        ];
+        let buffer: Vec<u8> = events.iter().flat_map(|event| event.to_be_bytes()).collect();
+
+        let messages = decoder.decode(&buffer);
+
+        assert_eq!(
+            messages,
+            vec![
+                TraceMessage {
+                    timestamp: 0x120,
+                    lost: false,
+                    event_id: 0x10,
+                    message: "first".to_string(),
+                },
+                TraceMessage {
+                    timestamp: 0x121,
+                    lost: false,
+                    event_id: 0x11,
+                    message: "value 42 and 100000002".to_string(),
+                },
+                TraceMessage {
+                    timestamp: 0x205,
+                    lost: false,
+                    event_id: 0x10,
+                    message: "Unknown format string 0x2000".to_string(),
+                },
+            ]
+        );
+        assert_eq!(messages[1].to_string(), "[0x121] 0 [0x11] value 42 and 100000002");
+        assert!(decoder.flush().is_empty());
+    }
+
+    #[test]
+    fn test_decode_unwritten_block() {
+        // A zero word would be a string event with event id 0 here
+        let format = TraceFormat {
+            first_string_trace: 0,
+            ..FORMAT
+        };
+        let mut decoder = TraceDecoder::new(StringDb::new(vec![]), format);
+        assert!(decoder.decode(&[0u8; TRACE_BLOCK_SIZE]).is_empty());
+
+        let mut decoder = TraceDecoder::new(StringDb::new(vec![]), FORMAT);
+        let unknown = 0x0042_0000_1234_5678u64.to_be_bytes();
+        assert!(decoder.decode(&unknown).is_empty());
+        assert!(decoder.flush().is_empty());
+    }
+}